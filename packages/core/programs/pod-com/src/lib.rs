@@ -7,6 +7,9 @@ use anchor_lang::solana_program::pubkey::Pubkey;
 // Native Solana State Compression imports - 2025 approach
 use spl_account_compression::{
     program::SplAccountCompression,
+    state::{merkle_tree_get_size, ConcurrentMerkleTreeHeader, CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1},
+    zero_copy::ZeroCopy,
+    ConcurrentMerkleTree, Noop,
 };
 
 // Cryptographic operations - native Solana compatible
@@ -82,6 +85,20 @@ impl Drop for SecureBuffer {
     }
 }
 
+/// Domain separator for direct-message delivery receipts
+pub const MESSAGE_RECEIPT_DOMAIN: &[u8] = b"PoD-Protocol-Message-Receipt-v1";
+
+/// Bytes a recipient signs (ed25519) to acknowledge a direct message:
+/// domain || message account || payload_hash || status
+pub fn message_receipt_bytes(message: &Pubkey, payload_hash: &[u8; 32], status: &MessageStatus) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MESSAGE_RECEIPT_DOMAIN.len() + 32 + 32 + 1);
+    bytes.extend_from_slice(MESSAGE_RECEIPT_DOMAIN);
+    bytes.extend_from_slice(message.as_ref());
+    bytes.extend_from_slice(payload_hash);
+    bytes.push(status.clone() as u8);
+    bytes
+}
+
 /// Secure hash computation wrapper
 pub fn secure_hash_data(data: &[u8]) -> Result<[u8; 32]> {
     // Use secure buffer for intermediate hash computations
@@ -135,14 +152,58 @@ pub fn secure_hash_data(data: &[u8]) -> Result<[u8; 32]> {
 
 // Constants
 const MAX_METADATA_URI_LENGTH: usize = 200; // Maximum length of metadata URI
+const MAX_AGENT_NAME_LENGTH: usize = 32; // Agent names are PDA seeds (32-byte seed limit)
 const MESSAGE_EXPIRATION_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
+const CLOSE_EXPIRED_MESSAGE_REWARD: u64 = 5_000; // Lamports paid to whoever closes an expired message
 const MAX_CHANNEL_NAME_LENGTH: usize = 50; // Maximum channel name length
 const MAX_CHANNEL_DESCRIPTION_LENGTH: usize = 200; // Maximum channel description length
 const MAX_PARTICIPANTS_PER_CHANNEL: u32 = 1000; // Maximum participants in a channel
 const MAX_MESSAGE_CONTENT_LENGTH: usize = 1000; // Maximum message content length
 const RATE_LIMIT_MESSAGES_PER_MINUTE: u16 = 60; // Rate limit for messages
 const INVITE_RATE_LIMIT_PER_HOUR: u16 = 20; // Maximum invitations per hour
+const DEFAULT_MESSAGE_EDIT_WINDOW_SECONDS: u32 = 15 * 60; // Channel messages editable for 15 minutes
+const DEFAULT_MAX_INVITATION_EXPIRY_SECONDS: u32 = 30 * 24 * 60 * 60; // Invitations live at most 30 days
+const MAX_INVITE_CODE_USES: u32 = 1_000; // Cap on agents admitted by a single invite code
+const MAX_SESSION_KEY_DURATION_SECONDS: i64 = 30 * 24 * 60 * 60; // Session keys live at most 30 days
 const MIN_REPUTATION_FOR_CHANNELS: u64 = 50; // Minimum reputation to create channels
+const MAX_FEE_PER_MESSAGE: u64 = 1_000_000_000; // Max 1 SOL per message
+const MAX_ESCROW_DEPOSIT: u64 = 10_000_000_000; // Max 10 SOL per escrow deposit
+const MAX_PROTOCOL_FEE_BPS: u16 = 1_000; // Protocol fee capped at 10% of claimed channel fees
+const MAX_REPUTATION: u64 = 1000; // Reputation ceiling
+const REPUTATION_EPOCH_SECONDS: i64 = 24 * 60 * 60; // Reputation deltas are bounded per day
+const MAX_REPUTATION_GAIN_PER_EPOCH: i16 = 20; // Cap on positive change per epoch
+const MAX_REPUTATION_LOSS_PER_EPOCH: i16 = 50; // Cap on negative change per epoch
+const REPUTATION_ESCROW_COMPLETED: i16 = 5; // Payee completed an agent escrow
+const REPUTATION_DISPUTE_LOST: i16 = -25; // Agent lost an escrow dispute
+const REPUTATION_MESSAGE_READ: i16 = 1; // Recipient marked a direct message as read
+const REPUTATION_INVITATION_ACCEPTED: i16 = 2; // Invitee accepted a channel invitation
+const MESSAGE_TREE_MAX_DEPTH: usize = 20; // Up to ~1M compressed messages per channel
+const MESSAGE_TREE_MAX_BUFFER_SIZE: usize = 64; // Concurrent changelog buffer for message tree
+const PARTICIPANT_TREE_MAX_DEPTH: usize = 14; // Room for membership churn (leave + rejoin appends)
+const PARTICIPANT_TREE_MAX_BUFFER_SIZE: usize = 64; // Concurrent changelog buffer for participant tree
+const MAX_ESCROW_MILESTONES: usize = 8; // Maximum milestones per agent escrow
+const MAX_AGENT_ESCROW_DURATION_SECONDS: i64 = 365 * 24 * 60 * 60; // 1 year
+const MAX_ARBITRATORS: usize = 5; // Maximum arbitrators in an m-of-n set
+const MAX_EVIDENCE_CID_LENGTH: usize = 100; // Maximum IPFS CID length for dispute evidence
+const ESCROW_DISPUTE_TIMEOUT_SECONDS: i64 = 30 * 24 * 60 * 60; // Parties may settle an undecided dispute after 30 days
+const MAX_KEY_BUNDLE_CID_LENGTH: usize = 100; // Maximum IPFS CID length for a channel key bundle
+const BASIS_POINTS_DENOMINATOR: u16 = 10_000; // 100% in basis points
+
+// Number of reaction kinds tracked per channel message (see ReactionKind)
+pub const REACTION_KIND_COUNT: usize = 8;
+
+// Latest direct messages kept in each recipient's inbox ring buffer (see AgentInbox)
+pub const INBOX_CAPACITY: usize = 16;
+// Direct messages a sender agent may write per hour; half a ring, so no single sender can
+// cycle a recipient's inbox
+const DIRECT_MESSAGE_RATE_LIMIT_PER_HOUR: u16 = (INBOX_CAPACITY / 2) as u16;
+
+// Session key permission bits
+pub const SESSION_PERMISSION_SEND_MESSAGE: u8 = 1 << 0; // Direct messages (send_message)
+pub const SESSION_PERMISSION_BROADCAST: u8 = 1 << 1; // Channel messages (broadcast_message[_compressed])
+pub const SESSION_PERMISSION_EDIT_MESSAGE: u8 = 1 << 2; // edit_channel_message
+pub const SESSION_PERMISSION_ALL: u8 =
+    SESSION_PERMISSION_SEND_MESSAGE | SESSION_PERMISSION_BROADCAST | SESSION_PERMISSION_EDIT_MESSAGE;
 
 // Account Space Constants with optimized struct packing (PERF-02)
 // All structs use #[repr(C)] for consistent memory layout and optimal performance
@@ -152,15 +213,30 @@ const AGENT_ACCOUNT_SPACE: usize = 8
     + 8  // reputation
     + 8  // last_updated
     + (4 + MAX_METADATA_URI_LENGTH) // metadata_uri
+    + (4 + MAX_AGENT_NAME_LENGTH) // name
     + 2  // invites_sent
     + 8  // last_invite_at
     + 1  // bump
-    + 7; // _reserved - 286 bytes (optimized layout)
-const MESSAGE_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 5; // 128 bytes (optimized layout)
+    + 4  // reputation_epoch
+    + 2  // epoch_reputation_delta
+    + 2  // active_escrows
+    + 32 // pending_owner
+    + 2  // direct_messages_sent
+    + 8  // last_direct_message_at
+    + 1; // _reserved - 366 bytes (optimized layout)
+const AGENT_TOMBSTONE_SPACE: usize = 8 + 32 + 8 + 8 + 4 + 2 + 1 + 1; // 64 bytes
+const MESSAGE_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 12 + 1 + 32; // 248 bytes (optimized layout)
+const AGENT_INBOX_SPACE: usize = 8
+    + 32 // owner
+    + 8  // total_received
+    + (48 * INBOX_CAPACITY) // entries
+    + 1  // bump
+    + 7; // _reserved - 824 bytes
 const CHANNEL_ACCOUNT_SPACE: usize = 8
     + 32 // creator
     + 8  // fee_per_message
     + 8  // escrow_balance
+    + 8  // accrued_fees
     + 8  // created_at
     + 4  // max_participants
     + 4  // current_participants
@@ -168,13 +244,80 @@ const CHANNEL_ACCOUNT_SPACE: usize = 8
     + (4 + MAX_CHANNEL_DESCRIPTION_LENGTH) // description
     + 1  // visibility
     + 1  // is_active
+    + 8  // last_sync_timestamp
+    + 8  // total_compressed_messages
+    + 8  // compressed_data_size
+    + 32 // message_tree
+    + 32 // message_tree_root
+    + 32 // participant_tree
+    + 32 // participant_tree_root
+    + (4 + MAX_KEY_BUNDLE_CID_LENGTH) // key_bundle_cid
+    + 4  // key_epoch
+    + 1  // key_rotation_pending
     + 1  // bump
-    + 5; // _reserved - 333 bytes (optimized layout)
-const CHANNEL_PARTICIPANT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 6; // 104 bytes (optimized layout)
+    + 4; // total_synced_messages - 606 bytes (optimized layout)
+const CHANNEL_PARTICIPANT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 1 + 3; // 104 bytes (optimized layout)
+const COMPRESSED_MEMBERSHIP_SPACE: usize = 8 + 32 + 32 + 8 + 4 + 1 + 3; // 88 bytes
 const CHANNEL_INVITATION_SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 5; // 168 bytes (optimized layout)
+const CHANNEL_INVITE_CODE_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 4 + 4 + 1 + 7; // 136 bytes
+const SESSION_KEY_SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 6; // 176 bytes
 const CHANNEL_MESSAGE_SPACE: usize =
-    8 + 32 + 32 + 33 + 8 + 9 + (4 + MAX_MESSAGE_CONTENT_LENGTH) + 1 + 1 + 6; // 1134 bytes (optimized layout)
-const ESCROW_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 7; // 96 bytes (already optimal)
+    8 + 32 + 32 + 32 + 33 + 8 + 9 + (4 + MAX_MESSAGE_CONTENT_LENGTH) + 1 + 1 + 4 + 4 + (2 * REACTION_KIND_COUNT) + 2; // 1186 bytes (optimized layout)
+const MESSAGE_REACTION_SPACE: usize = 8 + 32 + 32 + 8 + 1 + 1 + 6; // 88 bytes
+const ESCROW_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 7; // 104 bytes (already optimal)
+const PROTOCOL_CONFIG_SPACE: usize = 8
+    + 32 // authority
+    + 32 // pending_authority
+    + 32 // fee_recipient
+    + 8  // min_reputation_for_channels
+    + 8  // max_fee_per_message
+    + 8  // max_escrow_deposit
+    + 8  // updated_at
+    + 2  // rate_limit_messages_per_minute
+    + 2  // invite_rate_limit_per_hour
+    + 2  // protocol_fee_bps
+    + 1  // is_paused
+    + 1  // bump
+    + 4  // message_edit_window_seconds
+    + 4  // max_invitation_expiry_seconds
+    + 4; // _reserved - 156 bytes
+const AGENT_ESCROW_ACCOUNT_SPACE: usize = 8
+    + 32 // payer
+    + 32 // payee
+    + 32 // payer_wallet
+    + 32 // payee_wallet
+    + 32 // escrow_id
+    + 8  // amount
+    + 8  // released_amount
+    + 8  // created_at
+    + 8  // expires_at
+    + 32 // arbitrator_set
+    + (9 * MAX_ESCROW_MILESTONES) // milestones
+    + 1  // milestone_count
+    + 1  // status
+    + 1  // bump
+    + 5; // _reserved - 312 bytes
+const ARBITRATOR_SET_SPACE: usize = 8
+    + 32 // authority
+    + (32 * MAX_ARBITRATORS) // arbitrators
+    + 8  // set_id
+    + 1  // arbitrator_count
+    + 1  // threshold
+    + 1  // bump
+    + 5; // _reserved - 216 bytes
+const ESCROW_DISPUTE_SPACE: usize = 8
+    + 32 // escrow
+    + 32 // arbitrator_set
+    + 32 // opened_by
+    + 32 // reason_hash
+    + 8  // opened_at
+    + 8  // resolved_at
+    + (2 * MAX_ARBITRATORS) // votes (payee share in basis points)
+    + 2  // evidence_count
+    + 1  // voted_mask
+    + 1  // is_resolved
+    + 1  // bump
+    + 3; // _reserved - 170 bytes
 
 // Error codes
 #[error_code]
@@ -215,6 +358,96 @@ pub enum PodComError {
     InvalidTimestamp,
     #[msg("Invalid message hash")]
     InvalidMessageHash,
+    #[msg("Invalid Merkle tree account")]
+    InvalidMerkleTree,
+    #[msg("Merkle tree already initialized")]
+    MerkleTreeAlreadyInitialized,
+    #[msg("Merkle tree not initialized")]
+    MerkleTreeNotInitialized,
+    #[msg("Invalid compressed participant data")]
+    InvalidCompressedParticipant,
+    #[msg("Escrow is not active")]
+    EscrowNotActive,
+    #[msg("Escrow timeout has not elapsed")]
+    EscrowNotExpired,
+    #[msg("Invalid escrow timeout")]
+    InvalidEscrowTimeout,
+    #[msg("Invalid escrow milestone")]
+    InvalidEscrowMilestone,
+    #[msg("Invalid arbitrator set")]
+    InvalidArbitratorSet,
+    #[msg("Signer is not an arbitrator for this escrow")]
+    NotArbitrator,
+    #[msg("Arbitrator has already voted on this dispute")]
+    ArbitratorAlreadyVoted,
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    #[msg("Invalid dispute resolution")]
+    InvalidDisputeResolution,
+    #[msg("Invalid protocol configuration")]
+    InvalidProtocolConfig,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Channel role does not permit this action")]
+    InsufficientChannelRole,
+    #[msg("Participant is muted in this channel")]
+    ParticipantMuted,
+    #[msg("Message can no longer be edited")]
+    MessageEditWindowExpired,
+    #[msg("Message has not expired yet")]
+    MessageNotExpired,
+    #[msg("Account is still in use and cannot be closed")]
+    AccountNotClosable,
+    #[msg("Invitation expiry is outside the allowed range")]
+    InvalidInvitationExpiry,
+    #[msg("Invitation has already been used")]
+    InvitationAlreadyUsed,
+    #[msg("Invite code does not match")]
+    InvalidInviteCode,
+    #[msg("Invite code has no uses left")]
+    InviteCodeExhausted,
+    #[msg("Agent still has active escrows")]
+    AgentHasActiveEscrows,
+    #[msg("Agent name must be 1-32 bytes")]
+    InvalidAgentName,
+    #[msg("Invalid session key parameters")]
+    InvalidSessionKey,
+    #[msg("Session key has expired")]
+    SessionKeyExpired,
+    #[msg("Session key does not permit this action")]
+    SessionKeyPermissionDenied,
+    #[msg("Session key spend cap exceeded")]
+    SessionKeySpendCapExceeded,
+    #[msg("Invalid encrypted message envelope")]
+    InvalidMessageEnvelope,
+    #[msg("Channel key epoch must advance by exactly one")]
+    InvalidKeyEpoch,
+    #[msg("Invalid channel key bundle CID")]
+    InvalidKeyBundle,
+    #[msg("Reply target is not a message in this channel")]
+    InvalidReplyTarget,
+    #[msg("Reaction counter is full")]
+    ReactionLimitReached,
+    #[msg("Missing or invalid signed delivery receipt")]
+    InvalidDeliveryReceipt,
+    #[msg("Channel name must be non-empty without leading or trailing whitespace")]
+    InvalidChannelName,
+    #[msg("Channel name is part of the channel address and cannot change")]
+    ChannelNameImmutable,
+    #[msg("Kicked, muted or read-only participants cannot close their membership")]
+    ParticipantUnderModeration,
+    #[msg("Recipient inbox is missing or did not index this message")]
+    InvalidInbox,
+    #[msg("Channel key must be rotated before posting to this private channel")]
+    ChannelKeyRotationPending,
+    #[msg("Invitation has expired")]
+    InvitationExpired,
+    #[msg("Invite code has expired")]
+    InviteCodeExpired,
+    #[msg("Invite code max uses must be between 1 and the protocol limit")]
+    InvalidInviteCodeMaxUses,
+    #[msg("Account is not in a legacy layout that can be migrated")]
+    AccountNotMigratable,
 }
 
 // Message types
@@ -227,6 +460,37 @@ pub enum MessageType {
     Custom(u8),
 }
 
+// Reactions an agent can leave on a channel message
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReactionKind {
+    ThumbsUp,
+    ThumbsDown,
+    Heart,
+    Laugh,
+    Celebrate,
+    Surprised,
+    Sad,
+    Rocket,
+}
+
+impl ReactionKind {
+    pub const ALL: [ReactionKind; REACTION_KIND_COUNT] = [
+        ReactionKind::ThumbsUp,
+        ReactionKind::ThumbsDown,
+        ReactionKind::Heart,
+        ReactionKind::Laugh,
+        ReactionKind::Celebrate,
+        ReactionKind::Surprised,
+        ReactionKind::Sad,
+        ReactionKind::Rocket,
+    ];
+
+    // Slot in ChannelMessage.reaction_counts, also used as the reaction PDA seed
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
 // Message status
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
 pub enum MessageStatus {
@@ -234,6 +498,36 @@ pub enum MessageStatus {
     Delivered,
     Read,
     Failed,
+    Expired,
+}
+
+impl MessageStatus {
+    // Direct-message state machine; pod-sdk-types::MessageStatus mirrors this table:
+    // Pending -> Delivered -> Read, Pending/Delivered -> Failed, Pending/Delivered -> Expired
+    pub fn can_transition_to(&self, next: &MessageStatus) -> bool {
+        matches!(
+            (self, next),
+            (MessageStatus::Pending, MessageStatus::Delivered)
+                | (MessageStatus::Delivered, MessageStatus::Read)
+                | (
+                    MessageStatus::Pending | MessageStatus::Delivered,
+                    MessageStatus::Failed | MessageStatus::Expired
+                )
+        )
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, MessageStatus::Read | MessageStatus::Failed | MessageStatus::Expired)
+    }
+}
+
+// Protocol activity that changes an agent's reputation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReputationReason {
+    EscrowCompleted,
+    DisputeLost,
+    MessageRead,
+    InvitationAccepted,
 }
 
 // Channel visibility
@@ -243,10 +537,71 @@ pub enum ChannelVisibility {
     Private,
 }
 
+// Participant role inside a channel; the channel creator outranks every role
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelRole {
+    Member,
+    ReadOnly,
+    Moderator,
+    Admin,
+}
+
+impl ChannelRole {
+    // Ordering used to decide who may moderate whom
+    pub fn rank(&self) -> u8 {
+        match self {
+            ChannelRole::ReadOnly => 0,
+            ChannelRole::Member => 1,
+            ChannelRole::Moderator => 2,
+            ChannelRole::Admin => 3,
+        }
+    }
+
+    pub fn can_send_messages(&self) -> bool {
+        !matches!(self, ChannelRole::ReadOnly)
+    }
+
+    pub fn can_invite_members(&self) -> bool {
+        matches!(self, ChannelRole::Admin)
+    }
+
+    pub fn can_moderate(&self) -> bool {
+        matches!(self, ChannelRole::Moderator | ChannelRole::Admin)
+    }
+}
+
+// Agent escrow lifecycle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AgentEscrowStatus {
+    Active,
+    Released,
+    Refunded,
+    Disputed,
+    Resolved,
+}
+
+// Single milestone within an agent escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct EscrowMilestoneState {
+    pub amount: u64,     // 8 bytes
+    pub completed: bool, // 1 byte
+}
+
+// One direct message in a recipient's inbox ring buffer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InboxEntry {
+    pub message: Pubkey,       // 32 bytes - Message account PDA; default if the slot is unused
+    pub created_at: i64,       // 8 bytes
+    pub status: MessageStatus, // 1 byte - Kept in step by update_message_status
+    _reserved: [u8; 7],        // 7 bytes (padding for alignment)
+}
+
 // Program Events for monitoring and indexing
 #[event]
 pub struct AgentRegistered {
     pub agent: Pubkey,
+    pub agent_account: Pubkey,
+    pub name: String,
     pub capabilities: u64,
     pub metadata_uri: String,
     pub timestamp: i64,
@@ -257,6 +612,19 @@ pub struct MessageSent {
     pub sender: Pubkey,
     pub recipient: Pubkey,
     pub message_type: MessageType,
+    pub is_encrypted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageStatusChanged {
+    pub message: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub payload_hash: [u8; 32],
+    pub previous_status: MessageStatus,
+    pub new_status: MessageStatus,
+    pub receipt_signature: [u8; 64], // Recipient's ed25519 signature over message_receipt_bytes; zero for Failed
     pub timestamp: i64,
 }
 
@@ -300,6 +668,391 @@ pub struct EscrowWithdrawal {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MessageTreeInitialized {
+    pub channel: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub timestamp: i64,
+}
+
+// Carries every field hashed into the leaf so indexers can rebuild inclusion proofs
+#[event]
+pub struct CompressedMessageAppended {
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_hash: [u8; 32],
+    pub leaf_index: u64,
+    pub root: [u8; 32],
+    pub content_hash: [u8; 32],
+    pub ipfs_hash: String,
+    pub message_type: MessageType,
+    pub reply_to: Option<Pubkey>,
+    pub key_epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantTreeInitialized {
+    pub channel: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub timestamp: i64,
+}
+
+// Carries the full participant leaf so clients can build proofs for later updates
+#[event]
+pub struct CompressedParticipantUpdated {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_hash: [u8; 32],
+    pub leaf_index: u64,
+    pub root: [u8; 32],
+    pub joined_at: i64,
+    pub messages_sent: u64,
+    pub last_message_at: i64,
+    pub metadata_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedParticipantLeft {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_index: u64,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelFeesClaimed {
+    pub channel: Pubkey,
+    pub creator: Pubkey,
+    pub escrow: Pubkey,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub remaining_escrow_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentEscrowCreated {
+    pub escrow: Pubkey,
+    pub payer: Pubkey,
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub milestone_count: u8,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentEscrowReleased {
+    pub escrow: Pubkey,
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentEscrowMilestoneCompleted {
+    pub escrow: Pubkey,
+    pub payee: Pubkey,
+    pub milestone_index: u8,
+    pub amount: u64,
+    pub released_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentEscrowRefunded {
+    pub escrow: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbitratorSetCreated {
+    pub arbitrator_set: Pubkey,
+    pub authority: Pubkey,
+    pub arbitrators: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDisputeOpened {
+    pub escrow: Pubkey,
+    pub dispute: Pubkey,
+    pub opened_by: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowEvidenceSubmitted {
+    pub escrow: Pubkey,
+    pub dispute: Pubkey,
+    pub submitter: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub ipfs_cid: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDisputeVoted {
+    pub escrow: Pubkey,
+    pub dispute: Pubkey,
+    pub arbitrator: Pubkey,
+    pub payee_share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDisputeResolved {
+    pub escrow: Pubkey,
+    pub dispute: Pubkey,
+    pub payee_share_bps: u16,
+    pub payee_amount: u64,
+    pub payer_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationChanged {
+    pub agent: Pubkey,
+    pub old_reputation: u64,
+    pub new_reputation: u64,
+    pub delta: i16,
+    pub reason: ReputationReason,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolConfigUpdated {
+    pub authority: Pubkey,
+    pub min_reputation_for_channels: u64,
+    pub max_fee_per_message: u64,
+    pub max_escrow_deposit: u64,
+    pub rate_limit_messages_per_minute: u16,
+    pub invite_rate_limit_per_hour: u16,
+    pub message_edit_window_seconds: u32,
+    pub max_invitation_expiry_seconds: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolAuthorityTransferStarted {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolAuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolPauseChanged {
+    pub authority: Pubkey,
+    pub is_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeeUpdated {
+    pub authority: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantRoleChanged {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub moderator: Pubkey,
+    pub role: ChannelRole,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantKicked {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantMuteChanged {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub moderator: Pubkey,
+    pub is_muted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelMessageEdited {
+    pub message: Pubkey,
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub previous_content_hash: [u8; 32],
+    pub new_content_hash: [u8; 32],
+    pub edited_at: i64,
+}
+
+#[event]
+pub struct ReactionAdded {
+    pub message: Pubkey,
+    pub reactor: Pubkey,
+    pub reaction: ReactionKind,
+    pub count: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReactionRemoved {
+    pub message: Pubkey,
+    pub reactor: Pubkey,
+    pub reaction: ReactionKind,
+    pub count: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelMessageDeleted {
+    pub message: Pubkey,
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExpiredMessageClosed {
+    pub message: Pubkey,
+    pub payer: Pubkey,
+    pub closer: Pubkey,
+    pub reward: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvitationAccepted {
+    pub channel: Pubkey,
+    pub invitee: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvitationRevoked {
+    pub channel: Pubkey,
+    pub invitee: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InviteCodeCreated {
+    pub channel: Pubkey,
+    pub invite_code: Pubkey,
+    pub creator: Pubkey,
+    pub max_uses: u32,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ChannelJoinedWithCode {
+    pub channel: Pubkey,
+    pub invite_code: Pubkey,
+    pub participant: Pubkey,
+    pub uses: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyCreated {
+    pub session_key: Pubkey,
+    pub agent: Pubkey,
+    pub session_signer: Pubkey,
+    pub channel: Pubkey,
+    pub permissions: u8,
+    pub spend_cap: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SessionKeyRevoked {
+    pub session_key: Pubkey,
+    pub agent: Pubkey,
+    pub session_signer: Pubkey,
+    pub spent: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelKeyRotated {
+    pub channel: Pubkey,
+    pub key_epoch: u32,
+    pub key_bundle_cid: String,
+    pub rotated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelKeyRotationRequired {
+    pub channel: Pubkey,
+    pub departed: Pubkey,
+    pub key_epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentClosed {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentOwnershipTransferStarted {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentOwnershipTransferred {
+    pub previous_agent: Pubkey,
+    pub new_agent: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub reputation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageFeeCharged {
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub escrow: Pubkey,
+    pub amount: u64,
+    pub remaining_escrow_balance: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct CompressedMessageSynced {
     pub channel_id: Pubkey,
@@ -315,7 +1068,8 @@ pub struct CompressedMessageSynced {
 pub struct ChannelAccount {
     pub creator: Pubkey,               // 32 bytes
     pub fee_per_message: u64,          // 8 bytes (lamports)
-    pub escrow_balance: u64,           // 8 bytes (lamports)
+    pub escrow_balance: u64,           // 8 bytes (lamports) - Spendable deposits across member escrows
+    pub accrued_fees: u64,             // 8 bytes (lamports) - Fees charged into member escrows, not yet claimed
     pub created_at: i64,               // 8 bytes
     pub max_participants: u32,         // 4 bytes
     pub current_participants: u32,     // 4 bytes
@@ -326,8 +1080,15 @@ pub struct ChannelAccount {
     pub last_sync_timestamp: i64,      // 8 bytes - Last batch sync timestamp
     pub total_compressed_messages: u64, // 8 bytes - Total compressed messages
     pub compressed_data_size: u64,     // 8 bytes - Total compressed data size
+    pub message_tree: Pubkey,          // 32 bytes - Concurrent Merkle tree of compressed messages
+    pub message_tree_root: [u8; 32],   // 32 bytes - Latest root of the message tree
+    pub participant_tree: Pubkey,      // 32 bytes - Concurrent Merkle tree of compressed participants
+    pub participant_tree_root: [u8; 32], // 32 bytes - Latest root of the participant tree
+    pub key_bundle_cid: String,        // 4 + 100 bytes - IPFS CID of the channel key wrapped per member
+    pub key_epoch: u32,                // 4 bytes - Current channel key epoch (0 = no key published)
+    pub key_rotation_pending: bool,    // 1 byte - A member left since the last rotation
     pub bump: u8,                      // 1 byte
-    _reserved: [u8; 5],                // 5 bytes (padding for alignment)
+    pub total_synced_messages: u32,    // 4 bytes - Message hashes recorded by batch sync
 }
 
 // Channel participant account structure with optimized memory layout (PERF-02)
@@ -341,7 +1102,23 @@ pub struct ChannelParticipant {
     pub last_message_at: i64, // 8 bytes
     pub is_active: bool,      // 1 byte
     pub bump: u8,             // 1 byte
-    _reserved: [u8; 6],       // 6 bytes (padding for alignment)
+    pub role: ChannelRole,    // 1 byte - Carved from _reserved; legacy records read as Member
+    pub is_muted: bool,       // 1 byte
+    pub is_kicked: bool,      // 1 byte - Set by kick_participant; the record then stands as a ban
+    _reserved: [u8; 3],       // 3 bytes (padding for alignment)
+}
+
+// Marks an agent as holding a leaf in a channel's participant tree
+// (PDA: ["compressed_member", channel, agent]); it blocks duplicate joins and is closed on leave
+#[account]
+#[repr(C)]
+pub struct CompressedMembership {
+    pub channel: Pubkey,     // 32 bytes
+    pub participant: Pubkey, // 32 bytes - Agent PDA
+    pub joined_at: i64,      // 8 bytes
+    pub leaf_index: u32,     // 4 bytes - The agent's leaf in the participant tree
+    pub bump: u8,            // 1 byte
+    _reserved: [u8; 3],      // 3 bytes (padding for alignment)
 }
 
 // Channel invitation account structure (for private channels)
@@ -363,6 +1140,41 @@ pub struct ChannelInvitation {
     _reserved: [u8; 5],             // 5 bytes (padding for alignment)
 }
 
+// Multi-use invite code for a private channel (PDA: ["invite_code", channel, code_signer])
+// The code is a keypair handed out off-chain; joiners prove possession by co-signing the join,
+// so using the code never reveals its secret
+#[account]
+#[repr(C)]
+pub struct ChannelInviteCode {
+    pub channel: Pubkey,     // 32 bytes
+    pub creator: Pubkey,     // 32 bytes - Wallet that created (and paid for) the code
+    pub code_signer: Pubkey, // 32 bytes - Public key of the invite code keypair
+    pub created_at: i64,     // 8 bytes
+    pub expires_at: i64,     // 8 bytes
+    pub max_uses: u32,       // 4 bytes
+    pub uses: u32,           // 4 bytes
+    pub bump: u8,            // 1 byte
+    _reserved: [u8; 7],      // 7 bytes (padding for alignment)
+}
+
+// Delegated signer for an agent (PDA: ["session_key", agent, session_signer])
+// Lets a bot host post messages without holding the owner's wallet
+#[account]
+#[repr(C)]
+pub struct SessionKey {
+    pub agent: Pubkey,          // 32 bytes
+    pub owner: Pubkey,          // 32 bytes - Agent owner; paid for and receives the rent
+    pub session_signer: Pubkey, // 32 bytes
+    pub channel: Pubkey,        // 32 bytes - Pubkey::default() allows every channel
+    pub spend_cap: u64,         // 8 bytes - Max lamports of message fees charged via this key
+    pub spent: u64,             // 8 bytes
+    pub created_at: i64,        // 8 bytes
+    pub expires_at: i64,        // 8 bytes
+    pub permissions: u8,        // 1 byte - SESSION_PERMISSION_* bitmask
+    pub bump: u8,               // 1 byte
+    _reserved: [u8; 6],         // 6 bytes (padding for alignment)
+}
+
 // Channel message account structure (for broadcast messages)
 // PERFORMANCE OPTIMIZATION (PERF-02): Optimized memory layout
 #[account]
@@ -370,40 +1182,151 @@ pub struct ChannelInvitation {
 pub struct ChannelMessage {
     pub channel: Pubkey,           // 32 bytes
     pub sender: Pubkey,            // 32 bytes
+    pub thread_root: Pubkey,       // 32 bytes - First message of the thread (itself when top-level)
     pub reply_to: Option<Pubkey>,  // 33 bytes (1 for Option + 32 for Pubkey)
     pub created_at: i64,           // 8 bytes
     pub edited_at: Option<i64>,    // 9 bytes (1 for Option + 8 for i64)
     pub content: String,           // 4 + 1000 bytes (max content)
     pub message_type: MessageType, // 1 byte
     pub bump: u8,                  // 1 byte
-    _reserved: [u8; 6],            // 6 bytes (padding for alignment)
+    pub key_epoch: u32,            // 4 bytes - Channel key epoch the content was encrypted under
+    pub reply_count: u32,          // 4 bytes - Direct replies posted to this message
+    pub reaction_counts: [u16; REACTION_KIND_COUNT], // 16 bytes - Indexed by ReactionKind
+    _reserved: [u8; 2],            // 2 bytes (padding for alignment)
+}
+
+// One agent's reaction of one kind to a channel message
+// (PDA: ["reaction", message, reactor_agent, [reaction index]]); its existence prevents double-reacting
+#[account]
+#[repr(C)]
+pub struct MessageReaction {
+    pub message: Pubkey,        // 32 bytes
+    pub reactor: Pubkey,        // 32 bytes - Reacting agent PDA
+    pub created_at: i64,        // 8 bytes
+    pub reaction: ReactionKind, // 1 byte
+    pub bump: u8,               // 1 byte
+    _reserved: [u8; 6],         // 6 bytes (padding for alignment)
+}
+
+// Program-wide limits tunable by the protocol authority (PDA: ["protocol_config"])
+#[account]
+#[repr(C)]
+pub struct ProtocolConfig {
+    pub authority: Pubkey,                   // 32 bytes
+    pub pending_authority: Pubkey,           // 32 bytes - Set by set_authority, cleared on accept
+    pub fee_recipient: Pubkey,               // 32 bytes - Receives the protocol fee
+    pub min_reputation_for_channels: u64,    // 8 bytes
+    pub max_fee_per_message: u64,            // 8 bytes (lamports)
+    pub max_escrow_deposit: u64,             // 8 bytes (lamports)
+    pub updated_at: i64,                     // 8 bytes
+    pub rate_limit_messages_per_minute: u16, // 2 bytes
+    pub invite_rate_limit_per_hour: u16,     // 2 bytes
+    pub protocol_fee_bps: u16,               // 2 bytes - Share of claimed channel fees
+    pub is_paused: bool,                     // 1 byte
+    pub bump: u8,                            // 1 byte
+    pub message_edit_window_seconds: u32,    // 4 bytes
+    pub max_invitation_expiry_seconds: u32,  // 4 bytes
+    _reserved: [u8; 4],                      // 4 bytes (reserved for future limits)
 }
 
 // Escrow account structure with optimized memory layout (PERF-02)
 #[account]
 #[repr(C)]
 pub struct EscrowAccount {
-    pub channel: Pubkey,   // 32 bytes
-    pub depositor: Pubkey, // 32 bytes
-    pub amount: u64,       // 8 bytes
-    pub created_at: i64,   // 8 bytes
-    pub bump: u8,          // 1 byte
-    _reserved: [u8; 7],    // 7 bytes (padding for alignment)
+    pub channel: Pubkey,     // 32 bytes
+    pub depositor: Pubkey,   // 32 bytes
+    pub amount: u64,         // 8 bytes - Spendable balance owned by the depositor
+    pub unclaimed_fees: u64, // 8 bytes - Fees charged but still held here for the channel creator
+    pub created_at: i64,     // 8 bytes
+    pub bump: u8,            // 1 byte
+    _reserved: [u8; 7],      // 7 bytes (padding for alignment)
+}
+
+// Escrow between two agents, released in full or per milestone, refundable after timeout
+#[account]
+#[repr(C)]
+pub struct AgentEscrowAccount {
+    pub payer: Pubkey,                                             // 32 bytes - Payer agent PDA
+    pub payee: Pubkey,                                             // 32 bytes - Payee agent PDA
+    pub payer_wallet: Pubkey,                                      // 32 bytes - Receives refunds
+    pub payee_wallet: Pubkey,                                      // 32 bytes - Receives releases
+    pub escrow_id: [u8; 32],                                       // 32 bytes - Client-chosen identifier
+    pub amount: u64,                                               // 8 bytes - Total deposited
+    pub released_amount: u64,                                      // 8 bytes - Paid out to the payee so far
+    pub created_at: i64,                                           // 8 bytes
+    pub expires_at: i64,                                           // 8 bytes - Payer may refund after this
+    pub arbitrator_set: Pubkey,                                    // 32 bytes - Resolves disputes (default = none)
+    pub milestones: [EscrowMilestoneState; MAX_ESCROW_MILESTONES], // 72 bytes
+    pub milestone_count: u8,                                       // 1 byte
+    pub status: AgentEscrowStatus,                                 // 1 byte
+    pub bump: u8,                                                  // 1 byte
+    _reserved: [u8; 5],                                            // 5 bytes (padding for alignment)
+}
+
+// m-of-n arbitrator set that can split a disputed agent escrow
+#[account]
+#[repr(C)]
+pub struct ArbitratorSet {
+    pub authority: Pubkey,                      // 32 bytes
+    pub arbitrators: [Pubkey; MAX_ARBITRATORS], // 160 bytes - Wallets or PDAs allowed to vote
+    pub set_id: u64,                            // 8 bytes
+    pub arbitrator_count: u8,                   // 1 byte
+    pub threshold: u8,                          // 1 byte - Matching votes needed to resolve
+    pub bump: u8,                               // 1 byte
+    _reserved: [u8; 5],                         // 5 bytes (padding for alignment)
+}
+
+// Dispute opened against an agent escrow
+#[account]
+#[repr(C)]
+pub struct EscrowDispute {
+    pub escrow: Pubkey,                // 32 bytes
+    pub arbitrator_set: Pubkey,        // 32 bytes
+    pub opened_by: Pubkey,             // 32 bytes
+    pub reason_hash: [u8; 32],         // 32 bytes
+    pub opened_at: i64,                // 8 bytes
+    pub resolved_at: i64,              // 8 bytes
+    pub votes: [u16; MAX_ARBITRATORS], // 10 bytes - Payee share per arbitrator slot
+    pub evidence_count: u16,           // 2 bytes
+    pub voted_mask: u8,                // 1 byte - Bit i set once arbitrator i has voted (votes may be changed)
+    pub is_resolved: bool,             // 1 byte
+    pub bump: u8,                      // 1 byte
+    _reserved: [u8; 3],                // 3 bytes (padding for alignment)
 }
 
 // Agent account structure with optimized memory layout (PERF-02)
 #[account]
 #[repr(C)]
 pub struct AgentAccount {
-    pub pubkey: Pubkey,       // 32 bytes
-    pub capabilities: u64,    // 8 bytes
-    pub reputation: u64,      // 8 bytes
-    pub last_updated: i64,    // 8 bytes
-    pub metadata_uri: String, // 4 + MAX_METADATA_URI_LENGTH bytes
-    pub invites_sent: u16,    // 2 bytes - rate limiting
-    pub last_invite_at: i64,  // 8 bytes - rate limit window start
-    pub bump: u8,             // 1 byte
-    _reserved: [u8; 7],       // 7 bytes (padding for alignment)
+    pub pubkey: Pubkey,              // 32 bytes
+    pub capabilities: u64,           // 8 bytes
+    pub reputation: u64,             // 8 bytes
+    pub last_updated: i64,           // 8 bytes
+    pub metadata_uri: String,        // 4 + MAX_METADATA_URI_LENGTH bytes
+    pub name: String,                // 4 + MAX_AGENT_NAME_LENGTH bytes - empty for v1 agents
+    pub invites_sent: u16,           // 2 bytes - rate limiting
+    pub last_invite_at: i64,         // 8 bytes - rate limit window start
+    pub bump: u8,                    // 1 byte
+    pub reputation_epoch: u32,       // 4 bytes - epoch of epoch_reputation_delta
+    pub epoch_reputation_delta: i16, // 2 bytes - net change applied this epoch
+    pub active_escrows: u16,         // 2 bytes - unsettled agent escrows this agent funded
+    pub pending_owner: Pubkey,       // 32 bytes - set by transfer_agent_ownership
+    pub direct_messages_sent: u16,   // 2 bytes - direct message rate limiting
+    pub last_direct_message_at: i64, // 8 bytes - direct message rate limit window start
+    _reserved: [u8; 1],              // 1 byte (padding for alignment)
+}
+
+// Reputation left behind by a closed agent, restored when its PDA is registered again
+#[account]
+#[repr(C)]
+pub struct AgentTombstone {
+    pub owner: Pubkey,               // 32 bytes
+    pub reputation: u64,             // 8 bytes
+    pub closed_at: i64,              // 8 bytes
+    pub reputation_epoch: u32,       // 4 bytes
+    pub epoch_reputation_delta: i16, // 2 bytes
+    pub bump: u8,                    // 1 byte
+    _reserved: [u8; 1],              // 1 byte (padding for alignment)
 }
 
 // Message account structure with optimized memory layout (PERF-02)
@@ -412,13 +1335,116 @@ pub struct AgentAccount {
 pub struct MessageAccount {
     pub sender: Pubkey,            // 32 bytes
     pub recipient: Pubkey,         // 32 bytes
+    pub payer: Pubkey,             // 32 bytes - Wallet refunded when the account is closed
     pub payload_hash: [u8; 32],    // 32 bytes
+    pub ephemeral_pubkey: [u8; 32], // 32 bytes - Sender's X25519 envelope key; zero if plaintext
     pub created_at: i64,           // 8 bytes
     pub expires_at: i64,           // 8 bytes
+    pub delivered_at: i64,         // 8 bytes - Set by the recipient's delivery receipt; 0 if none
+    pub read_at: i64,              // 8 bytes - Set by the recipient's read receipt; 0 if none
     pub message_type: MessageType, // 1 byte (max)
     pub status: MessageStatus,     // 1 byte (max)
     pub bump: u8,                  // 1 byte
-    _reserved: [u8; 5],            // 5 bytes (padding for alignment)
+    pub envelope_nonce: [u8; 12],  // 12 bytes - AEAD nonce for the encrypted payload
+    pub inbox_indexed: bool,       // 1 byte - Written to the recipient agent's inbox at send time
+    pub recipient_agent: Pubkey,   // 32 bytes - Recipient agent PDA whose inbox indexed the message
+}
+
+// Ring buffer of the latest direct messages sent to a recipient agent (PDA: ["inbox", recipient_agent]).
+// send_message writes slot `total_received % INBOX_CAPACITY`, so one fetch lists the inbox.
+#[account]
+#[repr(C)]
+pub struct AgentInbox {
+    pub owner: Pubkey,                            // 32 bytes - Recipient agent PDA
+    pub total_received: u64,                      // 8 bytes - Messages ever indexed
+    pub entries: [InboxEntry; INBOX_CAPACITY],    // 768 bytes
+    pub bump: u8,                                 // 1 byte
+    _reserved: [u8; 7],                           // 7 bytes (padding for alignment)
+}
+
+// =============================================================================
+// LEGACY ACCOUNT LAYOUTS
+// =============================================================================
+
+// Layouts of accounts created before their structs grew; migrate_account reads these and
+// rewrites the account in the current layout. ChannelParticipant and ChannelInvitation kept
+// their size and field prefix, so they need no migration.
+mod legacy {
+    use super::*;
+
+    pub const AGENT_ACCOUNT_SPACE: usize = 8 + 32 + 8 + 8 + 8 + (4 + MAX_METADATA_URI_LENGTH) + 2 + 8 + 1 + 7; // 286 bytes
+    pub const MESSAGE_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 5; // 128 bytes
+    pub const CHANNEL_ACCOUNT_SPACE: usize = 8
+        + 32 + 8 + 8 + 8 + 4 + 4
+        + (4 + MAX_CHANNEL_NAME_LENGTH)
+        + (4 + MAX_CHANNEL_DESCRIPTION_LENGTH)
+        + 1 + 1 + 1 + 5; // 333 bytes
+    pub const CHANNEL_MESSAGE_SPACE: usize =
+        8 + 32 + 32 + 33 + 8 + 9 + (4 + MAX_MESSAGE_CONTENT_LENGTH) + 1 + 1 + 6; // 1134 bytes
+    pub const ESCROW_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 7; // 96 bytes
+
+    #[derive(AnchorDeserialize)]
+    pub struct AgentAccount {
+        pub pubkey: Pubkey,
+        pub capabilities: u64,
+        pub reputation: u64,
+        pub last_updated: i64,
+        pub metadata_uri: String,
+        pub invites_sent: u16,
+        pub last_invite_at: i64,
+        pub bump: u8,
+    }
+
+    #[derive(AnchorDeserialize)]
+    pub struct MessageAccount {
+        pub sender: Pubkey,
+        pub recipient: Pubkey,
+        pub payload_hash: [u8; 32],
+        pub created_at: i64,
+        pub expires_at: i64,
+        pub message_type: MessageType,
+        pub status: MessageStatus,
+        pub bump: u8,
+    }
+
+    #[derive(AnchorDeserialize)]
+    pub struct ChannelAccount {
+        pub creator: Pubkey,
+        pub fee_per_message: u64,
+        pub escrow_balance: u64,
+        pub created_at: i64,
+        pub max_participants: u32,
+        pub current_participants: u32,
+        pub name: String,
+        pub description: String,
+        pub visibility: ChannelVisibility,
+        pub is_active: bool,
+        pub last_sync_timestamp: i64,
+        pub total_compressed_messages: u64,
+        pub compressed_data_size: u64,
+        pub bump: u8,
+    }
+
+    #[derive(AnchorDeserialize)]
+    pub struct ChannelMessage {
+        pub channel: Pubkey,
+        pub sender: Pubkey,
+        pub reply_to: Option<Pubkey>,
+        pub created_at: i64,
+        pub edited_at: Option<i64>,
+        pub content: String,
+        pub message_type: MessageType,
+        pub bump: u8,
+    }
+
+    #[derive(AnchorDeserialize)]
+    pub struct EscrowAccount {
+        pub channel: Pubkey,
+        pub depositor: Pubkey,
+        pub amount: u64,
+        pub created_at: i64,
+        pub bump: u8,
+    }
 }
 
 // =============================================================================
@@ -442,7 +1468,7 @@ pub struct CompressedChannelMessage {
 impl CompressedChannelMessage {
     pub fn hash(&self) -> std::result::Result<[u8; 32], PodComError> {
         // Calculate required buffer size
-        let mut size = 32 + 32 + 32 + self.ipfs_hash.len() + 1 + 8; // base fields
+        let mut size = 32 + 32 + 32 + self.ipfs_hash.len() + 1 + 8 + 2; // base fields and option flags
         if self.edited_at.is_some() { size += 8; }
         if self.reply_to.is_some() { size += 32; }
         
@@ -487,12 +1513,18 @@ impl CompressedChannelMessage {
         data[offset..offset+8].copy_from_slice(&self.created_at.to_le_bytes());
         offset += 8;
         
+        // Optional fields carry a presence flag so leaves differing in them never collide
+        data[offset] = self.edited_at.is_some() as u8;
+        offset += 1;
         if let Some(edited) = self.edited_at {
             data[offset..offset+8].copy_from_slice(&edited.to_le_bytes());
             offset += 8;
         }
+        data[offset] = self.reply_to.is_some() as u8;
+        offset += 1;
         if let Some(reply_to) = self.reply_to {
             data[offset..offset+32].copy_from_slice(&reply_to.to_bytes());
+            offset += 32;
         }
         
         // Perform hash computation on secure data using Blake3
@@ -542,6 +1574,867 @@ impl CompressedChannelParticipant {
     }
 }
 
+// Read the current root and appended leaf count from an SPL concurrent Merkle tree account
+fn read_merkle_tree_state<const MAX_DEPTH: usize, const MAX_BUFFER_SIZE: usize>(
+    merkle_tree: &AccountInfo,
+) -> Result<([u8; 32], u64)> {
+    // SECURITY: Only trust tree data owned by the account compression program
+    if *merkle_tree.owner != spl_account_compression::ID {
+        return Err(PodComError::InvalidMerkleTree.into());
+    }
+
+    let data = merkle_tree.try_borrow_data()?;
+    if data.len() < CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1 {
+        return Err(PodComError::InvalidMerkleTree.into());
+    }
+    let (header_bytes, rest) = data.split_at(CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1);
+    let header = ConcurrentMerkleTreeHeader::try_from_slice(header_bytes)?;
+
+    // Tree dimensions must match the generic parameters used for zero-copy access
+    if header.get_max_depth() as usize != MAX_DEPTH
+        || header.get_max_buffer_size() as usize != MAX_BUFFER_SIZE
+    {
+        return Err(PodComError::InvalidMerkleTree.into());
+    }
+
+    let tree_size = merkle_tree_get_size(&header)?;
+    if rest.len() < tree_size {
+        return Err(PodComError::InvalidMerkleTree.into());
+    }
+    let tree = ConcurrentMerkleTree::<MAX_DEPTH, MAX_BUFFER_SIZE>::load_bytes(&rest[..tree_size])?;
+
+    Ok((tree.get_root(), tree.rightmost_proof.index as u64))
+}
+
+// Initialize an empty concurrent Merkle tree with the channel PDA as its authority
+fn init_channel_tree<'info>(
+    channel: &Account<'info, ChannelAccount>,
+    merkle_tree: AccountInfo<'info>,
+    compression_program: AccountInfo<'info>,
+    noop_program: AccountInfo<'info>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let bump = [channel.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"channel",
+        channel.creator.as_ref(),
+        channel.name.as_bytes(),
+        &bump,
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        compression_program,
+        spl_account_compression::cpi::accounts::Initialize {
+            merkle_tree,
+            authority: channel.to_account_info(),
+            noop: noop_program,
+        },
+        signer_seeds,
+    );
+    spl_account_compression::cpi::init_empty_merkle_tree(cpi_ctx, max_depth, max_buffer_size)
+}
+
+// Append a leaf to a channel-owned tree, signed by the channel PDA
+fn append_to_channel_tree<'info>(
+    channel: &Account<'info, ChannelAccount>,
+    merkle_tree: AccountInfo<'info>,
+    compression_program: AccountInfo<'info>,
+    noop_program: AccountInfo<'info>,
+    leaf: [u8; 32],
+) -> Result<()> {
+    let bump = [channel.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"channel",
+        channel.creator.as_ref(),
+        channel.name.as_bytes(),
+        &bump,
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        compression_program,
+        spl_account_compression::cpi::accounts::Modify {
+            merkle_tree,
+            authority: channel.to_account_info(),
+            noop: noop_program,
+        },
+        signer_seeds,
+    );
+    spl_account_compression::cpi::append(cpi_ctx, leaf)
+}
+
+// Replace a leaf in a channel-owned tree; proof nodes are forwarded as remaining accounts
+#[allow(clippy::too_many_arguments)]
+fn replace_channel_tree_leaf<'info>(
+    channel: &Account<'info, ChannelAccount>,
+    merkle_tree: AccountInfo<'info>,
+    compression_program: AccountInfo<'info>,
+    noop_program: AccountInfo<'info>,
+    proof: &[AccountInfo<'info>],
+    root: [u8; 32],
+    previous_leaf: [u8; 32],
+    new_leaf: [u8; 32],
+    index: u32,
+) -> Result<()> {
+    let bump = [channel.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"channel",
+        channel.creator.as_ref(),
+        channel.name.as_bytes(),
+        &bump,
+    ]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        compression_program,
+        spl_account_compression::cpi::accounts::Modify {
+            merkle_tree,
+            authority: channel.to_account_info(),
+            noop: noop_program,
+        },
+        signer_seeds,
+    )
+    .with_remaining_accounts(proof.to_vec());
+    spl_account_compression::cpi::replace_leaf(cpi_ctx, root, previous_leaf, new_leaf, index)
+}
+
+// Apply a reputation change, clamped to the per-epoch budget and [0, MAX_REPUTATION]
+fn apply_reputation_delta(
+    agent: &mut Account<'_, AgentAccount>,
+    delta: i16,
+    reason: ReputationReason,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let epoch = (clock.unix_timestamp / REPUTATION_EPOCH_SECONDS) as u32;
+
+    // New epoch resets the budget
+    if agent.reputation_epoch != epoch {
+        agent.reputation_epoch = epoch;
+        agent.epoch_reputation_delta = 0;
+    }
+
+    let applied = agent
+        .epoch_reputation_delta
+        .saturating_add(delta)
+        .clamp(-MAX_REPUTATION_LOSS_PER_EPOCH, MAX_REPUTATION_GAIN_PER_EPOCH)
+        - agent.epoch_reputation_delta;
+    if applied == 0 {
+        return Ok(());
+    }
+
+    let old_reputation = agent.reputation;
+    let new_reputation = if applied > 0 {
+        old_reputation.saturating_add(applied as u64).min(MAX_REPUTATION)
+    } else {
+        old_reputation.saturating_sub(applied.unsigned_abs() as u64)
+    };
+
+    agent.epoch_reputation_delta += applied;
+    agent.reputation = new_reputation;
+    agent.last_updated = clock.unix_timestamp;
+
+    emit!(ReputationChanged {
+        agent: agent.key(),
+        old_reputation,
+        new_reputation,
+        delta: applied,
+        reason,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Initialise a direct message account for `send_message` / `send_encrypted_message`
+fn write_direct_message(
+    accounts: &mut SendMessage<'_>,
+    bump: u8,
+    inbox_bump: u8,
+    recipient: Pubkey,
+    payload_hash: [u8; 32],
+    message_type: MessageType,
+    ephemeral_pubkey: [u8; 32],
+    envelope_nonce: [u8; 12],
+) -> Result<()> {
+    authorize_agent_signer(
+        &accounts.sender_agent,
+        &accounts.signer.key(),
+        accounts.session_key.as_mut(),
+        SESSION_PERMISSION_SEND_MESSAGE,
+        None,
+        0,
+    )?;
+
+    let clock = Clock::get()?;
+
+    // Rate limiting per sender agent, so no sender can flush a recipient's inbox ring
+    let sender_agent = &mut accounts.sender_agent;
+    if sender_agent.last_direct_message_at > 0 {
+        let elapsed = clock.unix_timestamp - sender_agent.last_direct_message_at;
+        if elapsed < 3600 {
+            if sender_agent.direct_messages_sent >= DIRECT_MESSAGE_RATE_LIMIT_PER_HOUR {
+                return Err(PodComError::RateLimitExceeded.into());
+            }
+            sender_agent.direct_messages_sent = sender_agent
+                .direct_messages_sent
+                .checked_add(1)
+                .ok_or(PodComError::RateLimitExceeded)?;
+        } else {
+            sender_agent.direct_messages_sent = 1;
+            sender_agent.last_direct_message_at = clock.unix_timestamp;
+        }
+    } else {
+        sender_agent.direct_messages_sent = 1;
+        sender_agent.last_direct_message_at = clock.unix_timestamp;
+    }
+
+    let message = &mut accounts.message_account;
+
+    // IMPORTANT: Use agent PDA as sender for consistency across all message types
+    // This ensures all messages are associated with registered agents, not raw wallets
+    message.sender = accounts.sender_agent.key();
+    message.recipient = recipient;
+    message.payer = accounts.signer.key();
+    message.payload_hash = payload_hash;
+    message.ephemeral_pubkey = ephemeral_pubkey;
+    message.envelope_nonce = envelope_nonce;
+    message.message_type = message_type.clone();
+    message.created_at = clock.unix_timestamp;
+    message.expires_at = clock.unix_timestamp + MESSAGE_EXPIRATION_SECONDS;
+    message.status = MessageStatus::Pending;
+    message.bump = bump;
+
+    // Index the message in the recipient agent's inbox, overwriting the oldest slot once full
+    let inbox = &mut accounts.recipient_inbox;
+    if inbox.owner == Pubkey::default() {
+        inbox.owner = accounts.recipient_agent.key();
+        inbox.bump = inbox_bump;
+    }
+    message.inbox_indexed = true;
+    message.recipient_agent = inbox.owner;
+    let slot = (inbox.total_received % INBOX_CAPACITY as u64) as usize;
+    inbox.entries[slot].message = message.key();
+    inbox.entries[slot].created_at = message.created_at;
+    inbox.entries[slot].status = MessageStatus::Pending;
+    inbox.total_received = inbox.total_received.saturating_add(1);
+
+    // Emit event for monitoring
+    emit!(MessageSent {
+        sender: message.sender,
+        recipient: message.recipient,
+        message_type,
+        is_encrypted: ephemeral_pubkey != [0u8; 32],
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Message sent from {:?} to {:?}",
+        message.sender,
+        message.recipient
+    );
+    Ok(())
+}
+
+// Authorise `signer` to act for `agent`: the owner's wallet always may, otherwise a live
+// session key must grant `permission` (and `channel`, when scoped). Fees charged through a
+// session key count against its spend cap.
+fn authorize_agent_signer(
+    agent: &AgentAccount,
+    signer: &Pubkey,
+    session_key: Option<&mut Account<'_, SessionKey>>,
+    permission: u8,
+    channel: Option<Pubkey>,
+    spend: u64,
+) -> Result<()> {
+    if *signer == agent.pubkey {
+        return Ok(());
+    }
+
+    // Seeds on the context already tie the session key to this agent and signer
+    let session = session_key.ok_or(PodComError::Unauthorized)?;
+    if Clock::get()?.unix_timestamp >= session.expires_at {
+        return Err(PodComError::SessionKeyExpired.into());
+    }
+    if session.permissions & permission == 0 {
+        return Err(PodComError::SessionKeyPermissionDenied.into());
+    }
+    if let Some(channel) = channel {
+        if session.channel != Pubkey::default() && session.channel != channel {
+            return Err(PodComError::SessionKeyPermissionDenied.into());
+        }
+    }
+
+    session.spent = session
+        .spent
+        .checked_add(spend)
+        .ok_or(PodComError::SessionKeySpendCapExceeded)?;
+    if session.spent > session.spend_cap {
+        return Err(PodComError::SessionKeySpendCapExceeded.into());
+    }
+    Ok(())
+}
+
+// Drop a settled escrow from the payer's active escrow count. Payees are never pinned:
+// they don't consent to an escrow, so it must not stop them closing or moving their agent.
+fn release_active_escrow(payer_agent: &mut Account<'_, AgentAccount>) {
+    payer_agent.active_escrows = payer_agent.active_escrows.saturating_sub(1);
+}
+
+// Validate registration input and initialise a freshly created agent account.
+// v1 agents (`[b"agent", wallet]`) are registered with an empty name; an empty
+// seed doesn't change the derived address, so `[b"agent", wallet, name]` resolves
+// both v1 and v2 agents in account constraints.
+fn init_agent_account(
+    agent: &mut Account<'_, AgentAccount>,
+    owner: Pubkey,
+    name: String,
+    capabilities: u64,
+    metadata_uri: String,
+    bump: u8,
+) -> Result<()> {
+    // SECURITY: Comprehensive input validation
+    
+    // Validate metadata_uri format and content
+    if metadata_uri.trim().is_empty() {
+        return Err(PodComError::InvalidMetadataUriLength.into());
+    }
+    
+    // Enforce strict length limits
+    if metadata_uri.len() > MAX_METADATA_URI_LENGTH {
+        return Err(PodComError::InvalidMetadataUriLength.into());
+    }
+    
+    // Additional security validations
+    if metadata_uri.len() < 10 { // Minimum reasonable URL length
+        return Err(PodComError::InvalidMetadataUriLength.into());
+    }
+    
+    // Validate URL format and prevent dangerous schemes
+    if !is_valid_metadata_uri(&metadata_uri) {
+        return Err(PodComError::InvalidMetadataUriLength.into());
+    }
+    
+    // Capabilities validation - prevent overflow and unreasonable values
+    if capabilities > u64::MAX / 2 {
+        return Err(PodComError::Unauthorized.into());
+    }
+    
+    // Check for null bytes and other dangerous characters
+    if metadata_uri.contains('\0') || metadata_uri.contains('\r') || metadata_uri.contains('\n') {
+        return Err(PodComError::InvalidMetadataUriLength.into());
+    }
+
+    let clock = Clock::get()?;
+
+    agent.pubkey = owner;
+    agent.name = name.clone();
+    agent.capabilities = capabilities;
+    agent.metadata_uri = metadata_uri.clone();
+    agent.reputation = 100; // Initial reputation
+    agent.last_updated = clock.unix_timestamp;
+    agent.invites_sent = 0;
+    agent.last_invite_at = 0;
+    agent.direct_messages_sent = 0;
+    agent.last_direct_message_at = 0;
+    agent.active_escrows = 0;
+    agent.pending_owner = Pubkey::default();
+    agent.bump = bump;
+
+    // Emit event for monitoring
+    emit!(AgentRegistered {
+        agent: agent.pubkey,
+        agent_account: agent.key(),
+        name,
+        capabilities,
+        metadata_uri,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Agent registered: {:?}", agent.pubkey);
+    Ok(())
+}
+
+// Carry a closed agent's reputation over to its re-registration. The tombstone PDA is
+// always passed in; it only holds data if an agent at this address was closed before.
+fn restore_agent_reputation<'info>(
+    agent: &mut Account<'info, AgentAccount>,
+    tombstone: &UncheckedAccount<'info>,
+    signer: &Signer<'info>,
+) -> Result<()> {
+    if tombstone.owner != &crate::ID || tombstone.data_is_empty() {
+        return Ok(());
+    }
+
+    let tombstone_info = tombstone.to_account_info();
+    let tombstone = Account::<AgentTombstone>::try_from(&tombstone_info)?;
+    agent.reputation = tombstone.reputation;
+    agent.reputation_epoch = tombstone.reputation_epoch;
+    agent.epoch_reputation_delta = tombstone.epoch_reputation_delta;
+
+    // The owner paid for the tombstone when closing, so the rent goes back to them
+    tombstone.close(signer.to_account_info())?;
+
+    msg!("Agent reputation restored to {}", agent.reputation);
+    Ok(())
+}
+
+// Re-encode a legacy account (see the legacy module) in its current layout, selected by the
+// unchanged discriminator and the legacy size. Returns the serialized account and its space.
+fn encode_migrated_account(
+    account_key: Pubkey,
+    data: &[u8],
+    sender_agent: Option<&Account<'_, AgentAccount>>,
+) -> Result<(Vec<u8>, usize)> {
+    if data.len() < 8 {
+        return Err(PodComError::AccountNotMigratable.into());
+    }
+    let (discriminator, mut body) = data.split_at(8);
+    let mut encoded = Vec::new();
+
+    if discriminator == AgentAccount::DISCRIMINATOR && data.len() == legacy::AGENT_ACCOUNT_SPACE {
+        let old = legacy::AgentAccount::deserialize(&mut body)
+            .map_err(|_| PodComError::AccountNotMigratable)?;
+        AgentAccount {
+            pubkey: old.pubkey,
+            capabilities: old.capabilities,
+            reputation: old.reputation,
+            last_updated: old.last_updated,
+            metadata_uri: old.metadata_uri,
+            name: String::new(), // Legacy agents are v1 agents
+            invites_sent: old.invites_sent,
+            last_invite_at: old.last_invite_at,
+            bump: old.bump,
+            reputation_epoch: 0,
+            epoch_reputation_delta: 0,
+            active_escrows: 0,
+            pending_owner: Pubkey::default(),
+            direct_messages_sent: 0,
+            last_direct_message_at: 0,
+            _reserved: [0; 1],
+        }
+        .try_serialize(&mut encoded)?;
+        return Ok((encoded, AGENT_ACCOUNT_SPACE));
+    }
+
+    if discriminator == MessageAccount::DISCRIMINATOR && data.len() == legacy::MESSAGE_ACCOUNT_SPACE {
+        let old = legacy::MessageAccount::deserialize(&mut body)
+            .map_err(|_| PodComError::AccountNotMigratable)?;
+        // Legacy messages were paid for by the sender agent's owner and never indexed in an inbox
+        let sender_agent = sender_agent.ok_or(PodComError::AccountNotMigratable)?;
+        if sender_agent.key() != old.sender {
+            return Err(PodComError::Unauthorized.into());
+        }
+        MessageAccount {
+            sender: old.sender,
+            recipient: old.recipient,
+            payer: sender_agent.pubkey,
+            payload_hash: old.payload_hash,
+            ephemeral_pubkey: [0; 32],
+            created_at: old.created_at,
+            expires_at: old.expires_at,
+            delivered_at: 0,
+            read_at: 0,
+            message_type: old.message_type,
+            status: old.status,
+            bump: old.bump,
+            envelope_nonce: [0; 12],
+            inbox_indexed: false,
+            recipient_agent: Pubkey::default(),
+        }
+        .try_serialize(&mut encoded)?;
+        return Ok((encoded, MESSAGE_ACCOUNT_SPACE));
+    }
+
+    if discriminator == ChannelAccount::DISCRIMINATOR && data.len() == legacy::CHANNEL_ACCOUNT_SPACE {
+        let old = legacy::ChannelAccount::deserialize(&mut body)
+            .map_err(|_| PodComError::AccountNotMigratable)?;
+        ChannelAccount {
+            creator: old.creator,
+            fee_per_message: old.fee_per_message,
+            escrow_balance: old.escrow_balance,
+            accrued_fees: 0,
+            created_at: old.created_at,
+            max_participants: old.max_participants,
+            current_participants: old.current_participants,
+            name: old.name,
+            description: old.description,
+            visibility: old.visibility,
+            is_active: old.is_active,
+            last_sync_timestamp: old.last_sync_timestamp,
+            total_compressed_messages: old.total_compressed_messages,
+            compressed_data_size: old.compressed_data_size,
+            message_tree: Pubkey::default(),
+            message_tree_root: [0; 32],
+            participant_tree: Pubkey::default(),
+            participant_tree_root: [0; 32],
+            key_bundle_cid: String::new(),
+            key_epoch: 0,
+            key_rotation_pending: false,
+            bump: old.bump,
+            total_synced_messages: 0,
+        }
+        .try_serialize(&mut encoded)?;
+        return Ok((encoded, CHANNEL_ACCOUNT_SPACE));
+    }
+
+    if discriminator == ChannelMessage::DISCRIMINATOR && data.len() == legacy::CHANNEL_MESSAGE_SPACE {
+        let old = legacy::ChannelMessage::deserialize(&mut body)
+            .map_err(|_| PodComError::AccountNotMigratable)?;
+        ChannelMessage {
+            channel: old.channel,
+            sender: old.sender,
+            // Legacy replies never recorded their thread, so the parent stands in for its root
+            thread_root: old.reply_to.unwrap_or(account_key),
+            reply_to: old.reply_to,
+            created_at: old.created_at,
+            edited_at: old.edited_at,
+            content: old.content,
+            message_type: old.message_type,
+            bump: old.bump,
+            key_epoch: 0,
+            reply_count: 0,
+            reaction_counts: [0; REACTION_KIND_COUNT],
+            _reserved: [0; 2],
+        }
+        .try_serialize(&mut encoded)?;
+        return Ok((encoded, CHANNEL_MESSAGE_SPACE));
+    }
+
+    if discriminator == EscrowAccount::DISCRIMINATOR && data.len() == legacy::ESCROW_ACCOUNT_SPACE {
+        let old = legacy::EscrowAccount::deserialize(&mut body)
+            .map_err(|_| PodComError::AccountNotMigratable)?;
+        EscrowAccount {
+            channel: old.channel,
+            depositor: old.depositor,
+            amount: old.amount,
+            unclaimed_fees: 0,
+            created_at: old.created_at,
+            bump: old.bump,
+            _reserved: [0; 7],
+        }
+        .try_serialize(&mut encoded)?;
+        return Ok((encoded, ESCROW_ACCOUNT_SPACE));
+    }
+
+    Err(PodComError::AccountNotMigratable.into())
+}
+
+// Validate an outstanding invitation for `invitee`: unused, unexpired and with an untampered hash
+fn verify_invitation(invitation: &ChannelInvitation, invitee: &Pubkey, now: i64) -> Result<()> {
+    if invitation.invitee != *invitee {
+        return Err(PodComError::PrivateChannelRequiresInvitation.into());
+    }
+    if invitation.is_used {
+        return Err(PodComError::InvitationAlreadyUsed.into());
+    }
+    if now > invitation.expires_at {
+        return Err(PodComError::InvitationExpired.into());
+    }
+
+    // CRYPTOGRAPHIC VERIFICATION: Re-create and verify invitation hash
+    let mut hash_input = Vec::new();
+    hash_input.extend_from_slice(&invitation.channel.to_bytes());
+    hash_input.extend_from_slice(&invitation.inviter.to_bytes());
+    hash_input.extend_from_slice(&invitation.invitee.to_bytes());
+    hash_input.extend_from_slice(&invitation.nonce.to_le_bytes());
+    hash_input.extend_from_slice(&invitation.created_at.to_le_bytes());
+
+    let computed_hash = anchor_lang::solana_program::keccak::hash(&hash_input);
+
+    // Verify the invitation hash matches to prevent forgery
+    if computed_hash.to_bytes() != invitation.invitation_hash {
+        return Err(PodComError::Unauthorized.into());
+    }
+    Ok(())
+}
+
+// Reject posts from read-only or muted participants
+fn check_can_broadcast(participant: &ChannelParticipant) -> Result<()> {
+    if !participant.role.can_send_messages() {
+        return Err(PodComError::InsufficientChannelRole.into());
+    }
+    if participant.is_muted {
+        return Err(PodComError::ParticipantMuted.into());
+    }
+    Ok(())
+}
+
+// Check that the instruction just before this one is an Ed25519Program verification of
+// `expected_message` by `signer`, and return the verified signature
+fn verify_message_receipt(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    expected_message: &[u8],
+) -> Result<[u8; 64]> {
+    use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
+
+    let current_index = instructions::load_current_index_checked(instructions_sysvar)?;
+    let verify_index = current_index
+        .checked_sub(1)
+        .ok_or(PodComError::InvalidDeliveryReceipt)?;
+    let verify_ix = instructions::load_instruction_at_checked(verify_index as usize, instructions_sysvar)?;
+    if verify_ix.program_id != ed25519_program::ID {
+        return Err(PodComError::InvalidDeliveryReceipt.into());
+    }
+
+    // Layout: [count, padding, 7 x u16 offsets] then the signature, key and message it points to
+    let data = &verify_ix.data;
+    if data.len() < 16 || data[0] != 1 {
+        return Err(PodComError::InvalidDeliveryReceipt.into());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let (signature_offset, signature_ix) = (read_u16(2) as usize, read_u16(4));
+    let (pubkey_offset, pubkey_ix) = (read_u16(6) as usize, read_u16(8));
+    let (message_offset, message_size, message_ix) = (read_u16(10) as usize, read_u16(12) as usize, read_u16(14));
+
+    // Every field must live inside the verify instruction itself
+    if signature_ix != u16::MAX || pubkey_ix != u16::MAX || message_ix != u16::MAX {
+        return Err(PodComError::InvalidDeliveryReceipt.into());
+    }
+    let field = |offset: usize, len: usize| {
+        data.get(offset..offset + len)
+            .ok_or(PodComError::InvalidDeliveryReceipt)
+    };
+    if field(pubkey_offset, 32)? != signer.as_ref() || field(message_offset, message_size)? != expected_message {
+        return Err(PodComError::InvalidDeliveryReceipt.into());
+    }
+
+    let mut signature = [0u8; 64];
+    signature.copy_from_slice(field(signature_offset, 64)?);
+    Ok(signature)
+}
+
+// Count a reply on its parent message and return the thread it belongs to
+fn record_reply(parent: &mut Account<'_, ChannelMessage>, reply_to: Pubkey) -> Result<Pubkey> {
+    if parent.key() != reply_to {
+        return Err(PodComError::InvalidReplyTarget.into());
+    }
+    parent.reply_count = parent
+        .reply_count
+        .checked_add(1)
+        .ok_or(PodComError::InvalidReplyTarget)?;
+    Ok(parent.thread_root)
+}
+
+// Uncount a deleted reply on its parent; a parent that is gone (or re-created since) has nothing to update
+fn release_reply(parent_info: &AccountInfo<'_>, reply_to: Pubkey, reply_created_at: i64) -> Result<()> {
+    if *parent_info.key != reply_to {
+        return Err(PodComError::InvalidReplyTarget.into());
+    }
+    if parent_info.owner != &crate::ID || parent_info.data_is_empty() {
+        return Ok(());
+    }
+    let mut parent = Account::<ChannelMessage>::try_from(parent_info)?;
+    if parent.created_at > reply_created_at {
+        return Ok(());
+    }
+    parent.reply_count = parent
+        .reply_count
+        .checked_sub(1)
+        .ok_or(PodComError::InvalidReplyTarget)?;
+    parent.exit(&crate::ID)
+}
+
+// Private channels must re-key once a member is gone; the next rotate_channel_key clears the flag
+fn require_channel_key_rotation(channel: &mut ChannelAccount, channel_key: Pubkey, departed: Pubkey) -> Result<()> {
+    if channel.visibility != ChannelVisibility::Private {
+        return Ok(());
+    }
+    let clock = Clock::get()?;
+
+    channel.key_rotation_pending = true;
+
+    emit!(ChannelKeyRotationRequired {
+        channel: channel_key,
+        departed,
+        key_epoch: channel.key_epoch,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Private content must not be encrypted under a key a departed member still holds
+fn check_channel_key_current(channel: &ChannelAccount) -> Result<()> {
+    if channel.visibility == ChannelVisibility::Private && channel.key_rotation_pending {
+        return Err(PodComError::ChannelKeyRotationPending.into());
+    }
+    Ok(())
+}
+
+// Resolve the moderator's rank and ensure it outranks the target; returns the moderator's rank
+fn check_moderation_authority(
+    channel: &ChannelAccount,
+    moderator: &Pubkey,
+    moderator_participant: Option<&Account<'_, ChannelParticipant>>,
+    target: &ChannelParticipant,
+    target_owner: &Pubkey,
+) -> Result<u8> {
+    if !target.is_active {
+        return Err(PodComError::NotInChannel.into());
+    }
+
+    // Participant records of the creator's agents can never be moderated
+    if *target_owner == channel.creator {
+        return Err(PodComError::InsufficientChannelRole.into());
+    }
+
+    let moderator_rank = if *moderator == channel.creator {
+        u8::MAX
+    } else {
+        let participant = moderator_participant.ok_or(PodComError::InsufficientChannelRole)?;
+        if !participant.is_active {
+            return Err(PodComError::NotInChannel.into());
+        }
+        if !participant.role.can_moderate() {
+            return Err(PodComError::InsufficientChannelRole.into());
+        }
+        participant.role.rank()
+    };
+
+    if target.role.rank() >= moderator_rank {
+        return Err(PodComError::InsufficientChannelRole.into());
+    }
+
+    Ok(moderator_rank)
+}
+
+// Flip the protocol pause flag and record who did it
+fn set_protocol_paused(config: &mut Account<'_, ProtocolConfig>, is_paused: bool) -> Result<()> {
+    let clock = Clock::get()?;
+
+    if config.is_paused == is_paused {
+        return Err(PodComError::InvalidProtocolConfig.into());
+    }
+
+    config.is_paused = is_paused;
+    config.updated_at = clock.unix_timestamp;
+
+    emit!(ProtocolPauseChanged {
+        authority: config.authority,
+        is_paused,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Protocol paused: {}", is_paused);
+    Ok(())
+}
+
+// Move lamports out of a program-owned escrow PDA with checked arithmetic
+fn transfer_escrow_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_lamports = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(PodComError::InsufficientFunds)?;
+    let to_lamports = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(PodComError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+// True when none of the escrow's parties (wallets or agent PDAs) sits on the arbitrator set,
+// so neither side can vote on its own dispute
+fn arbitrator_set_is_independent(set: &ArbitratorSet, parties: &[Pubkey]) -> bool {
+    set.arbitrators[..set.arbitrator_count as usize]
+        .iter()
+        .all(|arbitrator| !parties.contains(arbitrator))
+}
+
+// Pay out what is left in a disputed escrow, `payee_share_bps` to the payee and the rest to
+// the payer, and mark both the escrow and its dispute settled. Returns (payee, payer) amounts.
+fn settle_disputed_escrow<'info>(
+    escrow: &mut Account<'info, AgentEscrowAccount>,
+    dispute: &mut Account<'info, EscrowDispute>,
+    payee_wallet: &AccountInfo<'info>,
+    payer_wallet: &AccountInfo<'info>,
+    payee_share_bps: u16,
+    now: i64,
+) -> Result<(u64, u64)> {
+    let remaining = escrow
+        .amount
+        .checked_sub(escrow.released_amount)
+        .ok_or(PodComError::InsufficientFunds)?;
+    let payee_amount = (remaining as u128)
+        .checked_mul(payee_share_bps as u128)
+        .and_then(|v| v.checked_div(BASIS_POINTS_DENOMINATOR as u128))
+        .and_then(|v| u64::try_from(v).ok())
+        .ok_or(PodComError::InsufficientFunds)?;
+    let payer_amount = remaining
+        .checked_sub(payee_amount)
+        .ok_or(PodComError::InsufficientFunds)?;
+
+    let escrow_info = escrow.to_account_info();
+    transfer_escrow_lamports(&escrow_info, payee_wallet, payee_amount)?;
+    transfer_escrow_lamports(&escrow_info, payer_wallet, payer_amount)?;
+
+    escrow.released_amount = escrow
+        .released_amount
+        .checked_add(payee_amount)
+        .ok_or(PodComError::InsufficientFunds)?;
+    escrow.status = AgentEscrowStatus::Resolved;
+    dispute.is_resolved = true;
+    dispute.resolved_at = now;
+
+    Ok((payee_amount, payer_amount))
+}
+
+// Median payee share of the votes cast so far (the lower one for an even count)
+fn median_dispute_vote(dispute: &EscrowDispute) -> Option<u16> {
+    let mut votes = [0u16; MAX_ARBITRATORS];
+    let mut count = 0;
+    for (i, vote) in dispute.votes.iter().enumerate() {
+        if dispute.voted_mask & (1 << i) != 0 {
+            votes[count] = *vote;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    let cast = &mut votes[..count];
+    cast.sort_unstable();
+    Some(cast[(count - 1) / 2])
+}
+
+// Debit the sender's escrow for one channel message; returns the remaining escrow balance.
+// Any error aborts the whole transaction, so a failed charge also rolls back the post.
+fn charge_message_fee<'info>(
+    channel: &mut Account<'info, ChannelAccount>,
+    escrow: Option<&mut Account<'info, EscrowAccount>>,
+    sender: &Pubkey,
+) -> Result<u64> {
+    let fee = channel.fee_per_message;
+    let escrow = escrow.ok_or(PodComError::InsufficientFunds)?;
+
+    // Escrow must belong to the sender and this channel
+    if escrow.depositor != *sender || escrow.channel != channel.key() {
+        return Err(PodComError::Unauthorized.into());
+    }
+
+    escrow.amount = escrow.amount.checked_sub(fee)
+        .ok_or(PodComError::InsufficientFunds)?;
+    // Lamports stay in the escrow PDA until the creator claims them
+    escrow.unclaimed_fees = escrow.unclaimed_fees.checked_add(fee)
+        .ok_or(PodComError::InsufficientFunds)?;
+    // The fee moves from the member's spendable balance to the creator's accrued fees
+    channel.escrow_balance = channel.escrow_balance.checked_sub(fee)
+        .ok_or(PodComError::InsufficientFunds)?;
+    channel.accrued_fees = channel.accrued_fees.checked_add(fee)
+        .ok_or(PodComError::InsufficientFunds)?;
+
+    emit!(MessageFeeCharged {
+        channel: channel.key(),
+        sender: *sender,
+        escrow: escrow.key(),
+        amount: fee,
+        remaining_escrow_balance: escrow.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(escrow.amount)
+}
+
 // SECURITY: Validate metadata URI format and prevent dangerous schemes
 fn is_valid_metadata_uri(uri: &str) -> bool {
     // Check for valid URL schemes only
@@ -685,106 +2578,495 @@ pub struct ParticipantExtendedMetadata {
 pub mod pod_com {
     use super::*;
 
-    // Register a new agent
-    pub fn register_agent(
-        ctx: Context<RegisterAgent>,
-        capabilities: u64,
-        metadata_uri: String,
+    // Create the protocol config PDA with default limits (program upgrade authority only)
+    pub fn initialize_protocol(ctx: Context<InitializeProtocol>) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        config.authority = ctx.accounts.authority.key();
+        config.pending_authority = Pubkey::default();
+        config.fee_recipient = ctx.accounts.authority.key();
+        config.min_reputation_for_channels = MIN_REPUTATION_FOR_CHANNELS;
+        config.max_fee_per_message = MAX_FEE_PER_MESSAGE;
+        config.max_escrow_deposit = MAX_ESCROW_DEPOSIT;
+        config.updated_at = clock.unix_timestamp;
+        config.rate_limit_messages_per_minute = RATE_LIMIT_MESSAGES_PER_MINUTE;
+        config.invite_rate_limit_per_hour = INVITE_RATE_LIMIT_PER_HOUR;
+        config.message_edit_window_seconds = DEFAULT_MESSAGE_EDIT_WINDOW_SECONDS;
+        config.max_invitation_expiry_seconds = DEFAULT_MAX_INVITATION_EXPIRY_SECONDS;
+        config.protocol_fee_bps = 0;
+        config.is_paused = false;
+        config.bump = ctx.bumps.protocol_config;
+
+        emit!(ProtocolConfigUpdated {
+            authority: config.authority,
+            min_reputation_for_channels: config.min_reputation_for_channels,
+            max_fee_per_message: config.max_fee_per_message,
+            max_escrow_deposit: config.max_escrow_deposit,
+            rate_limit_messages_per_minute: config.rate_limit_messages_per_minute,
+            invite_rate_limit_per_hour: config.invite_rate_limit_per_hour,
+            message_edit_window_seconds: config.message_edit_window_seconds,
+            max_invitation_expiry_seconds: config.max_invitation_expiry_seconds,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Protocol initialized with authority {:?}", config.authority);
+        Ok(())
+    }
+
+    // Tune protocol limits without a redeploy (authority only)
+    pub fn update_protocol_config(
+        ctx: Context<UpdateProtocolConfig>,
+        min_reputation_for_channels: Option<u64>,
+        rate_limit_messages_per_minute: Option<u16>,
+        invite_rate_limit_per_hour: Option<u16>,
+        max_fee_per_message: Option<u64>,
+        max_escrow_deposit: Option<u64>,
+        message_edit_window_seconds: Option<u32>,
+        max_invitation_expiry_seconds: Option<u32>,
     ) -> Result<()> {
-        // SECURITY: Comprehensive input validation
-        
-        // Validate metadata_uri format and content
-        if metadata_uri.trim().is_empty() {
-            return Err(PodComError::InvalidMetadataUriLength.into());
-        }
-        
-        // Enforce strict length limits
-        if metadata_uri.len() > MAX_METADATA_URI_LENGTH {
-            return Err(PodComError::InvalidMetadataUriLength.into());
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        if let Some(min_reputation) = min_reputation_for_channels {
+            if min_reputation > MAX_REPUTATION {
+                return Err(PodComError::InvalidProtocolConfig.into());
+            }
+            config.min_reputation_for_channels = min_reputation;
         }
-        
-        // Additional security validations
-        if metadata_uri.len() < 10 { // Minimum reasonable URL length
-            return Err(PodComError::InvalidMetadataUriLength.into());
+
+        // Zero limits would lock everyone out; pause is the tool for that
+        if let Some(rate_limit) = rate_limit_messages_per_minute {
+            if rate_limit == 0 {
+                return Err(PodComError::InvalidProtocolConfig.into());
+            }
+            config.rate_limit_messages_per_minute = rate_limit;
         }
-        
-        // Validate URL format and prevent dangerous schemes
-        if !super::is_valid_metadata_uri(&metadata_uri) {
-            return Err(PodComError::InvalidMetadataUriLength.into());
+
+        if let Some(invite_limit) = invite_rate_limit_per_hour {
+            if invite_limit == 0 {
+                return Err(PodComError::InvalidProtocolConfig.into());
+            }
+            config.invite_rate_limit_per_hour = invite_limit;
         }
-        
-        // Capabilities validation - prevent overflow and unreasonable values
-        if capabilities > u64::MAX / 2 {
-            return Err(PodComError::Unauthorized.into());
+
+        if let Some(max_fee) = max_fee_per_message {
+            config.max_fee_per_message = max_fee;
         }
-        
-        // Check for null bytes and other dangerous characters
-        if metadata_uri.contains('\0') || metadata_uri.contains('\r') || metadata_uri.contains('\n') {
-            return Err(PodComError::InvalidMetadataUriLength.into());
+
+        if let Some(max_deposit) = max_escrow_deposit {
+            if max_deposit == 0 {
+                return Err(PodComError::InvalidProtocolConfig.into());
+            }
+            config.max_escrow_deposit = max_deposit;
         }
 
-        let agent = &mut ctx.accounts.agent_account;
-        let clock = Clock::get()?;
+        // A zero window disables editing entirely
+        if let Some(edit_window) = message_edit_window_seconds {
+            config.message_edit_window_seconds = edit_window;
+        }
 
-        agent.pubkey = ctx.accounts.signer.key();
-        agent.capabilities = capabilities;
-        agent.metadata_uri = metadata_uri.clone();
-        agent.reputation = 100; // Initial reputation
-        agent.last_updated = clock.unix_timestamp;
-        agent.invites_sent = 0;
-        agent.last_invite_at = 0;
-        agent.bump = ctx.bumps.agent_account;
+        if let Some(max_expiry) = max_invitation_expiry_seconds {
+            if max_expiry == 0 {
+                return Err(PodComError::InvalidProtocolConfig.into());
+            }
+            config.max_invitation_expiry_seconds = max_expiry;
+        }
 
-        // Emit event for monitoring
-        emit!(AgentRegistered {
-            agent: agent.pubkey,
-            capabilities,
-            metadata_uri,
+        config.updated_at = clock.unix_timestamp;
+
+        emit!(ProtocolConfigUpdated {
+            authority: config.authority,
+            min_reputation_for_channels: config.min_reputation_for_channels,
+            max_fee_per_message: config.max_fee_per_message,
+            max_escrow_deposit: config.max_escrow_deposit,
+            rate_limit_messages_per_minute: config.rate_limit_messages_per_minute,
+            invite_rate_limit_per_hour: config.invite_rate_limit_per_hour,
+            message_edit_window_seconds: config.message_edit_window_seconds,
+            max_invitation_expiry_seconds: config.max_invitation_expiry_seconds,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!("Agent registered: {:?}", agent.pubkey);
+        msg!("Protocol config updated");
         Ok(())
     }
 
-    /// **DEPRECATED**: Use `broadcast_message_compressed` (ZK compression) for cost-efficient messaging instead of plain send_message
-    // Send a message from one agent to another (uncompressed, deprecated)
-    #[deprecated(
-        note = "Use broadcast_message_compressed for ZK compression instead of send_message"
-    )]
-    pub fn send_message(
+    // Start a two-step authority transfer; the new authority must accept
+    pub fn set_authority(ctx: Context<UpdateProtocolConfig>, new_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        if new_authority == Pubkey::default() || new_authority == config.authority {
+            return Err(PodComError::InvalidProtocolConfig.into());
+        }
+
+        config.pending_authority = new_authority;
+        config.updated_at = clock.unix_timestamp;
+
+        emit!(ProtocolAuthorityTransferStarted {
+            authority: config.authority,
+            pending_authority: new_authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Protocol authority transfer to {:?} pending", new_authority);
+        Ok(())
+    }
+
+    // Complete a pending authority transfer (pending authority only)
+    pub fn accept_authority(ctx: Context<AcceptProtocolAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        let previous_authority = config.authority;
+        config.authority = config.pending_authority;
+        config.pending_authority = Pubkey::default();
+        config.updated_at = clock.unix_timestamp;
+
+        emit!(ProtocolAuthorityTransferred {
+            previous_authority,
+            new_authority: config.authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Protocol authority transferred to {:?}", config.authority);
+        Ok(())
+    }
+
+    // Halt every user-facing mutating instruction during an incident
+    pub fn pause(ctx: Context<UpdateProtocolConfig>) -> Result<()> {
+        set_protocol_paused(&mut ctx.accounts.protocol_config, true)
+    }
+
+    // Resume normal operation after an incident
+    pub fn unpause(ctx: Context<UpdateProtocolConfig>) -> Result<()> {
+        set_protocol_paused(&mut ctx.accounts.protocol_config, false)
+    }
+
+    // Set the protocol's share of claimed channel fees and where it is paid
+    pub fn set_protocol_fee(
+        ctx: Context<UpdateProtocolConfig>,
+        protocol_fee_bps: u16,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        let clock = Clock::get()?;
+
+        if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+            return Err(PodComError::InvalidProtocolConfig.into());
+        }
+        if protocol_fee_bps > 0 && fee_recipient == Pubkey::default() {
+            return Err(PodComError::InvalidProtocolConfig.into());
+        }
+
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.fee_recipient = fee_recipient;
+        config.updated_at = clock.unix_timestamp;
+
+        emit!(ProtocolFeeUpdated {
+            authority: config.authority,
+            protocol_fee_bps,
+            fee_recipient,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Protocol fee set to {} bps", protocol_fee_bps);
+        Ok(())
+    }
+
+    // Register a new agent at the wallet's v1 PDA (`[b"agent", wallet]`)
+    pub fn register_agent(
+        ctx: Context<RegisterAgent>,
+        capabilities: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        super::init_agent_account(
+            &mut ctx.accounts.agent_account,
+            ctx.accounts.signer.key(),
+            String::new(),
+            capabilities,
+            metadata_uri,
+            ctx.bumps.agent_account,
+        )?;
+        super::restore_agent_reputation(
+            &mut ctx.accounts.agent_account,
+            &ctx.accounts.agent_tombstone,
+            &ctx.accounts.signer,
+        )
+    }
+
+    // Register a named agent at `[b"agent", wallet, name]`, allowing many agents per wallet
+    pub fn register_agent_v2(
+        ctx: Context<RegisterAgentV2>,
+        name: String,
+        capabilities: u64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        // The name is a PDA seed, so it is bounded by the seed length limit
+        if name.is_empty() || name.len() > MAX_AGENT_NAME_LENGTH {
+            return Err(PodComError::InvalidAgentName.into());
+        }
+
+        super::init_agent_account(
+            &mut ctx.accounts.agent_account,
+            ctx.accounts.signer.key(),
+            name,
+            capabilities,
+            metadata_uri,
+            ctx.bumps.agent_account,
+        )?;
+        super::restore_agent_reputation(
+            &mut ctx.accounts.agent_account,
+            &ctx.accounts.agent_tombstone,
+            &ctx.accounts.signer,
+        )
+    }
+
+    /// **DEPRECATED**: Use `broadcast_message_compressed` (ZK compression) for cost-efficient messaging instead of plain send_message
+    // Send a message from one agent to another (uncompressed, deprecated)
+    #[deprecated(
+        note = "Use broadcast_message_compressed for ZK compression instead of send_message"
+    )]
+    pub fn send_message(
         ctx: Context<SendMessage>,
         recipient: Pubkey,
         payload_hash: [u8; 32],
         message_type: MessageType,
     ) -> Result<()> {
-        let message = &mut ctx.accounts.message_account;
-        let clock = Clock::get()?;
+        write_direct_message(
+            ctx.accounts,
+            ctx.bumps.message_account,
+            ctx.bumps.recipient_inbox,
+            recipient,
+            payload_hash,
+            message_type,
+            [0u8; 32],
+            [0u8; 12],
+        )
+    }
 
-        // IMPORTANT: Use agent PDA as sender for consistency across all message types
-        // This ensures all messages are associated with registered agents, not raw wallets
-        message.sender = ctx.accounts.sender_agent.key();
-        message.recipient = recipient;
-        message.payload_hash = payload_hash;
-        message.message_type = message_type.clone();
-        message.created_at = clock.unix_timestamp;
-        message.expires_at = clock.unix_timestamp + MESSAGE_EXPIRATION_SECONDS;
-        message.status = MessageStatus::Pending;
-        message.bump = ctx.bumps.message_account;
+    // Send a direct message whose payload is end-to-end encrypted off-chain. The envelope
+    // header (sender's ephemeral X25519 key and AEAD nonce) is stored with the message so the
+    // recipient can derive the key; `payload_hash` commits to the ciphertext.
+    pub fn send_encrypted_message(
+        ctx: Context<SendMessage>,
+        recipient: Pubkey,
+        payload_hash: [u8; 32],
+        message_type: MessageType,
+        ephemeral_pubkey: [u8; 32],
+        envelope_nonce: [u8; 12],
+    ) -> Result<()> {
+        // An all-zero key marks a plaintext message
+        if ephemeral_pubkey == [0u8; 32] {
+            return Err(PodComError::InvalidMessageEnvelope.into());
+        }
 
-        // Emit event for monitoring
-        emit!(MessageSent {
-            sender: message.sender,
-            recipient: message.recipient,
+        write_direct_message(
+            ctx.accounts,
+            ctx.bumps.message_account,
+            ctx.bumps.recipient_inbox,
+            recipient,
+            payload_hash,
             message_type,
+            ephemeral_pubkey,
+            envelope_nonce,
+        )
+    }
+
+    // Permissionless crank: close an expired direct message and return its rent to the payer
+    pub fn close_expired_message(ctx: Context<CloseExpiredMessage>) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = &ctx.accounts.message_account;
+
+        if clock.unix_timestamp <= message.expires_at {
+            return Err(PodComError::MessageNotExpired.into());
+        }
+
+        // Pay the closer a small reward up front; `close = payer` sweeps the rest at exit
+        let reward = if ctx.accounts.closer.key() == message.payer {
+            0
+        } else {
+            CLOSE_EXPIRED_MESSAGE_REWARD.min(message.to_account_info().lamports())
+        };
+        if reward > 0 {
+            transfer_escrow_lamports(
+                &message.to_account_info(),
+                &ctx.accounts.closer.to_account_info(),
+                reward,
+            )?;
+        }
+
+        emit!(ExpiredMessageClosed {
+            message: message.key(),
+            payer: message.payer,
+            closer: ctx.accounts.closer.key(),
+            reward,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!(
-            "Message sent from {:?} to {:?}",
-            message.sender,
-            message.recipient
-        );
+        msg!("Expired message {:?} closed", message.key());
+        Ok(())
+    }
+
+    // Deregister an agent, refunding its rent to the owner
+    pub fn close_agent(ctx: Context<CloseAgent>) -> Result<()> {
+        let agent = &ctx.accounts.agent_account;
+        let clock = Clock::get()?;
+
+        // Escrows this agent funded reference its PDA and would be stranded without it
+        if agent.active_escrows > 0 {
+            return Err(PodComError::AgentHasActiveEscrows.into());
+        }
+
+        // SECURITY: Record the reputation so closing and re-registering can't reset it
+        let tombstone = &mut ctx.accounts.agent_tombstone;
+        tombstone.owner = agent.pubkey;
+        tombstone.reputation = agent.reputation;
+        tombstone.closed_at = clock.unix_timestamp;
+        tombstone.reputation_epoch = agent.reputation_epoch;
+        tombstone.epoch_reputation_delta = agent.epoch_reputation_delta;
+        tombstone.bump = ctx.bumps.agent_tombstone;
+
+        emit!(AgentClosed {
+            agent: agent.key(),
+            owner: agent.pubkey,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent {:?} closed", agent.key());
+        Ok(())
+    }
+
+    // Start moving an agent to a new wallet; Pubkey::default() cancels a pending transfer
+    pub fn transfer_agent_ownership(
+        ctx: Context<TransferAgentOwnership>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        let clock = Clock::get()?;
+
+        if new_owner == agent.pubkey {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        agent.pending_owner = new_owner;
+
+        emit!(AgentOwnershipTransferStarted {
+            agent: agent.key(),
+            owner: agent.pubkey,
+            pending_owner: new_owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent {:?} ownership transfer to {:?} pending", agent.key(), new_owner);
+        Ok(())
+    }
+
+    // Complete an ownership transfer: the agent is re-created at the new owner's PDA
+    // with its reputation intact, and the old account is closed to the previous owner
+    pub fn accept_agent_ownership(ctx: Context<AcceptAgentOwnership>) -> Result<()> {
+        let previous = &ctx.accounts.previous_agent;
+        let clock = Clock::get()?;
+
+        // Escrows funded by the old PDA must settle before the agent moves
+        if previous.active_escrows > 0 {
+            return Err(PodComError::AgentHasActiveEscrows.into());
+        }
+
+        let agent = &mut ctx.accounts.agent_account;
+        agent.pubkey = ctx.accounts.new_owner.key();
+        agent.capabilities = previous.capabilities;
+        agent.reputation = previous.reputation;
+        agent.last_updated = clock.unix_timestamp;
+        agent.metadata_uri = previous.metadata_uri.clone();
+        agent.name = previous.name.clone();
+        agent.invites_sent = previous.invites_sent;
+        agent.last_invite_at = previous.last_invite_at;
+        agent.direct_messages_sent = previous.direct_messages_sent;
+        agent.last_direct_message_at = previous.last_direct_message_at;
+        agent.reputation_epoch = previous.reputation_epoch;
+        agent.epoch_reputation_delta = previous.epoch_reputation_delta;
+        agent.active_escrows = 0;
+        agent.pending_owner = Pubkey::default();
+        agent.bump = ctx.bumps.agent_account;
+
+        emit!(AgentOwnershipTransferred {
+            previous_agent: previous.key(),
+            new_agent: agent.key(),
+            previous_owner: previous.pubkey,
+            new_owner: agent.pubkey,
+            reputation: agent.reputation,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent ownership transferred to {:?}", agent.pubkey);
+        Ok(())
+    }
+
+    // Delegate message permissions for an agent to a session signer (owner only)
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        session_signer: Pubkey,
+        permissions: u8,
+        channel: Pubkey,
+        spend_cap: u64,
+        expires_in: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        if permissions == 0 || permissions & !SESSION_PERMISSION_ALL != 0 {
+            return Err(PodComError::InvalidSessionKey.into());
+        }
+        if session_signer == ctx.accounts.owner.key() || session_signer == Pubkey::default() {
+            return Err(PodComError::InvalidSessionKey.into());
+        }
+        if expires_in <= 0 || expires_in > MAX_SESSION_KEY_DURATION_SECONDS {
+            return Err(PodComError::InvalidSessionKey.into());
+        }
+
+        let session = &mut ctx.accounts.session_key;
+        session.agent = ctx.accounts.agent_account.key();
+        session.owner = ctx.accounts.owner.key();
+        session.session_signer = session_signer;
+        session.channel = channel;
+        session.spend_cap = spend_cap;
+        session.spent = 0;
+        session.created_at = clock.unix_timestamp;
+        session.expires_at = clock
+            .unix_timestamp
+            .checked_add(expires_in)
+            .ok_or(PodComError::InvalidSessionKey)?;
+        session.permissions = permissions;
+        session.bump = ctx.bumps.session_key;
+
+        emit!(SessionKeyCreated {
+            session_key: session.key(),
+            agent: session.agent,
+            session_signer,
+            channel,
+            permissions,
+            spend_cap,
+            expires_at: session.expires_at,
+        });
+
+        msg!("Session key {:?} created for agent {:?}", session_signer, session.agent);
+        Ok(())
+    }
+
+    // Revoke a session key and return its rent to the owner
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        let session = &ctx.accounts.session_key;
+        let clock = Clock::get()?;
+
+        emit!(SessionKeyRevoked {
+            session_key: session.key(),
+            agent: session.agent,
+            session_signer: session.session_signer,
+            spent: session.spent,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Session key {:?} revoked", session.session_signer);
         Ok(())
     }
 
@@ -805,7 +3087,11 @@ pub mod pod_com {
 
         // Additional security: Verify PDA derivation to prevent substitution attacks
         let (expected_pda, _bump) = Pubkey::find_program_address(
-            &[b"agent", ctx.accounts.signer.key().as_ref()],
+            &[
+                b"agent",
+                ctx.accounts.signer.key().as_ref(),
+                ctx.accounts.agent_account.name.as_bytes(),
+            ],
             &crate::ID,
         );
         if ctx.accounts.agent_account.key() != expected_pda {
@@ -838,34 +3124,99 @@ pub mod pod_com {
     ) -> Result<()> {
         let message = &mut ctx.accounts.message_account;
         let clock = Clock::get()?;
+        let signer = ctx.accounts.signer.key();
+
+        if !message.status.can_transition_to(&new_status) {
+            return Err(PodComError::InvalidMessageStatusTransition.into());
+        }
 
-        // Verify the message hasn't expired
-        if clock.unix_timestamp > message.expires_at {
+        // Past expiry the only remaining move is to Expired, which in turn requires expiry
+        let is_expired = clock.unix_timestamp > message.expires_at;
+        if is_expired && new_status != MessageStatus::Expired {
             return Err(PodComError::MessageExpired.into());
         }
+        if !is_expired && new_status == MessageStatus::Expired {
+            return Err(PodComError::InvalidMessageStatusTransition.into());
+        }
 
         // Verify the caller is the recipient for certain status updates
+        let mut receipt_signature = [0u8; 64];
         match new_status {
             MessageStatus::Delivered | MessageStatus::Read => {
                 if ctx.accounts.recipient_agent.pubkey != message.recipient {
                     return Err(PodComError::Unauthorized.into());
                 }
+                // Acknowledgements carry the recipient's signature over the payload hash
+                receipt_signature = verify_message_receipt(
+                    &ctx.accounts.instructions_sysvar,
+                    &ctx.accounts.signer.key(),
+                    &message_receipt_bytes(&message.key(), &message.payload_hash, &new_status),
+                )?;
             }
-            MessageStatus::Failed => {
-                // Only sender or recipient can mark as failed
-                if ctx.accounts.signer.key() != message.sender
-                    && ctx.accounts.signer.key() != message.recipient
-                {
+            MessageStatus::Failed | MessageStatus::Expired => {
+                // Only the sender's owner or the recipient can close out a message
+                if signer != ctx.accounts.sender_agent.pubkey && signer != message.recipient {
                     return Err(PodComError::Unauthorized.into());
                 }
             }
-            _ => return Err(PodComError::InvalidMessageStatusTransition.into()),
+            MessageStatus::Pending => return Err(PodComError::InvalidMessageStatusTransition.into()),
         }
 
+        // Reward the sender when a message is read (the table allows that once), never for self-messages
+        let first_read = new_status == MessageStatus::Read;
+        // Compare owner wallets: one wallet may own several named agents
+        let is_self_message = ctx.accounts.sender_agent.pubkey == ctx.accounts.recipient_agent.pubkey;
+
         // Update status
-        message.status = new_status;
+        let previous_status = message.status.clone();
+        message.status = new_status.clone();
+        match new_status {
+            MessageStatus::Delivered => message.delivered_at = clock.unix_timestamp,
+            MessageStatus::Read => message.read_at = clock.unix_timestamp,
+            _ => {}
+        }
+
+        // Mirror the status into the inbox that indexed the message; it may only be left out
+        // once the ring buffer has overwritten the entry
+        let message_key = message.key();
+        if message.inbox_indexed {
+            let inbox = ctx
+                .accounts
+                .recipient_inbox
+                .as_mut()
+                .ok_or(PodComError::InvalidInbox)?;
+            match inbox.entries.iter_mut().find(|entry| entry.message == message_key) {
+                Some(entry) => entry.status = new_status.clone(),
+                None => {
+                    let overwritten = inbox.total_received > INBOX_CAPACITY as u64
+                        && inbox.entries.iter().all(|entry| entry.created_at >= message.created_at);
+                    if !overwritten {
+                        return Err(PodComError::InvalidInbox.into());
+                    }
+                }
+            }
+        }
+
+        emit!(MessageStatusChanged {
+            message: message.key(),
+            sender: message.sender,
+            recipient: message.recipient,
+            payload_hash: message.payload_hash,
+            previous_status,
+            new_status,
+            receipt_signature,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if first_read && !is_self_message {
+            apply_reputation_delta(
+                &mut ctx.accounts.sender_agent,
+                REPUTATION_MESSAGE_READ,
+                ReputationReason::MessageRead,
+            )?;
+        }
 
-        msg!("Message status updated to {:?}", message.status);
+        msg!("Message status updated to {:?}", ctx.accounts.message_account.status);
         Ok(())
     }
 
@@ -880,7 +3231,7 @@ pub mod pod_com {
     ) -> Result<()> {
         // Comprehensive input validation
         if name.trim().is_empty() {
-            return Err(PodComError::ChannelNameTooLong.into()); // Reusing error for empty name
+            return Err(PodComError::InvalidChannelName.into());
         }
         if name.len() > MAX_CHANNEL_NAME_LENGTH {
             return Err(PodComError::ChannelNameTooLong.into());
@@ -891,10 +3242,16 @@ pub mod pod_com {
         if max_participants == 0 || max_participants > MAX_PARTICIPANTS_PER_CHANNEL {
             return Err(PodComError::ChannelFull.into()); // Reusing error for invalid participant count
         }
-        if fee_per_message > 1_000_000_000 {
-            // Max 1 SOL per message
+        if fee_per_message > ctx.accounts.protocol_config.max_fee_per_message {
             return Err(PodComError::InsufficientFunds.into()); // Reusing error for excessive fee
         }
+        if ctx.accounts.agent_account.reputation < ctx.accounts.protocol_config.min_reputation_for_channels {
+            return Err(PodComError::InsufficientReputation.into());
+        }
+        // The stored name must match the PDA seed so the channel can sign CPIs
+        if name.trim() != name {
+            return Err(PodComError::InvalidChannelName.into());
+        }
 
         let channel = &mut ctx.accounts.channel_account;
         let clock = Clock::get()?;
@@ -907,6 +3264,7 @@ pub mod pod_com {
         channel.current_participants = 1; // Creator is first participant
         channel.fee_per_message = fee_per_message;
         channel.escrow_balance = 0;
+        channel.accrued_fees = 0;
         channel.created_at = clock.unix_timestamp;
         channel.bump = ctx.bumps.channel_account;
 
@@ -914,14 +3272,20 @@ pub mod pod_com {
         Ok(())
     }
 
-    // Deposit to escrow for a channel
+    // Deposit to escrow for a channel, opening the escrow on first use and topping it up after
     pub fn deposit_escrow(ctx: Context<DepositEscrow>, amount: u64) -> Result<()> {
         // Input validation
         if amount == 0 {
             return Err(PodComError::InsufficientFunds.into());
         }
-        if amount > 10_000_000_000 {
-            // Max 10 SOL per deposit
+        // The deposit cap bounds the escrow's balance, not each top-up
+        let new_amount = ctx
+            .accounts
+            .escrow_account
+            .amount
+            .checked_add(amount)
+            .ok_or(PodComError::InsufficientFunds)?;
+        if new_amount > ctx.accounts.protocol_config.max_escrow_deposit {
             return Err(PodComError::InsufficientFunds.into());
         }
 
@@ -942,18 +3306,24 @@ pub mod pod_com {
             ],
         )?;
 
-        // Initialize escrow account data
         let escrow = &mut ctx.accounts.escrow_account;
         let channel = &mut ctx.accounts.channel_account;
 
-        escrow.channel = channel.key();
-        escrow.depositor = ctx.accounts.depositor.key();
-        escrow.amount = amount;
-        escrow.created_at = clock.unix_timestamp;
-        escrow.bump = ctx.bumps.escrow_account;
+        // Initialize escrow account data on the first deposit
+        if escrow.depositor == Pubkey::default() {
+            escrow.channel = channel.key();
+            escrow.depositor = ctx.accounts.depositor.key();
+            escrow.unclaimed_fees = 0;
+            escrow.created_at = clock.unix_timestamp;
+            escrow.bump = ctx.bumps.escrow_account;
+        }
+        escrow.amount = new_amount;
 
         // Update channel escrow balance
-        channel.escrow_balance += amount;
+        channel.escrow_balance = channel
+            .escrow_balance
+            .checked_add(amount)
+            .ok_or(PodComError::InsufficientFunds)?;
 
         msg!("Deposited {} lamports to escrow", amount);
         Ok(())
@@ -988,934 +3358,3565 @@ pub mod pod_com {
         let channel = &mut ctx.accounts.channel_account;
 
         escrow.amount -= amount;
-        channel.escrow_balance -= amount;
+        channel.escrow_balance = channel
+            .escrow_balance
+            .checked_sub(amount)
+            .ok_or(PodComError::InsufficientFunds)?;
 
         msg!("Withdrew {} lamports from escrow", amount);
         Ok(())
     }
 
-    // Join a channel
-    pub fn join_channel(ctx: Context<JoinChannel>) -> Result<()> {
-        let channel = &mut ctx.accounts.channel_account;
-        let participant = &mut ctx.accounts.participant_account;
+    // Claim channel fees accrued in a participant's escrow (creator only)
+    pub fn claim_channel_fees(ctx: Context<ClaimChannelFees>) -> Result<()> {
         let clock = Clock::get()?;
 
-        // Check if channel is full
-        if channel.current_participants >= channel.max_participants {
-            return Err(PodComError::ChannelFull.into());
+        // Verify caller is the creator
+        if ctx.accounts.creator.key() != ctx.accounts.channel_account.creator {
+            return Err(PodComError::Unauthorized.into());
         }
 
-        // SECURITY FIX (HIGH-01): Enhanced atomic payment verification for premium channels
-        if channel.fee_per_message > 0 {
-            // Require escrow account for premium channels
-            let escrow = ctx.accounts.escrow_account
-                .as_ref()
-                .ok_or(PodComError::InsufficientFunds)?;
-            
-            // Verify escrow ownership and PDA derivation for security
-            if escrow.depositor != ctx.accounts.user.key() {
-                return Err(PodComError::Unauthorized.into());
-            }
-            
-            // Verify escrow PDA is correctly derived to prevent substitution attacks
-            let (expected_escrow_pda, _bump) = Pubkey::find_program_address(
-                &[b"escrow", channel.key().as_ref(), ctx.accounts.user.key().as_ref()],
-                &crate::ID
-            );
-            let escrow_account = ctx.accounts.escrow_account.as_ref()
-                .ok_or(PodComError::InsufficientFunds)?;
-            
-            if escrow_account.key() != expected_escrow_pda {
-                return Err(PodComError::Unauthorized.into());
-            }
-            
-            // Verify sufficient balance with overflow protection
-            if escrow.amount < channel.fee_per_message {
-                return Err(PodComError::InsufficientFunds.into());
-            }
-            
-            // ATOMIC OPERATION: Deduct fee and grant access in single transaction
-            let escrow_mut = ctx.accounts.escrow_account.as_mut()
-                .ok_or(PodComError::InsufficientFunds)?;
-            escrow_mut.amount = escrow_mut.amount.checked_sub(channel.fee_per_message)
-                .ok_or(PodComError::InsufficientFunds)?;
-            
-            // Update channel escrow balance atomically
-            channel.escrow_balance = channel.escrow_balance.checked_add(channel.fee_per_message)
-                .ok_or(PodComError::InsufficientFunds)?;
+        let amount = ctx.accounts.escrow_account.unclaimed_fees;
+        if amount == 0 {
+            return Err(PodComError::InsufficientFunds.into());
         }
 
-        // SECURITY ENHANCEMENT (MED-01): Enhanced private channel invitation verification
-        if channel.visibility == ChannelVisibility::Private {
-            if let Some(invitation) = &ctx.accounts.invitation_account {
-                // Basic validation checks
-                if invitation.invitee != ctx.accounts.user.key() {
-                    return Err(PodComError::PrivateChannelRequiresInvitation.into());
-                }
-                
-                if invitation.is_used || invitation.is_accepted {
-                    return Err(PodComError::PrivateChannelRequiresInvitation.into());
-                }
-                
-                if clock.unix_timestamp > invitation.expires_at {
-                    return Err(PodComError::MessageExpired.into());
-                }
-                
-                // CRYPTOGRAPHIC VERIFICATION: Re-create and verify invitation hash
-                let mut hash_input = Vec::new();
-                hash_input.extend_from_slice(&invitation.channel.to_bytes());
-                hash_input.extend_from_slice(&invitation.inviter.to_bytes());
-                hash_input.extend_from_slice(&invitation.invitee.to_bytes());
-                hash_input.extend_from_slice(&invitation.nonce.to_le_bytes());
-                hash_input.extend_from_slice(&invitation.created_at.to_le_bytes());
-                
-                let computed_hash = anchor_lang::solana_program::keccak::hash(&hash_input);
-                
-                // Verify the invitation hash matches to prevent forgery
-                if computed_hash.to_bytes() != invitation.invitation_hash {
-                    return Err(PodComError::Unauthorized.into());
-                }
-                
-            } else {
-                return Err(PodComError::PrivateChannelRequiresInvitation.into());
-            }
+        // SECURITY: Never drain the escrow below its rent-exempt minimum
+        let escrow_info = ctx.accounts.escrow_account.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(escrow_info.data_len());
+        let available = escrow_info
+            .lamports()
+            .checked_sub(rent_floor)
+            .ok_or(PodComError::InsufficientFunds)?;
+        if available < amount {
+            return Err(PodComError::InsufficientFunds.into());
         }
 
-        // Initialize participant account
-        participant.channel = channel.key();
-        participant.participant = ctx.accounts.agent_account.key(); // Use agent PDA
-        participant.joined_at = clock.unix_timestamp;
-        participant.is_active = true;
-        participant.messages_sent = 0;
-        participant.last_message_at = 0;
-        participant.bump = ctx.bumps.participant_account;
+        // Split the claim between the protocol and the channel creator
+        let protocol_fee = (amount as u128)
+            .checked_mul(ctx.accounts.protocol_config.protocol_fee_bps as u128)
+            .and_then(|v| v.checked_div(BASIS_POINTS_DENOMINATOR as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(PodComError::InsufficientFunds)?;
+        let creator_amount = amount
+            .checked_sub(protocol_fee)
+            .ok_or(PodComError::InsufficientFunds)?;
+
+        // Transfer SOL from escrow PDA to the channel creator and fee recipient
+        transfer_escrow_lamports(
+            &escrow_info,
+            &ctx.accounts.creator.to_account_info(),
+            creator_amount,
+        )?;
+        if protocol_fee > 0 {
+            transfer_escrow_lamports(
+                &escrow_info,
+                &ctx.accounts.fee_recipient.to_account_info(),
+                protocol_fee,
+            )?;
+        }
 
-        // Update channel participant count
-        channel.current_participants += 1;
+        // Update account data
+        let escrow = &mut ctx.accounts.escrow_account;
+        let channel = &mut ctx.accounts.channel_account;
 
-        // SECURITY ENHANCEMENT (MED-01): Mark invitation as used (single-use enforcement)
-        if channel.visibility == ChannelVisibility::Private {
-            if let Some(invitation) = &mut ctx.accounts.invitation_account {
-                invitation.is_accepted = true;
-                invitation.is_used = true; // Prevent reuse of the same invitation
+        escrow.unclaimed_fees = 0;
+        channel.accrued_fees = channel
+            .accrued_fees
+            .checked_sub(amount)
+            .ok_or(PodComError::InsufficientFunds)?;
+
+        emit!(ChannelFeesClaimed {
+            channel: channel.key(),
+            creator: channel.creator,
+            escrow: escrow.key(),
+            amount,
+            protocol_fee,
+            remaining_escrow_balance: escrow.amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Claimed {} lamports of channel fees", amount);
+        Ok(())
+    }
+
+    // Create a time-locked escrow from one agent to another, optionally split into milestones
+    pub fn create_agent_escrow(
+        ctx: Context<CreateAgentEscrow>,
+        escrow_id: [u8; 32],
+        amount: u64,
+        expires_at: i64,
+        milestone_amounts: Vec<u64>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        // Input validation
+        if amount == 0 || amount > ctx.accounts.protocol_config.max_escrow_deposit {
+            return Err(PodComError::InsufficientFunds.into());
+        }
+        if expires_at <= clock.unix_timestamp
+            || expires_at - clock.unix_timestamp > MAX_AGENT_ESCROW_DURATION_SECONDS
+        {
+            return Err(PodComError::InvalidEscrowTimeout.into());
+        }
+        // Self-escrows would farm reputation; compare wallets since one wallet can own several agents
+        if ctx.accounts.payer.key() == ctx.accounts.payee_agent.pubkey {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Milestones, when given, must account for the full amount
+        if milestone_amounts.len() > MAX_ESCROW_MILESTONES {
+            return Err(PodComError::InvalidEscrowMilestone.into());
+        }
+        let mut milestones = [EscrowMilestoneState::default(); MAX_ESCROW_MILESTONES];
+        let mut milestone_total: u64 = 0;
+        for (slot, milestone_amount) in milestones.iter_mut().zip(milestone_amounts.iter()) {
+            if *milestone_amount == 0 {
+                return Err(PodComError::InvalidEscrowMilestone.into());
             }
+            milestone_total = milestone_total
+                .checked_add(*milestone_amount)
+                .ok_or(PodComError::InvalidEscrowMilestone)?;
+            slot.amount = *milestone_amount;
+        }
+        if !milestone_amounts.is_empty() && milestone_total != amount {
+            return Err(PodComError::InvalidEscrowMilestone.into());
         }
 
-        msg!(
-            "User {:?} joined channel {:?}",
-            participant.participant,
-            channel.name
+        // Transfer SOL from payer to escrow PDA
+        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.escrow_account.key(),
+            amount,
         );
+
+        anchor_lang::solana_program::program::invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.escrow_account.to_account_info(),
+            ],
+        )?;
+
+        // The payer is pinned until the escrow settles
+        let payer_agent = &mut ctx.accounts.payer_agent;
+        payer_agent.active_escrows = payer_agent
+            .active_escrows
+            .checked_add(1)
+            .ok_or(PodComError::AgentHasActiveEscrows)?;
+
+        let escrow = &mut ctx.accounts.escrow_account;
+        escrow.payer = ctx.accounts.payer_agent.key();
+        escrow.payee = ctx.accounts.payee_agent.key();
+        escrow.payer_wallet = ctx.accounts.payer.key();
+        escrow.payee_wallet = ctx.accounts.payee_agent.pubkey;
+        escrow.escrow_id = escrow_id;
+        escrow.amount = amount;
+        escrow.released_amount = 0;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.expires_at = expires_at;
+        // SECURITY: The arbitrators must be independent of both sides of the escrow
+        if let Some(set) = &ctx.accounts.arbitrator_set {
+            let parties = [
+                ctx.accounts.payer.key(),
+                ctx.accounts.payee_agent.pubkey,
+                ctx.accounts.payer_agent.key(),
+                ctx.accounts.payee_agent.key(),
+            ];
+            if !arbitrator_set_is_independent(set, &parties) {
+                return Err(PodComError::InvalidArbitratorSet.into());
+            }
+        }
+
+        escrow.arbitrator_set = ctx
+            .accounts
+            .arbitrator_set
+            .as_ref()
+            .map(|set| set.key())
+            .unwrap_or_default();
+        escrow.milestones = milestones;
+        escrow.milestone_count = milestone_amounts.len() as u8;
+        escrow.status = AgentEscrowStatus::Active;
+        escrow.bump = ctx.bumps.escrow_account;
+
+        emit!(AgentEscrowCreated {
+            escrow: escrow.key(),
+            payer: escrow.payer,
+            payee: escrow.payee,
+            amount,
+            milestone_count: escrow.milestone_count,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent escrow created with {} lamports", amount);
         Ok(())
     }
 
-    // Leave a channel
-    pub fn leave_channel(ctx: Context<LeaveChannel>) -> Result<()> {
-        let channel = &mut ctx.accounts.channel_account;
-        let participant = &mut ctx.accounts.participant_account;
+    // Release everything still held in an agent escrow to the payee (payer only)
+    pub fn release_agent_escrow(ctx: Context<SettleAgentEscrow>) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
 
-        // Verify user is in the channel
-        if !participant.is_active {
-            return Err(PodComError::NotInChannel.into());
+        if escrow.status != AgentEscrowStatus::Active {
+            return Err(PodComError::EscrowNotActive.into());
         }
 
-        // Mark participant as inactive
-        participant.is_active = false;
+        let remaining = escrow
+            .amount
+            .checked_sub(escrow.released_amount)
+            .ok_or(PodComError::InsufficientFunds)?;
 
-        // Update channel participant count
-        channel.current_participants -= 1;
+        transfer_escrow_lamports(
+            &ctx.accounts.escrow_account.to_account_info(),
+            &ctx.accounts.payee_wallet.to_account_info(),
+            remaining,
+        )?;
 
-        msg!(
-            "User {:?} left channel {:?}",
-            participant.participant,
-            channel.name
-        );
+        let escrow = &mut ctx.accounts.escrow_account;
+        escrow.released_amount = escrow.amount;
+        escrow.status = AgentEscrowStatus::Released;
+        let is_self_escrow = escrow.payer_wallet == escrow.payee_wallet;
+        release_active_escrow(&mut ctx.accounts.payer_agent);
+
+        if !is_self_escrow {
+            apply_reputation_delta(
+                &mut ctx.accounts.payee_agent,
+                REPUTATION_ESCROW_COMPLETED,
+                ReputationReason::EscrowCompleted,
+            )?;
+        }
+        let escrow = &ctx.accounts.escrow_account;
+
+        emit!(AgentEscrowReleased {
+            escrow: escrow.key(),
+            payee: escrow.payee,
+            amount: remaining,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent escrow released {} lamports to payee", remaining);
         Ok(())
     }
 
-    // Broadcast message to a channel
-    pub fn broadcast_message(
-        ctx: Context<BroadcastMessage>,
-        content: String,
-        message_type: MessageType,
-        reply_to: Option<Pubkey>,
-        _nonce: u64,
+    // Approve a milestone and pay its share to the payee (payer only)
+    pub fn complete_agent_escrow_milestone(
+        ctx: Context<SettleAgentEscrow>,
+        milestone_index: u8,
     ) -> Result<()> {
-        let participant = &ctx.accounts.participant_account;
-        let channel = &ctx.accounts.channel_account;
-        let message = &mut ctx.accounts.message_account;
         let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
 
-        // SECURITY: Comprehensive message content validation
-        
-        // Validate content length
-        if content.len() > MAX_MESSAGE_CONTENT_LENGTH {
-            return Err(PodComError::MessageContentTooLong.into());
+        if escrow.status != AgentEscrowStatus::Active {
+            return Err(PodComError::EscrowNotActive.into());
         }
-        
-        // Reject empty messages
-        if content.trim().is_empty() {
-            return Err(PodComError::MessageContentTooLong.into());
+        if milestone_index >= escrow.milestone_count {
+            return Err(PodComError::InvalidEscrowMilestone.into());
         }
-        
-        // Validate message content for dangerous patterns
-        if !super::is_valid_message_content(&content) {
-            return Err(PodComError::MessageContentTooLong.into());
+
+        let milestone = escrow.milestones[milestone_index as usize];
+        if milestone.completed {
+            return Err(PodComError::InvalidEscrowMilestone.into());
         }
-        
-        // Additional safety checks
-        if content.len() > 10000 { // Extra safety beyond MAX_MESSAGE_CONTENT_LENGTH
-            return Err(PodComError::MessageContentTooLong.into());
+
+        transfer_escrow_lamports(
+            &ctx.accounts.escrow_account.to_account_info(),
+            &ctx.accounts.payee_wallet.to_account_info(),
+            milestone.amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow_account;
+        escrow.milestones[milestone_index as usize].completed = true;
+        escrow.released_amount = escrow
+            .released_amount
+            .checked_add(milestone.amount)
+            .ok_or(PodComError::InsufficientFunds)?;
+
+        // Last milestone settles the escrow
+        if escrow.released_amount == escrow.amount {
+            escrow.status = AgentEscrowStatus::Released;
+            let is_self_escrow = escrow.payer_wallet == escrow.payee_wallet;
+            release_active_escrow(&mut ctx.accounts.payer_agent);
+            if !is_self_escrow {
+                apply_reputation_delta(
+                    &mut ctx.accounts.payee_agent,
+                    REPUTATION_ESCROW_COMPLETED,
+                    ReputationReason::EscrowCompleted,
+                )?;
+            }
         }
+        let escrow = &ctx.accounts.escrow_account;
+
+        emit!(AgentEscrowMilestoneCompleted {
+            escrow: escrow.key(),
+            payee: escrow.payee,
+            milestone_index,
+            amount: milestone.amount,
+            released_amount: escrow.released_amount,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Verify user is an active participant
-        if !participant.is_active {
-            return Err(PodComError::NotInChannel.into());
+        msg!("Agent escrow milestone {} completed", milestone_index);
+        Ok(())
+    }
+
+    // Return unreleased funds to the payer once the escrow timeout has elapsed
+    pub fn refund_agent_escrow_after_timeout(ctx: Context<RefundAgentEscrow>) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
+
+        if escrow.status != AgentEscrowStatus::Active {
+            return Err(PodComError::EscrowNotActive.into());
+        }
+        if clock.unix_timestamp < escrow.expires_at {
+            return Err(PodComError::EscrowNotExpired.into());
         }
 
-        // SECURITY ENHANCEMENT (MED-02): Advanced sliding window rate limiting with burst protection
-        let current_time = clock.unix_timestamp;
-        let time_window = 60; // 1 minute window
-        let burst_limit = 10; // Maximum burst messages in 10 seconds
-        let burst_window = 10; // Burst detection window
+        let remaining = escrow
+            .amount
+            .checked_sub(escrow.released_amount)
+            .ok_or(PodComError::InsufficientFunds)?;
 
-        let participant = &mut ctx.accounts.participant_account;
-        
-        // Enhanced rate limiting algorithm with multiple time windows
-        if participant.last_message_at > 0 {
-            let time_since_last = current_time - participant.last_message_at;
+        transfer_escrow_lamports(
+            &ctx.accounts.escrow_account.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            remaining,
+        )?;
 
-            // ANTI-SPAM: Minimum time between messages (1 second cooldown)
-            if time_since_last < 1 {
-                return Err(PodComError::RateLimitExceeded.into());
-            }
+        let escrow = &mut ctx.accounts.escrow_account;
+        escrow.status = AgentEscrowStatus::Refunded;
+        release_active_escrow(&mut ctx.accounts.payer_agent);
 
-            // BURST DETECTION: Check for rapid-fire messages in short window
-            if time_since_last < burst_window {
-                // Count recent messages for burst detection
-                let recent_burst_count = if time_since_last < burst_window {
-                    participant.messages_sent.min(burst_limit + 1)
-                } else {
-                    0
-                };
-                
-                if recent_burst_count >= burst_limit {
-                    return Err(PodComError::RateLimitExceeded.into());
-                }
-            }
+        emit!(AgentEscrowRefunded {
+            escrow: escrow.key(),
+            payer: escrow.payer,
+            amount: remaining,
+            timestamp: clock.unix_timestamp,
+        });
 
-            // SLIDING WINDOW: Standard rate limiting over 1-minute window
-            if time_since_last < time_window {
-                if participant.messages_sent >= RATE_LIMIT_MESSAGES_PER_MINUTE as u64 {
-                    return Err(PodComError::RateLimitExceeded.into());
+        msg!("Agent escrow refunded {} lamports to payer", remaining);
+        Ok(())
+    }
+
+    // Close a settled agent escrow, refunding rent to the payer (payer only)
+    pub fn close_agent_escrow(ctx: Context<CloseAgentEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow_account;
+
+        match escrow.status {
+            AgentEscrowStatus::Released | AgentEscrowStatus::Refunded => {}
+            AgentEscrowStatus::Resolved => {
+                // The dispute PDA is keyed by the escrow address, so it must go too or a
+                // re-created escrow with the same id could never be disputed again
+                let dispute = ctx
+                    .accounts
+                    .dispute_account
+                    .as_ref()
+                    .ok_or(PodComError::AccountNotClosable)?;
+                let opener = ctx
+                    .accounts
+                    .dispute_opener
+                    .as_ref()
+                    .ok_or(PodComError::AccountNotClosable)?;
+                if opener.key() != dispute.opened_by {
+                    return Err(PodComError::Unauthorized.into());
                 }
-                // Use checked arithmetic to prevent overflow attacks
-                participant.messages_sent = participant.messages_sent.checked_add(1)
-                    .ok_or(PodComError::RateLimitExceeded)?;
-            } else {
-                // Reset counter for new time window
-                participant.messages_sent = 1;
+                dispute.close(opener.to_account_info())?;
+            }
+            AgentEscrowStatus::Active | AgentEscrowStatus::Disputed => {
+                return Err(PodComError::AccountNotClosable.into());
             }
-        } else {
-            // First message from this participant
-            participant.messages_sent = 1;
         }
-        
-        // Update timestamp for next rate limit calculation
-        participant.last_message_at = current_time;
-
-        // Initialize message
-        message.channel = channel.key();
-        // IMPORTANT: Use agent PDA as sender for consistency across all message types
-        // This ensures all messages are associated with registered agents, not raw wallets
-        message.sender = participant.participant; // This is the agent PDA
-        message.content = content;
-        message.message_type = message_type;
-        message.created_at = clock.unix_timestamp;
-        message.edited_at = None;
-        message.reply_to = reply_to;
-        message.bump = ctx.bumps.message_account;
 
-        msg!("Message broadcast to channel {:?}", channel.name);
+        msg!("Agent escrow {:?} closed", escrow.key());
         Ok(())
     }
 
-    // Invite user to private channel with cryptographic security
-    // SECURITY ENHANCEMENT (MED-01): Cryptographically secure single-use invitations
-    pub fn invite_to_channel(ctx: Context<InviteToChannel>, invitee: Pubkey, nonce: u64) -> Result<()> {
-        let channel = &ctx.accounts.channel_account;
-        let invitation = &mut ctx.accounts.invitation_account;
-        let inviter_agent = &mut ctx.accounts.agent_account;
+    // Register an m-of-n arbitrator set that escrows can designate for disputes (protocol authority only)
+    pub fn create_arbitrator_set(
+        ctx: Context<CreateArbitratorSet>,
+        set_id: u64,
+        arbitrators: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
         let clock = Clock::get()?;
 
-        // Only creator or existing participants can invite
-        if ctx.accounts.inviter.key() != channel.creator {
-            if let Some(participant) = &ctx.accounts.participant_account {
-                if !participant.is_active {
-                    return Err(PodComError::Unauthorized.into());
-                }
-            } else {
-                return Err(PodComError::Unauthorized.into());
-            }
+        if arbitrators.is_empty() || arbitrators.len() > MAX_ARBITRATORS {
+            return Err(PodComError::InvalidArbitratorSet.into());
+        }
+        if threshold == 0 || threshold as usize > arbitrators.len() {
+            return Err(PodComError::InvalidArbitratorSet.into());
         }
 
-        // Rate limiting per inviter to prevent spam
-        if inviter_agent.last_invite_at > 0 {
-            let elapsed = clock.unix_timestamp - inviter_agent.last_invite_at;
-            if elapsed < 3600 {
-                if inviter_agent.invites_sent >= INVITE_RATE_LIMIT_PER_HOUR {
-                    return Err(PodComError::RateLimitExceeded.into());
-                }
-                inviter_agent.invites_sent = inviter_agent
-                    .invites_sent
-                    .checked_add(1)
-                    .ok_or(PodComError::RateLimitExceeded)?;
-            } else {
-                inviter_agent.invites_sent = 1;
+        // SECURITY: Duplicate entries would let one arbitrator cast several votes
+        let mut slots = [Pubkey::default(); MAX_ARBITRATORS];
+        for (i, arbitrator) in arbitrators.iter().enumerate() {
+            if *arbitrator == Pubkey::default() || slots[..i].contains(arbitrator) {
+                return Err(PodComError::InvalidArbitratorSet.into());
             }
-        } else {
-            inviter_agent.invites_sent = 1;
+            slots[i] = *arbitrator;
         }
-        inviter_agent.last_invite_at = clock.unix_timestamp;
-
-        // Create cryptographic invitation hash to prevent forgery
-        // Hash = SHA256(channel + inviter + invitee + nonce + timestamp)
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&channel.key().to_bytes());
-        hash_input.extend_from_slice(&ctx.accounts.inviter.key().to_bytes());
-        hash_input.extend_from_slice(&invitee.to_bytes());
-        hash_input.extend_from_slice(&nonce.to_le_bytes());
-        hash_input.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
-        
-        // Use Solana's built-in keccak hash for the invitation verification
-        let invitation_hash = anchor_lang::solana_program::keccak::hash(&hash_input);
 
-        // Initialize secure invitation
-        invitation.channel = channel.key();
-        invitation.inviter = ctx.accounts.inviter.key();
-        invitation.invitee = invitee;
-        invitation.created_at = clock.unix_timestamp;
-        invitation.expires_at = clock.unix_timestamp + (7 * 24 * 60 * 60); // 7 days
-        invitation.is_accepted = false;
-        invitation.is_used = false; // Single-use enforcement
-        invitation.invitation_hash = invitation_hash.to_bytes();
-        invitation.nonce = nonce;
-        invitation.bump = ctx.bumps.invitation_account;
+        let set = &mut ctx.accounts.arbitrator_set;
+        set.authority = ctx.accounts.authority.key();
+        set.arbitrators = slots;
+        set.set_id = set_id;
+        set.arbitrator_count = arbitrators.len() as u8;
+        set.threshold = threshold;
+        set.bump = ctx.bumps.arbitrator_set;
+
+        emit!(ArbitratorSetCreated {
+            arbitrator_set: set.key(),
+            authority: set.authority,
+            arbitrators,
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
 
-        msg!(
-            "Secure invitation sent to {:?} for channel {:?} with hash {:?}",
-            invitee,
-            channel.name,
-            invitation_hash.to_bytes()
-        );
+        msg!("Arbitrator set created with threshold {}", threshold);
         Ok(())
     }
 
-    // Get channel participants (view function - would be called off-chain)
-    pub fn get_channel_participants(ctx: Context<GetChannelParticipants>) -> Result<Vec<Pubkey>> {
-        // Note: In Solana programs, this function returns empty as participant data
-        // is typically queried off-chain via getProgramAccounts RPC calls for efficiency.
-        // The channel account stores the current participant count, but individual
-        // participant pubkeys are stored in separate ChannelParticipant accounts.
+    // Freeze an agent escrow pending arbitration (payer or payee)
+    pub fn open_escrow_dispute(
+        ctx: Context<OpenEscrowDispute>,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
+        let opener = ctx.accounts.opener.key();
 
-        // For on-chain validation, we verify the channel exists and is active
-        let channel = &ctx.accounts.channel_account;
-        require!(channel.is_active, PodComError::NotInChannel);
-
-        // Return empty vector as participant enumeration is done off-chain
-        // Off-chain clients should use:
-        // - getProgramAccounts with ChannelParticipant discriminator
-        // - Filter by channel pubkey and is_active = true
-        Ok(vec![])
-    }
-
-    // Update channel settings (creator only)
-    pub fn update_channel(
-        ctx: Context<UpdateChannel>,
-        name: Option<String>,
-        description: Option<String>,
-        max_participants: Option<u32>,
-        fee_per_message: Option<u64>,
-        is_active: Option<bool>,
-    ) -> Result<()> {
-        let channel = &mut ctx.accounts.channel_account;
-
-        // Verify caller is the creator
-        if ctx.accounts.signer.key() != channel.creator {
+        if opener != escrow.payer_wallet && opener != escrow.payee_wallet {
             return Err(PodComError::Unauthorized.into());
         }
-
-        // Update fields if provided
-        if let Some(new_name) = name {
-            if new_name.len() > MAX_CHANNEL_NAME_LENGTH {
-                return Err(PodComError::ChannelNameTooLong.into());
-            }
-            channel.name = new_name;
+        if escrow.status != AgentEscrowStatus::Active {
+            return Err(PodComError::EscrowNotActive.into());
         }
-
-        if let Some(new_description) = description {
-            if new_description.len() > MAX_CHANNEL_DESCRIPTION_LENGTH {
-                return Err(PodComError::ChannelDescriptionTooLong.into());
-            }
-            channel.description = new_description;
+        if escrow.arbitrator_set == Pubkey::default() {
+            return Err(PodComError::InvalidArbitratorSet.into());
         }
 
-        if let Some(new_max) = max_participants {
-            // Don't allow reducing below current participants
-            if new_max < channel.current_participants {
-                return Err(PodComError::ChannelFull.into());
-            }
-            channel.max_participants = new_max;
-        }
+        let dispute = &mut ctx.accounts.dispute_account;
+        dispute.escrow = escrow.key();
+        dispute.arbitrator_set = escrow.arbitrator_set;
+        dispute.opened_by = opener;
+        dispute.reason_hash = reason_hash;
+        dispute.opened_at = clock.unix_timestamp;
+        dispute.resolved_at = 0;
+        dispute.votes = [0; MAX_ARBITRATORS];
+        dispute.evidence_count = 0;
+        dispute.voted_mask = 0;
+        dispute.is_resolved = false;
+        dispute.bump = ctx.bumps.dispute_account;
 
-        if let Some(new_fee) = fee_per_message {
-            channel.fee_per_message = new_fee;
-        }
+        let escrow = &mut ctx.accounts.escrow_account;
+        escrow.status = AgentEscrowStatus::Disputed;
 
-        if let Some(active) = is_active {
-            channel.is_active = active;
-        }
+        emit!(EscrowDisputeOpened {
+            escrow: escrow.key(),
+            dispute: dispute.key(),
+            opened_by: opener,
+            reason_hash,
+            timestamp: clock.unix_timestamp,
+        });
 
-        msg!("Channel {:?} updated", channel.name);
+        msg!("Dispute opened on agent escrow");
         Ok(())
     }
 
-    // Enhanced create channel with validation
-    pub fn create_channel_v2(
-        ctx: Context<CreateChannelV2>,
-        name: String,
-        description: String,
-        visibility: ChannelVisibility,
-        max_participants: u32,
-        fee_per_message: u64,
+    // Attach evidence (content hash + IPFS CID) to an open dispute (payer or payee)
+    pub fn submit_escrow_evidence(
+        ctx: Context<SubmitEscrowEvidence>,
+        evidence_hash: [u8; 32],
+        ipfs_cid: String,
     ) -> Result<()> {
-        let agent = &ctx.accounts.agent_account;
-        let channel = &mut ctx.accounts.channel_account;
-        let participant = &mut ctx.accounts.participant_account;
         let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
+        let submitter = ctx.accounts.submitter.key();
 
-        // Validate agent reputation
-        if agent.reputation < MIN_REPUTATION_FOR_CHANNELS {
-            return Err(PodComError::InsufficientReputation.into());
+        if submitter != escrow.payer_wallet && submitter != escrow.payee_wallet {
+            return Err(PodComError::Unauthorized.into());
         }
 
-        // Validate input lengths
-        if name.len() > MAX_CHANNEL_NAME_LENGTH {
-            return Err(PodComError::ChannelNameTooLong.into());
-        }
-        if description.len() > MAX_CHANNEL_DESCRIPTION_LENGTH {
-            return Err(PodComError::ChannelDescriptionTooLong.into());
+        // Validate IPFS CID format to prevent injection attacks
+        if ipfs_cid.is_empty()
+            || ipfs_cid.len() > MAX_EVIDENCE_CID_LENGTH
+            || !ipfs_cid.chars().all(|c| c.is_alphanumeric())
+        {
+            return Err(PodComError::InvalidMetadataUriLength.into()); // Reusing error for invalid CID
         }
 
-        // Validate max participants
-        if max_participants > MAX_PARTICIPANTS_PER_CHANNEL {
-            return Err(PodComError::ChannelFull.into());
+        let dispute = &mut ctx.accounts.dispute_account;
+        if dispute.is_resolved {
+            return Err(PodComError::DisputeNotOpen.into());
         }
+        dispute.evidence_count = dispute
+            .evidence_count
+            .checked_add(1)
+            .ok_or(PodComError::DisputeNotOpen)?;
+
+        emit!(EscrowEvidenceSubmitted {
+            escrow: escrow.key(),
+            dispute: dispute.key(),
+            submitter,
+            evidence_hash,
+            ipfs_cid,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Initialize channel
-        channel.creator = ctx.accounts.creator.key();
-        channel.name = name;
-        channel.description = description;
-        channel.visibility = visibility;
-        channel.max_participants = max_participants;
-        channel.current_participants = 1; // Creator is first participant
-        channel.fee_per_message = fee_per_message;
-        channel.escrow_balance = 0;
-        channel.created_at = clock.unix_timestamp;
-        channel.is_active = true;
-        channel.bump = ctx.bumps.channel_account;
-
-        // Add creator as first participant
-        participant.channel = channel.key();
-        participant.participant = ctx.accounts.agent_account.key(); // Use agent PDA
-        participant.joined_at = clock.unix_timestamp;
-        participant.is_active = true;
-        participant.messages_sent = 0;
-        participant.last_message_at = 0;
-        participant.bump = ctx.bumps.participant_account;
-
-        msg!("Enhanced channel created: {:?}", channel.name);
+        msg!("Evidence #{} submitted for escrow dispute", dispute.evidence_count);
         Ok(())
     }
 
-    // =============================================================================
-    // ZK COMPRESSION FUNCTIONS - SECURITY CRITICAL
-    // =============================================================================
-    
-    /*
-     * SECURITY NOTICE (AUD-2024-05): ZK COMPRESSION FUNCTIONS
-     *
-     * These functions integrate with Light Protocol for Zero-Knowledge compression.
-     * This logic has undergone an internal security audit and is considered stable
-     * for beta deployments. External review is recommended before mainnet usage.
-     * 
-     * KNOWN RISKS:
-     * - Proof forgery attacks if verification logic is flawed
-     * - Data corruption if compression/decompression fails
-     * - State inconsistency between on-chain and off-chain data
-     * - Potential for DOS attacks via malformed proofs
-     * 
-     * Recommended best practices:
-     * 1. Independent security audit by cryptography experts
-     * 2. Extensive testing with malicious inputs
-     * 3. Formal verification of proof systems
-     * 4. Bug bounty program focused on ZK components
-     */
-
-    /// Broadcast a compressed message to a channel with IPFS content storage
-    /// NOTICE: This function relies on audited ZK compression logic - see security notice above
-    pub fn broadcast_message_compressed(
-        ctx: Context<BroadcastMessageCompressed>,
-        content: String,
-        message_type: MessageType,
-        reply_to: Option<Pubkey>,
-        ipfs_hash: String,
+    // Vote on how to split a disputed escrow; settles once `threshold` arbitrators agree.
+    // Arbitrators may change their vote until then, so a split panel can converge.
+    pub fn resolve_escrow_dispute(
+        ctx: Context<ResolveEscrowDispute>,
+        payee_share_bps: u16,
     ) -> Result<()> {
-        let participant = &ctx.accounts.participant_account;
-        let channel = &ctx.accounts.channel_account;
         let clock = Clock::get()?;
 
-        // SECURITY CHECKS (CRIT-01): Comprehensive validation for ZK compression
-        
-        // Validate content length for IPFS storage with stricter limits for compression
-        if content.len() > MAX_MESSAGE_CONTENT_LENGTH * 10 {
-            return Err(PodComError::MessageContentTooLong.into());
-        }
-        
-        // Validate IPFS hash format to prevent injection attacks
-        if ipfs_hash.is_empty() || ipfs_hash.len() > 100 || !ipfs_hash.chars().all(|c| c.is_alphanumeric()) {
-            return Err(PodComError::InvalidMetadataUriLength.into()); // Reusing error for invalid hash
+        if payee_share_bps > BASIS_POINTS_DENOMINATOR {
+            return Err(PodComError::InvalidDisputeResolution.into());
         }
 
-        // Verify user is an active participant with additional PDA validation
-        if !participant.is_active {
-            return Err(PodComError::NotInChannel.into());
-        }
-        
-        // Verify participant PDA derivation to prevent substitution attacks
-        let agent_account = &ctx.accounts.participant_account;
-        let (expected_participant_pda, _bump) = Pubkey::find_program_address(
-            &[b"participant", channel.key().as_ref(), agent_account.participant.as_ref()],
-            &crate::ID
-        );
-        if ctx.accounts.participant_account.key() != expected_participant_pda {
-            return Err(PodComError::Unauthorized.into());
-        }
-        
-        // Additional security: Verify all Light Protocol accounts are legitimate
-        // This helps prevent malicious account substitution in ZK operations
-        if ctx.accounts.system_program.key() != anchor_lang::system_program::ID {
-            return Err(PodComError::Unauthorized.into());
+        let set = &ctx.accounts.arbitrator_set;
+        let arbitrator = ctx.accounts.arbitrator.key();
+        let slot = set.arbitrators[..set.arbitrator_count as usize]
+            .iter()
+            .position(|a| *a == arbitrator)
+            .ok_or(PodComError::NotArbitrator)?;
+        let threshold = set.threshold;
+
+        // Record this arbitrator's vote
+        let dispute = &mut ctx.accounts.dispute_account;
+        if dispute.is_resolved {
+            return Err(PodComError::DisputeNotOpen.into());
         }
+        dispute.voted_mask |= 1 << slot;
+        dispute.votes[slot] = payee_share_bps;
+
+        emit!(EscrowDisputeVoted {
+            escrow: dispute.escrow,
+            dispute: dispute.key(),
+            arbitrator,
+            payee_share_bps,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Rate limiting (same as regular messages)
-        let current_time = clock.unix_timestamp;
-        let participant = &mut ctx.accounts.participant_account;
-
-        if participant.last_message_at > 0 {
-            let time_since_last = current_time - participant.last_message_at;
-            if time_since_last < 1 {
-                return Err(PodComError::RateLimitExceeded.into());
-            }
-            if time_since_last < 60 {
-                if participant.messages_sent >= RATE_LIMIT_MESSAGES_PER_MINUTE as u64 {
-                    return Err(PodComError::RateLimitExceeded.into());
-                }
-                participant.messages_sent += 1;
-            } else {
-                participant.messages_sent = 1;
-            }
-        } else {
-            participant.messages_sent = 1;
+        // Settle only when enough arbitrators agree on the same split
+        let matching_votes = (0..MAX_ARBITRATORS)
+            .filter(|i| dispute.voted_mask & (1 << i) != 0 && dispute.votes[*i] == payee_share_bps)
+            .count();
+        if matching_votes < threshold as usize {
+            msg!("Dispute vote recorded ({}/{})", matching_votes, threshold);
+            return Ok(());
         }
-        participant.last_message_at = current_time;
 
-        // Create content hash using secure memory and Light Protocol's Poseidon hasher
-        let content_hash = secure_hash_data(content.as_bytes())?;
+        let (payee_amount, payer_amount) = settle_disputed_escrow(
+            &mut ctx.accounts.escrow_account,
+            &mut ctx.accounts.dispute_account,
+            &ctx.accounts.payee_wallet.to_account_info(),
+            &ctx.accounts.payer_wallet.to_account_info(),
+            payee_share_bps,
+            clock.unix_timestamp,
+        )?;
+        release_active_escrow(&mut ctx.accounts.payer_agent);
+
+        // The side awarded less than half of the escrow lost the dispute. Only an independent
+        // set may cost reputation, or a party on the set could vote its counterparty down.
+        let half = BASIS_POINTS_DENOMINATOR / 2;
+        let parties = [
+            ctx.accounts.payer_wallet.key(),
+            ctx.accounts.payee_wallet.key(),
+            ctx.accounts.payer_agent.key(),
+            ctx.accounts.payee_agent.key(),
+        ];
+        if !arbitrator_set_is_independent(&ctx.accounts.arbitrator_set, &parties) {
+            msg!("Arbitrator set includes an escrow party; reputation unchanged");
+        } else if payee_share_bps < half {
+            apply_reputation_delta(
+                &mut ctx.accounts.payee_agent,
+                REPUTATION_DISPUTE_LOST,
+                ReputationReason::DisputeLost,
+            )?;
+        } else if payee_share_bps > half {
+            apply_reputation_delta(
+                &mut ctx.accounts.payer_agent,
+                REPUTATION_DISPUTE_LOST,
+                ReputationReason::DisputeLost,
+            )?;
+        }
+        let escrow = &ctx.accounts.escrow_account;
+        let dispute = &ctx.accounts.dispute_account;
+
+        emit!(EscrowDisputeResolved {
+            escrow: escrow.key(),
+            dispute: dispute.key(),
+            payee_share_bps,
+            payee_amount,
+            payer_amount,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Create compressed message data (temporarily stored as regular account data)
-        let _compressed_message = CompressedChannelMessage {
-            channel: channel.key(),
-            sender: participant.participant,
-            content_hash,
-            ipfs_hash: ipfs_hash.clone(),
-            message_type,
-            created_at: clock.unix_timestamp,
-            edited_at: None,
-            reply_to,
-        };
+        msg!("Escrow dispute resolved: {} to payee, {} to payer", payee_amount, payer_amount);
+        Ok(())
+    }
 
+    // Fallback for a dispute the arbitrators never settled (payer or payee): once the dispute
+    // timeout has passed, split on the median of the votes cast, or refund the payer if none were
+    pub fn settle_expired_escrow_dispute(ctx: Context<SettleExpiredEscrowDispute>) -> Result<()> {
+        let clock = Clock::get()?;
+        let escrow = &ctx.accounts.escrow_account;
+        let settler = ctx.accounts.settler.key();
 
+        if settler != escrow.payer_wallet && settler != escrow.payee_wallet {
+            return Err(PodComError::Unauthorized.into());
+        }
 
-        // Emit event for indexing
-        emit!(MessageBroadcast {
-            channel: channel.key(),
-            sender: participant.participant,
-            message_type,
+        let dispute = &ctx.accounts.dispute_account;
+        if dispute.is_resolved {
+            return Err(PodComError::DisputeNotOpen.into());
+        }
+        let deadline = dispute
+            .opened_at
+            .checked_add(ESCROW_DISPUTE_TIMEOUT_SECONDS)
+            .ok_or(PodComError::InvalidEscrowTimeout)?;
+        if clock.unix_timestamp < deadline {
+            return Err(PodComError::EscrowNotExpired.into());
+        }
+        let payee_share_bps = median_dispute_vote(dispute).unwrap_or(0);
+
+        let (payee_amount, payer_amount) = settle_disputed_escrow(
+            &mut ctx.accounts.escrow_account,
+            &mut ctx.accounts.dispute_account,
+            &ctx.accounts.payee_wallet.to_account_info(),
+            &ctx.accounts.payer_wallet.to_account_info(),
+            payee_share_bps,
+            clock.unix_timestamp,
+        )?;
+        release_active_escrow(&mut ctx.accounts.payer_agent);
+
+        emit!(EscrowDisputeResolved {
+            escrow: ctx.accounts.escrow_account.key(),
+            dispute: ctx.accounts.dispute_account.key(),
+            payee_share_bps,
+            payee_amount,
+            payer_amount,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!(
-            "Compressed message broadcasted to channel: {:?}, IPFS: {}",
-            channel.name,
-            ipfs_hash
-        );
+        msg!("Expired escrow dispute settled: {} to payee, {} to payer", payee_amount, payer_amount);
         Ok(())
     }
 
-    /// Join a channel with compressed participant data
-    pub fn join_channel_compressed(
-        ctx: Context<JoinChannelCompressed>,
-        metadata_hash: [u8; 32],
-    ) -> Result<()> {
+    // Join a channel
+    pub fn join_channel(ctx: Context<JoinChannel>) -> Result<()> {
         let channel = &mut ctx.accounts.channel_account;
-        let agent = &ctx.accounts.agent_account;
+        let participant = &mut ctx.accounts.participant_account;
         let clock = Clock::get()?;
 
-        // SECURITY FIX: Verify agent account belongs to the authority
-        if agent.pubkey != ctx.accounts.authority.key() {
-            return Err(PodComError::Unauthorized.into());
-        }
-
-        // Check channel capacity
+        // Check if channel is full
         if channel.current_participants >= channel.max_participants {
             return Err(PodComError::ChannelFull.into());
         }
 
-        // For private channels, verify invitation
-        if channel.visibility == ChannelVisibility::Private {
-            let invitation = &ctx
-                .accounts
-                .invitation_account
+        // SECURITY FIX (HIGH-01): Enhanced atomic payment verification for premium channels
+        if channel.fee_per_message > 0 {
+            // Require escrow account for premium channels
+            let escrow = ctx.accounts.escrow_account
                 .as_ref()
-                .ok_or(PodComError::PrivateChannelRequiresInvitation)?;
-
-            if invitation.invitee != ctx.accounts.authority.key() {
+                .ok_or(PodComError::InsufficientFunds)?;
+            
+            // Verify escrow ownership and PDA derivation for security
+            if escrow.depositor != ctx.accounts.user.key() {
                 return Err(PodComError::Unauthorized.into());
             }
-            if clock.unix_timestamp > invitation.expires_at {
-                return Err(PodComError::MessageExpired.into());
+            
+            // Verify escrow PDA is correctly derived to prevent substitution attacks
+            let (expected_escrow_pda, _bump) = Pubkey::find_program_address(
+                &[b"escrow", channel.key().as_ref(), ctx.accounts.user.key().as_ref()],
+                &crate::ID
+            );
+            let escrow_account = ctx.accounts.escrow_account.as_ref()
+                .ok_or(PodComError::InsufficientFunds)?;
+            
+            if escrow_account.key() != expected_escrow_pda {
+                return Err(PodComError::Unauthorized.into());
             }
-            if !invitation.is_accepted {
-                return Err(PodComError::Unauthorized.into());
+            
+            // Require enough balance for at least one message; fees are charged per broadcast
+            if escrow.amount < channel.fee_per_message {
+                return Err(PodComError::InsufficientFunds.into());
             }
         }
 
-        // Use provided metadata_hash for participant compression
-        let metadata_hash = metadata_hash;
-
-        let _compressed_participant = CompressedChannelParticipant {
-            channel: channel.key(),
-            participant: agent.key(),
-            joined_at: clock.unix_timestamp,
-            messages_sent: 0,
-            last_message_at: 0,
-            metadata_hash,
-        };
-
+        // SECURITY ENHANCEMENT (MED-01): Enhanced private channel invitation verification
+        if channel.visibility == ChannelVisibility::Private {
+            if let Some(invitation) = &ctx.accounts.invitation_account {
+                // An invitation accepted ahead of time via accept_invitation is still valid here
+                verify_invitation(invitation, &ctx.accounts.user.key(), clock.unix_timestamp)?;
+            } else {
+                return Err(PodComError::PrivateChannelRequiresInvitation.into());
+            }
+        }
 
+        // Initialize participant account
+        participant.channel = channel.key();
+        participant.participant = ctx.accounts.agent_account.key(); // Use agent PDA
+        participant.joined_at = clock.unix_timestamp;
+        participant.is_active = true;
+        participant.role = ChannelRole::Member;
+        participant.is_muted = false;
+        participant.messages_sent = 0;
+        participant.last_message_at = 0;
+        participant.bump = ctx.bumps.participant_account;
 
         // Update channel participant count
         channel.current_participants += 1;
 
-        // Emit event
-        emit!(ChannelJoined {
-            channel: channel.key(),
-            participant: agent.key(),
+        // SECURITY ENHANCEMENT (MED-01): Mark invitation as used (single-use enforcement)
+        if channel.visibility == ChannelVisibility::Private {
+            if let Some(invitation) = &mut ctx.accounts.invitation_account {
+                invitation.is_accepted = true;
+                invitation.is_used = true; // Prevent reuse of the same invitation
+
+                // Credit the inviter when their agent account is supplied
+                if let Some(inviter_agent) = &mut ctx.accounts.inviter_agent {
+                    if inviter_agent.pubkey != invitation.inviter {
+                        return Err(PodComError::Unauthorized.into());
+                    }
+                    // One wallet inviting its own agents would farm reputation
+                    if inviter_agent.pubkey != ctx.accounts.user.key() {
+                        apply_reputation_delta(
+                            inviter_agent,
+                            REPUTATION_INVITATION_ACCEPTED,
+                            ReputationReason::InvitationAccepted,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        msg!(
+            "User {:?} joined channel {:?}",
+            participant.participant,
+            channel.name
+        );
+        Ok(())
+    }
+
+    // Promote or demote a participant (admins manage non-admins, the creator manages everyone)
+    pub fn set_participant_role(ctx: Context<ModerateParticipant>, role: ChannelRole) -> Result<()> {
+        let clock = Clock::get()?;
+        let moderator_rank = check_moderation_authority(
+            &ctx.accounts.channel_account,
+            &ctx.accounts.moderator.key(),
+            ctx.accounts.moderator_participant.as_ref(),
+            &ctx.accounts.target_participant,
+            &ctx.accounts.target_agent.pubkey,
+        )?;
+
+        // Only admins change roles, and nobody can grant a role at or above their own
+        if moderator_rank < ChannelRole::Admin.rank() || role.rank() >= moderator_rank {
+            return Err(PodComError::InsufficientChannelRole.into());
+        }
+
+        let target = &mut ctx.accounts.target_participant;
+        target.role = role;
+
+        emit!(ParticipantRoleChanged {
+            channel: target.channel,
+            participant: target.participant,
+            moderator: ctx.accounts.moderator.key(),
+            role,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!("Agent joined channel with compression: {:?}", channel.name);
+        msg!("Participant {:?} role set to {:?}", target.participant, role);
         Ok(())
     }
 
-    /// Batch sync compressed messages - periodically sync state to chain
-    pub fn batch_sync_compressed_messages(
-        ctx: Context<BatchSyncCompressedMessages>,
-        message_hashes: Vec<[u8; 32]>,
-        sync_timestamp: i64,
-    ) -> Result<()> {
+    // Remove a participant from a channel (moderators and above)
+    pub fn kick_participant(ctx: Context<ModerateParticipant>) -> Result<()> {
+        let clock = Clock::get()?;
+        check_moderation_authority(
+            &ctx.accounts.channel_account,
+            &ctx.accounts.moderator.key(),
+            ctx.accounts.moderator_participant.as_ref(),
+            &ctx.accounts.target_participant,
+            &ctx.accounts.target_agent.pubkey,
+        )?;
+
         let channel = &mut ctx.accounts.channel_account;
+        let target = &mut ctx.accounts.target_participant;
+
+        target.is_active = false;
+        target.is_kicked = true;
+        channel.current_participants = channel
+            .current_participants
+            .checked_sub(1)
+            .ok_or(PodComError::NotInChannel)?;
+
+        let channel_key = channel.key();
+        require_channel_key_rotation(channel, channel_key, target.participant)?;
+
+        emit!(ParticipantKicked {
+            channel: channel_key,
+            participant: target.participant,
+            moderator: ctx.accounts.moderator.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Participant {:?} kicked from channel {:?}", target.participant, channel.name);
+        Ok(())
+    }
+
+    // Mute or unmute a participant (moderators and above)
+    pub fn mute_participant(ctx: Context<ModerateParticipant>, is_muted: bool) -> Result<()> {
         let clock = Clock::get()?;
+        check_moderation_authority(
+            &ctx.accounts.channel_account,
+            &ctx.accounts.moderator.key(),
+            ctx.accounts.moderator_participant.as_ref(),
+            &ctx.accounts.target_participant,
+            &ctx.accounts.target_agent.pubkey,
+        )?;
 
-        // Validate batch size (prevent spam)
-        if message_hashes.len() > 100 {
-            return Err(PodComError::RateLimitExceeded.into());
+        let target = &mut ctx.accounts.target_participant;
+        target.is_muted = is_muted;
+
+        emit!(ParticipantMuteChanged {
+            channel: target.channel,
+            participant: target.participant,
+            moderator: ctx.accounts.moderator.key(),
+            is_muted,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Participant {:?} muted: {}", target.participant, is_muted);
+        Ok(())
+    }
+
+    // Leave a channel
+    pub fn leave_channel(ctx: Context<LeaveChannel>) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let participant = &mut ctx.accounts.participant_account;
+
+        // Verify user is in the channel
+        if !participant.is_active {
+            return Err(PodComError::NotInChannel.into());
         }
 
-        // Verify authority is channel creator or has permissions
-        if channel.creator != ctx.accounts.authority.key() {
+        // Mark participant as inactive
+        participant.is_active = false;
+
+        // Update channel participant count
+        channel.current_participants -= 1;
+
+        let channel_key = channel.key();
+        require_channel_key_rotation(channel, channel_key, participant.participant)?;
+
+        msg!(
+            "User {:?} left channel {:?}",
+            participant.participant,
+            channel.name
+        );
+        Ok(())
+    }
+
+    // Leave a channel (if still active) and close the participant account, refunding rent
+    pub fn leave_and_close_channel(ctx: Context<LeaveAndCloseChannel>) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let participant = &ctx.accounts.participant_account;
+
+        // SECURITY: The record carries moderation state; closing it would let the member rejoin clean
+        if participant.is_kicked || participant.is_muted || participant.role == ChannelRole::ReadOnly {
+            return Err(PodComError::ParticipantUnderModeration.into());
+        }
+
+        // Kicked or departed participants were already removed from the count (and re-keyed)
+        if participant.is_active {
+            channel.current_participants = channel
+                .current_participants
+                .checked_sub(1)
+                .ok_or(PodComError::NotInChannel)?;
+
+            let channel_key = channel.key();
+            require_channel_key_rotation(channel, channel_key, participant.participant)?;
+        }
+
+        msg!(
+            "User {:?} left channel {:?} and closed participant account",
+            participant.participant,
+            channel.name
+        );
+        Ok(())
+    }
+
+    // Close a used invitation, refunding rent to the inviter (inviter or invitee may call)
+    pub fn close_channel_invitation(ctx: Context<CloseChannelInvitation>) -> Result<()> {
+        let invitation = &ctx.accounts.invitation_account;
+        let authority = ctx.accounts.authority.key();
+
+        if authority != invitation.inviter && authority != invitation.invitee {
             return Err(PodComError::Unauthorized.into());
         }
+        if !invitation.is_used {
+            return Err(PodComError::AccountNotClosable.into());
+        }
 
-        // Validate sync timestamp is reasonable (within 1 hour of current time)
-        let current_timestamp = clock.unix_timestamp;
-        let time_diff = (current_timestamp - sync_timestamp).abs();
-        if time_diff > 3600 {
-            return Err(PodComError::InvalidTimestamp.into());
+        msg!("Invitation for {:?} closed", invitation.invitee);
+        Ok(())
+    }
+
+    // Close an emptied channel escrow, refunding rent to the depositor
+    pub fn close_escrow(ctx: Context<CloseEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow_account;
+
+        // Both the depositor's balance and the creator's unclaimed fees must be gone
+        if escrow.amount > 0 || escrow.unclaimed_fees > 0 {
+            return Err(PodComError::AccountNotClosable.into());
         }
 
-        // Light Protocol batch compression implementation
-        let mut compressed_data = Vec::new();
-        let mut total_size = 0u64;
+        msg!("Escrow {:?} closed", escrow.key());
+        Ok(())
+    }
+
+    // Broadcast message to a channel
+    pub fn broadcast_message(
+        ctx: Context<BroadcastMessage>,
+        content: String,
+        message_type: MessageType,
+        reply_to: Option<Pubkey>,
+        _nonce: u64,
+    ) -> Result<()> {
+        let participant = &ctx.accounts.participant_account;
+        let channel = &ctx.accounts.channel_account;
+        let message = &mut ctx.accounts.message_account;
+        let clock = Clock::get()?;
+
+        // SECURITY: Comprehensive message content validation
         
-        for (i, hash) in message_hashes.iter().enumerate() {
-            // Verify hash format and create compressed account entry
-            if hash.iter().all(|&b| b == 0) {
-                return Err(PodComError::InvalidMessageHash.into());
+        // Validate content length
+        if content.len() > MAX_MESSAGE_CONTENT_LENGTH {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        
+        // Reject empty messages
+        if content.trim().is_empty() {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        
+        // Validate message content for dangerous patterns
+        if !super::is_valid_message_content(&content) {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        
+        // Additional safety checks
+        if content.len() > 10000 { // Extra safety beyond MAX_MESSAGE_CONTENT_LENGTH
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+
+        // Verify user is an active participant
+        if !participant.is_active {
+            return Err(PodComError::NotInChannel.into());
+        }
+
+        // Read-only and muted participants cannot post
+        check_can_broadcast(participant)?;
+        check_channel_key_current(channel)?;
+
+        authorize_agent_signer(
+            &ctx.accounts.agent_account,
+            &ctx.accounts.user.key(),
+            ctx.accounts.session_key.as_mut(),
+            SESSION_PERMISSION_BROADCAST,
+            Some(channel.key()),
+            channel.fee_per_message,
+        )?;
+
+        // SECURITY ENHANCEMENT (MED-02): Advanced sliding window rate limiting with burst protection
+        let rate_limit_per_minute = ctx.accounts.protocol_config.rate_limit_messages_per_minute as u64;
+        let current_time = clock.unix_timestamp;
+        let time_window = 60; // 1 minute window
+        let burst_limit = 10; // Maximum burst messages in 10 seconds
+        let burst_window = 10; // Burst detection window
+
+        let participant = &mut ctx.accounts.participant_account;
+        
+        // Enhanced rate limiting algorithm with multiple time windows
+        if participant.last_message_at > 0 {
+            let time_since_last = current_time - participant.last_message_at;
+
+            // ANTI-SPAM: Minimum time between messages (1 second cooldown)
+            if time_since_last < 1 {
+                return Err(PodComError::RateLimitExceeded.into());
             }
 
-            // Create Poseidon hash for Light Protocol compatibility
-            let compressed_hash = *blake3::hash(hash).as_bytes();
+            // BURST DETECTION: Check for rapid-fire messages in short window
+            if time_since_last < burst_window {
+                // Count recent messages for burst detection
+                let recent_burst_count = if time_since_last < burst_window {
+                    participant.messages_sent.min(burst_limit + 1)
+                } else {
+                    0
+                };
+                
+                if recent_burst_count >= burst_limit {
+                    return Err(PodComError::RateLimitExceeded.into());
+                }
+            }
 
-            // Store compressed account data
-            compressed_data.push(compressed_hash);
-            total_size += 32; // Each hash is 32 bytes
+            // SLIDING WINDOW: Standard rate limiting over 1-minute window
+            if time_since_last < time_window {
+                if participant.messages_sent >= rate_limit_per_minute {
+                    return Err(PodComError::RateLimitExceeded.into());
+                }
+                // Use checked arithmetic to prevent overflow attacks
+                participant.messages_sent = participant.messages_sent.checked_add(1)
+                    .ok_or(PodComError::RateLimitExceeded)?;
+            } else {
+                // Reset counter for new time window
+                participant.messages_sent = 1;
+            }
+        } else {
+            // First message from this participant
+            participant.messages_sent = 1;
+        }
+        
+        // Update timestamp for next rate limit calculation
+        participant.last_message_at = current_time;
 
-            // Emit event for indexing
-            emit!(CompressedMessageSynced {
-                channel_id: channel.key(),
-                message_hash: *hash,
-                compressed_hash,
-                batch_index: i as u32,
-                sync_timestamp,
-            });
+        // Paid channels debit the sender's escrow for every message
+        if ctx.accounts.channel_account.fee_per_message > 0 {
+            let sender = ctx.accounts.agent_account.pubkey;
+            charge_message_fee(
+                &mut ctx.accounts.channel_account,
+                ctx.accounts.escrow_account.as_mut(),
+                &sender,
+            )?;
         }
+        let channel = &ctx.accounts.channel_account;
 
-        // Update channel state with batch sync info
-        channel.last_sync_timestamp = sync_timestamp;
-        channel.total_compressed_messages = channel.total_compressed_messages.saturating_add(message_hashes.len() as u64);
-        channel.compressed_data_size = channel.compressed_data_size.saturating_add(total_size);
+        // Replies must pass their parent so it can count them; top-level messages root their own thread
+        let thread_root = match reply_to {
+            Some(parent_key) => {
+                let parent = ctx
+                    .accounts
+                    .parent_message
+                    .as_mut()
+                    .ok_or(PodComError::InvalidReplyTarget)?;
+                record_reply(parent, parent_key)?
+            }
+            None => message.key(),
+        };
 
-        // Calculate compression ratio (estimated)
-        let original_size = message_hashes.len() as u64 * 1024; // Assume 1KB per message
-        let compression_ratio = if total_size > 0 {
-            (original_size * 100) / total_size
-        } else {
-            100
+        // Initialize message
+        message.channel = channel.key();
+        // IMPORTANT: Use agent PDA as sender for consistency across all message types
+        // This ensures all messages are associated with registered agents, not raw wallets
+        message.sender = participant.participant; // This is the agent PDA
+        message.thread_root = thread_root;
+        message.content = content;
+        message.message_type = message_type;
+        message.created_at = clock.unix_timestamp;
+        message.edited_at = None;
+        message.reply_to = reply_to;
+        message.bump = ctx.bumps.message_account;
+        message.key_epoch = channel.key_epoch;
+
+        msg!("Message broadcast to channel {:?}", channel.name);
+        Ok(())
+    }
+
+    // Edit a channel message within the protocol's edit window (sender only)
+    pub fn edit_channel_message(ctx: Context<EditChannelMessage>, new_content: String) -> Result<()> {
+        let clock = Clock::get()?;
+
+        // Same content rules as broadcast_message
+        if new_content.len() > MAX_MESSAGE_CONTENT_LENGTH {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        if new_content.trim().is_empty() {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        if !super::is_valid_message_content(&new_content) {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+
+        // Muted or demoted participants lose the ability to rewrite history too
+        check_can_broadcast(&ctx.accounts.participant_account)?;
+        check_channel_key_current(&ctx.accounts.channel_account)?;
+
+        authorize_agent_signer(
+            &ctx.accounts.agent_account,
+            &ctx.accounts.user.key(),
+            ctx.accounts.session_key.as_mut(),
+            SESSION_PERMISSION_EDIT_MESSAGE,
+            Some(ctx.accounts.message_account.channel),
+            0,
+        )?;
+
+        let message = &mut ctx.accounts.message_account;
+        let edit_window = ctx.accounts.protocol_config.message_edit_window_seconds as i64;
+        let elapsed = clock
+            .unix_timestamp
+            .checked_sub(message.created_at)
+            .ok_or(PodComError::MessageEditWindowExpired)?;
+        if elapsed > edit_window {
+            return Err(PodComError::MessageEditWindowExpired.into());
+        }
+
+        // Only hashes are emitted so indexers can audit edits without storing old content
+        let previous_content_hash =
+            anchor_lang::solana_program::keccak::hash(message.content.as_bytes()).to_bytes();
+        let new_content_hash =
+            anchor_lang::solana_program::keccak::hash(new_content.as_bytes()).to_bytes();
+
+        message.content = new_content;
+        message.edited_at = Some(clock.unix_timestamp);
+        // Edits are re-encrypted under the channel's current key
+        message.key_epoch = ctx.accounts.channel_account.key_epoch;
+
+        emit!(ChannelMessageEdited {
+            message: message.key(),
+            channel: message.channel,
+            sender: message.sender,
+            previous_content_hash,
+            new_content_hash,
+            edited_at: clock.unix_timestamp,
+        });
+
+        msg!("Channel message {:?} edited", message.key());
+        Ok(())
+    }
+
+    // Delete a channel message, closing the account and refunding rent to the sender
+    pub fn delete_channel_message(ctx: Context<DeleteChannelMessage>) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = &ctx.accounts.message_account;
+
+        // Replies must pass their parent so its reply_count stays accurate
+        if let Some(parent_key) = message.reply_to {
+            let parent = ctx
+                .accounts
+                .parent_message
+                .as_ref()
+                .ok_or(PodComError::InvalidReplyTarget)?;
+            release_reply(&parent.to_account_info(), parent_key, message.created_at)?;
+        }
+
+        emit!(ChannelMessageDeleted {
+            message: message.key(),
+            channel: message.channel,
+            sender: message.sender,
+            content_hash: anchor_lang::solana_program::keccak::hash(message.content.as_bytes())
+                .to_bytes(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel message {:?} deleted", message.key());
+        Ok(())
+    }
+
+    // React to a channel message; each agent may leave each reaction kind once
+    pub fn add_reaction(ctx: Context<AddReaction>, reaction: ReactionKind) -> Result<()> {
+        let clock = Clock::get()?;
+
+        // Read-only and muted participants cannot react either
+        check_can_broadcast(&ctx.accounts.participant_account)?;
+
+        let message = &mut ctx.accounts.message_account;
+        let slot = &mut message.reaction_counts[reaction.index()];
+        *slot = slot.checked_add(1).ok_or(PodComError::ReactionLimitReached)?;
+        let count = *slot;
+
+        let record = &mut ctx.accounts.reaction_account;
+        record.message = message.key();
+        record.reactor = ctx.accounts.agent_account.key();
+        record.created_at = clock.unix_timestamp;
+        record.reaction = reaction;
+        record.bump = ctx.bumps.reaction_account;
+
+        emit!(ReactionAdded {
+            message: record.message,
+            reactor: record.reactor,
+            reaction,
+            count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Reaction {:?} added to message {:?}", reaction, record.message);
+        Ok(())
+    }
+
+    // Withdraw a reaction, closing its record and refunding rent to the agent owner
+    // The message may already be deleted; the record can still be closed to recover its rent
+    pub fn remove_reaction(ctx: Context<RemoveReaction>) -> Result<()> {
+        let clock = Clock::get()?;
+        let record = &ctx.accounts.reaction_account;
+        let reaction = record.reaction;
+
+        let message_info = ctx.accounts.message_account.to_account_info();
+        let message_exists = message_info.owner == &crate::ID && !message_info.data_is_empty();
+        let count = if message_exists {
+            let mut message = Account::<ChannelMessage>::try_from(&message_info)?;
+            // A message re-created at the same address after deletion never counted this reaction
+            if message.created_at > record.created_at {
+                0
+            } else {
+                let slot = &mut message.reaction_counts[reaction.index()];
+                *slot = slot.checked_sub(1).ok_or(PodComError::ReactionLimitReached)?;
+                let count = *slot;
+                message.exit(&crate::ID)?;
+                count
+            }
+        } else {
+            0
         };
 
-        msg!(
-            "Batch synced {} compressed messages at timestamp: {}, compression ratio: {}%, total size: {} bytes",
-            message_hashes.len(),
-            sync_timestamp,
-            compression_ratio,
-            total_size
-        );
-        
-        Ok(())
-    }
+        emit!(ReactionRemoved {
+            message: record.message,
+            reactor: record.reactor,
+            reaction,
+            count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Reaction {:?} removed from message {:?}", reaction, record.message);
+        Ok(())
+    }
+
+    // Invite user to private channel with cryptographic security
+    // SECURITY ENHANCEMENT (MED-01): Cryptographically secure single-use invitations
+    pub fn invite_to_channel(
+        ctx: Context<InviteToChannel>,
+        invitee: Pubkey,
+        nonce: u64,
+        expires_in: i64,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let invitation = &mut ctx.accounts.invitation_account;
+        let inviter_agent = &mut ctx.accounts.agent_account;
+        let clock = Clock::get()?;
+
+        // Expiry is chosen by the inviter within the protocol's bound
+        if expires_in <= 0
+            || expires_in > ctx.accounts.protocol_config.max_invitation_expiry_seconds as i64
+        {
+            return Err(PodComError::InvalidInvitationExpiry.into());
+        }
+
+        // Only the creator or participants whose role allows inviting can invite
+        if ctx.accounts.inviter.key() != channel.creator {
+            if let Some(participant) = &ctx.accounts.participant_account {
+                if !participant.is_active {
+                    return Err(PodComError::Unauthorized.into());
+                }
+                if !participant.role.can_invite_members() {
+                    return Err(PodComError::InsufficientChannelRole.into());
+                }
+            } else {
+                return Err(PodComError::Unauthorized.into());
+            }
+        }
+
+        // Rate limiting per inviter to prevent spam
+        if inviter_agent.last_invite_at > 0 {
+            let elapsed = clock.unix_timestamp - inviter_agent.last_invite_at;
+            if elapsed < 3600 {
+                if inviter_agent.invites_sent >= ctx.accounts.protocol_config.invite_rate_limit_per_hour {
+                    return Err(PodComError::RateLimitExceeded.into());
+                }
+                inviter_agent.invites_sent = inviter_agent
+                    .invites_sent
+                    .checked_add(1)
+                    .ok_or(PodComError::RateLimitExceeded)?;
+            } else {
+                inviter_agent.invites_sent = 1;
+            }
+        } else {
+            inviter_agent.invites_sent = 1;
+        }
+        inviter_agent.last_invite_at = clock.unix_timestamp;
+
+        // Create cryptographic invitation hash to prevent forgery
+        // Hash = SHA256(channel + inviter + invitee + nonce + timestamp)
+        let mut hash_input = Vec::new();
+        hash_input.extend_from_slice(&channel.key().to_bytes());
+        hash_input.extend_from_slice(&ctx.accounts.inviter.key().to_bytes());
+        hash_input.extend_from_slice(&invitee.to_bytes());
+        hash_input.extend_from_slice(&nonce.to_le_bytes());
+        hash_input.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
+        
+        // Use Solana's built-in keccak hash for the invitation verification
+        let invitation_hash = anchor_lang::solana_program::keccak::hash(&hash_input);
+
+        // Initialize secure invitation
+        invitation.channel = channel.key();
+        invitation.inviter = ctx.accounts.inviter.key();
+        invitation.invitee = invitee;
+        invitation.created_at = clock.unix_timestamp;
+        invitation.expires_at = clock
+            .unix_timestamp
+            .checked_add(expires_in)
+            .ok_or(PodComError::InvalidInvitationExpiry)?;
+        invitation.is_accepted = false;
+        invitation.is_used = false; // Single-use enforcement
+        invitation.invitation_hash = invitation_hash.to_bytes();
+        invitation.nonce = nonce;
+        invitation.bump = ctx.bumps.invitation_account;
+
+        msg!(
+            "Secure invitation sent to {:?} for channel {:?} with hash {:?}",
+            invitee,
+            channel.name,
+            invitation_hash.to_bytes()
+        );
+        Ok(())
+    }
+
+    // Accept an invitation without joining yet; join_channel or join_channel_compressed consume it later
+    pub fn accept_invitation(ctx: Context<AcceptInvitation>) -> Result<()> {
+        let invitation = &mut ctx.accounts.invitation_account;
+        let clock = Clock::get()?;
+
+        verify_invitation(invitation, &ctx.accounts.invitee.key(), clock.unix_timestamp)?;
+        if invitation.is_accepted {
+            return Err(PodComError::InvitationAlreadyUsed.into());
+        }
+
+        invitation.is_accepted = true;
+
+        emit!(InvitationAccepted {
+            channel: invitation.channel,
+            invitee: invitation.invitee,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Invitation to channel {:?} accepted", invitation.channel);
+        Ok(())
+    }
+
+    // Revoke an outstanding invitation (inviter or channel creator), refunding rent to the inviter
+    pub fn revoke_invitation(ctx: Context<RevokeInvitation>) -> Result<()> {
+        let invitation = &ctx.accounts.invitation_account;
+        let authority = ctx.accounts.authority.key();
+        let clock = Clock::get()?;
+
+        if authority != invitation.inviter && authority != ctx.accounts.channel_account.creator {
+            return Err(PodComError::Unauthorized.into());
+        }
+        if invitation.is_used {
+            return Err(PodComError::InvitationAlreadyUsed.into());
+        }
+
+        emit!(InvitationRevoked {
+            channel: invitation.channel,
+            invitee: invitation.invitee,
+            revoked_by: authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Invitation for {:?} revoked", invitation.invitee);
+        Ok(())
+    }
+
+    // Create a multi-use invite code for a private channel (same authority as invite_to_channel)
+    pub fn create_invite_code(
+        ctx: Context<CreateInviteCode>,
+        code_signer: Pubkey,
+        max_uses: u32,
+        expires_in: i64,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let invite_code = &mut ctx.accounts.invite_code;
+        let clock = Clock::get()?;
+
+        // Only the creator or participants whose role allows inviting can issue codes
+        if ctx.accounts.creator.key() != channel.creator {
+            let participant = ctx
+                .accounts
+                .participant_account
+                .as_ref()
+                .ok_or(PodComError::Unauthorized)?;
+            if !participant.role.can_invite_members() {
+                return Err(PodComError::InsufficientChannelRole.into());
+            }
+        }
+
+        if max_uses == 0 || max_uses > MAX_INVITE_CODE_USES {
+            return Err(PodComError::InvalidInviteCodeMaxUses.into());
+        }
+        if expires_in <= 0
+            || expires_in > ctx.accounts.protocol_config.max_invitation_expiry_seconds as i64
+        {
+            return Err(PodComError::InvalidInvitationExpiry.into());
+        }
+
+        invite_code.channel = channel.key();
+        invite_code.creator = ctx.accounts.creator.key();
+        invite_code.code_signer = code_signer;
+        invite_code.created_at = clock.unix_timestamp;
+        invite_code.expires_at = clock
+            .unix_timestamp
+            .checked_add(expires_in)
+            .ok_or(PodComError::InvalidInvitationExpiry)?;
+        invite_code.max_uses = max_uses;
+        invite_code.uses = 0;
+        invite_code.bump = ctx.bumps.invite_code;
+
+        emit!(InviteCodeCreated {
+            channel: channel.key(),
+            invite_code: invite_code.key(),
+            creator: invite_code.creator,
+            max_uses,
+            expires_at: invite_code.expires_at,
+        });
+
+        msg!("Invite code created for channel {:?} with {} uses", channel.name, max_uses);
+        Ok(())
+    }
+
+    // Join a channel with an invite code; the code keypair co-signs the transaction alongside
+    // the joiner, binding the code to this joiner without exposing its secret key
+    pub fn join_channel_with_code(ctx: Context<JoinChannelWithCode>) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let invite_code = &mut ctx.accounts.invite_code;
+        let participant = &mut ctx.accounts.participant_account;
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > invite_code.expires_at {
+            return Err(PodComError::InviteCodeExpired.into());
+        }
+        if invite_code.uses >= invite_code.max_uses {
+            return Err(PodComError::InviteCodeExhausted.into());
+        }
+
+        // Check if channel is full
+        if channel.current_participants >= channel.max_participants {
+            return Err(PodComError::ChannelFull.into());
+        }
+
+        // Paid channels need enough escrow for at least one message, as in join_channel
+        if channel.fee_per_message > 0 {
+            let escrow = ctx
+                .accounts
+                .escrow_account
+                .as_ref()
+                .ok_or(PodComError::InsufficientFunds)?;
+            if escrow.amount < channel.fee_per_message {
+                return Err(PodComError::InsufficientFunds.into());
+            }
+        }
+
+        // Initialize participant account
+        participant.channel = channel.key();
+        participant.participant = ctx.accounts.agent_account.key(); // Use agent PDA
+        participant.joined_at = clock.unix_timestamp;
+        participant.is_active = true;
+        participant.role = ChannelRole::Member;
+        participant.is_muted = false;
+        participant.messages_sent = 0;
+        participant.last_message_at = 0;
+        participant.bump = ctx.bumps.participant_account;
+
+        channel.current_participants = channel
+            .current_participants
+            .checked_add(1)
+            .ok_or(PodComError::ChannelFull)?;
+        invite_code.uses = invite_code
+            .uses
+            .checked_add(1)
+            .ok_or(PodComError::InviteCodeExhausted)?;
+
+        emit!(ChannelJoinedWithCode {
+            channel: channel.key(),
+            invite_code: invite_code.key(),
+            participant: participant.participant,
+            uses: invite_code.uses,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "User {:?} joined channel {:?} with invite code",
+            participant.participant,
+            channel.name
+        );
+        Ok(())
+    }
+
+    // Revoke an invite code (code creator or channel creator), refunding rent to the code creator
+    pub fn revoke_invite_code(ctx: Context<RevokeInviteCode>) -> Result<()> {
+        let invite_code = &ctx.accounts.invite_code;
+        let authority = ctx.accounts.authority.key();
+
+        if authority != invite_code.creator && authority != ctx.accounts.channel_account.creator {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        msg!("Invite code {:?} revoked after {} uses", invite_code.key(), invite_code.uses);
+        Ok(())
+    }
+
+    // Get channel participants (view function - would be called off-chain)
+    pub fn get_channel_participants(ctx: Context<GetChannelParticipants>) -> Result<Vec<Pubkey>> {
+        // Note: In Solana programs, this function returns empty as participant data
+        // is typically queried off-chain via getProgramAccounts RPC calls for efficiency.
+        // The channel account stores the current participant count, but individual
+        // participant pubkeys are stored in separate ChannelParticipant accounts.
+
+        // For on-chain validation, we verify the channel exists and is active
+        let channel = &ctx.accounts.channel_account;
+        require!(channel.is_active, PodComError::NotInChannel);
+
+        // Return empty vector as participant enumeration is done off-chain
+        // Off-chain clients should use:
+        // - getProgramAccounts with ChannelParticipant discriminator
+        // - Filter by channel pubkey and is_active = true
+        Ok(vec![])
+    }
+
+    // Update channel settings (creator only)
+    pub fn update_channel(
+        ctx: Context<UpdateChannel>,
+        name: Option<String>,
+        description: Option<String>,
+        max_participants: Option<u32>,
+        fee_per_message: Option<u64>,
+        is_active: Option<bool>,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+
+        // Verify caller is the creator
+        if ctx.accounts.signer.key() != channel.creator {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Update fields if provided; the name seeds the channel PDA, which signs tree CPIs,
+        // so it can only be "updated" to its current value
+        if let Some(new_name) = name {
+            if new_name != channel.name {
+                return Err(PodComError::ChannelNameImmutable.into());
+            }
+        }
+
+        if let Some(new_description) = description {
+            if new_description.len() > MAX_CHANNEL_DESCRIPTION_LENGTH {
+                return Err(PodComError::ChannelDescriptionTooLong.into());
+            }
+            channel.description = new_description;
+        }
+
+        if let Some(new_max) = max_participants {
+            // Don't allow reducing below current participants
+            if new_max < channel.current_participants {
+                return Err(PodComError::ChannelFull.into());
+            }
+            channel.max_participants = new_max;
+        }
+
+        if let Some(new_fee) = fee_per_message {
+            if new_fee > ctx.accounts.protocol_config.max_fee_per_message {
+                return Err(PodComError::InsufficientFunds.into()); // Reusing error for excessive fee
+            }
+            channel.fee_per_message = new_fee;
+        }
+
+        if let Some(active) = is_active {
+            channel.is_active = active;
+        }
+
+        msg!("Channel {:?} updated", channel.name);
+        Ok(())
+    }
+
+    // Publish a new channel key epoch (creator or admins); the bundle holds the key sealed to each member
+    pub fn rotate_channel_key(
+        ctx: Context<RotateChannelKey>,
+        key_epoch: u32,
+        key_bundle_cid: String,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let channel = &mut ctx.accounts.channel_account;
+        let authority = ctx.accounts.authority.key();
+
+        if authority != channel.creator {
+            let participant = ctx
+                .accounts
+                .participant_account
+                .as_ref()
+                .ok_or(PodComError::InsufficientChannelRole)?;
+            if !participant.role.can_invite_members() {
+                return Err(PodComError::InsufficientChannelRole.into());
+            }
+        }
+
+        if key_bundle_cid.is_empty() || key_bundle_cid.len() > MAX_KEY_BUNDLE_CID_LENGTH {
+            return Err(PodComError::InvalidKeyBundle.into());
+        }
+
+        // Concurrent rotations race on the epoch; only one of them can land
+        let expected_epoch = channel
+            .key_epoch
+            .checked_add(1)
+            .ok_or(PodComError::InvalidKeyEpoch)?;
+        if key_epoch != expected_epoch {
+            return Err(PodComError::InvalidKeyEpoch.into());
+        }
+
+        channel.key_epoch = key_epoch;
+        channel.key_bundle_cid = key_bundle_cid.clone();
+        channel.key_rotation_pending = false;
+
+        emit!(ChannelKeyRotated {
+            channel: channel.key(),
+            key_epoch,
+            key_bundle_cid,
+            rotated_by: authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel {:?} key rotated to epoch {}", channel.name, key_epoch);
+        Ok(())
+    }
+
+    // Enhanced create channel with validation
+    pub fn create_channel_v2(
+        ctx: Context<CreateChannelV2>,
+        name: String,
+        description: String,
+        visibility: ChannelVisibility,
+        max_participants: u32,
+        fee_per_message: u64,
+    ) -> Result<()> {
+        let agent = &ctx.accounts.agent_account;
+        let channel = &mut ctx.accounts.channel_account;
+        let participant = &mut ctx.accounts.participant_account;
+        let clock = Clock::get()?;
+
+        // Validate agent reputation
+        if agent.reputation < ctx.accounts.protocol_config.min_reputation_for_channels {
+            return Err(PodComError::InsufficientReputation.into());
+        }
+        if fee_per_message > ctx.accounts.protocol_config.max_fee_per_message {
+            return Err(PodComError::InsufficientFunds.into()); // Reusing error for excessive fee
+        }
+
+        // Validate input lengths
+        if name.len() > MAX_CHANNEL_NAME_LENGTH {
+            return Err(PodComError::ChannelNameTooLong.into());
+        }
+        if description.len() > MAX_CHANNEL_DESCRIPTION_LENGTH {
+            return Err(PodComError::ChannelDescriptionTooLong.into());
+        }
+
+        // Validate max participants
+        if max_participants > MAX_PARTICIPANTS_PER_CHANNEL {
+            return Err(PodComError::ChannelFull.into());
+        }
+
+        // Initialize channel
+        channel.creator = ctx.accounts.creator.key();
+        channel.name = name;
+        channel.description = description;
+        channel.visibility = visibility;
+        channel.max_participants = max_participants;
+        channel.current_participants = 1; // Creator is first participant
+        channel.fee_per_message = fee_per_message;
+        channel.escrow_balance = 0;
+        channel.accrued_fees = 0;
+        channel.created_at = clock.unix_timestamp;
+        channel.is_active = true;
+        channel.bump = ctx.bumps.channel_account;
+
+        // Add creator as first participant
+        participant.channel = channel.key();
+        participant.participant = ctx.accounts.agent_account.key(); // Use agent PDA
+        participant.joined_at = clock.unix_timestamp;
+        participant.is_active = true;
+        participant.role = ChannelRole::Admin;
+        participant.is_muted = false;
+        participant.messages_sent = 0;
+        participant.last_message_at = 0;
+        participant.bump = ctx.bumps.participant_account;
+
+        msg!("Enhanced channel created: {:?}", channel.name);
+        Ok(())
+    }
+
+    // =============================================================================
+    // ZK COMPRESSION FUNCTIONS - SECURITY CRITICAL
+    // =============================================================================
+    
+    /*
+     * SECURITY NOTICE (AUD-2024-05): ZK COMPRESSION FUNCTIONS
+     *
+     * These functions integrate with Light Protocol for Zero-Knowledge compression.
+     * This logic has undergone an internal security audit and is considered stable
+     * for beta deployments. External review is recommended before mainnet usage.
+     * 
+     * KNOWN RISKS:
+     * - Proof forgery attacks if verification logic is flawed
+     * - Data corruption if compression/decompression fails
+     * - State inconsistency between on-chain and off-chain data
+     * - Potential for DOS attacks via malformed proofs
+     * 
+     * Recommended best practices:
+     * 1. Independent security audit by cryptography experts
+     * 2. Extensive testing with malicious inputs
+     * 3. Formal verification of proof systems
+     * 4. Bug bounty program focused on ZK components
+     */
+
+    /// Initialize the channel-owned concurrent Merkle tree that stores compressed messages
+    /// The tree account must be pre-allocated and owned by the account compression program
+    pub fn init_channel_message_tree(ctx: Context<InitChannelMessageTree>) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // Verify caller is the creator
+        if ctx.accounts.authority.key() != channel.creator {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // A channel owns exactly one message tree for its lifetime
+        if channel.message_tree != Pubkey::default() {
+            return Err(PodComError::MerkleTreeAlreadyInitialized.into());
+        }
+
+        // Channel PDA is the tree authority, so every append must go through this program
+        init_channel_tree(
+            channel,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            MESSAGE_TREE_MAX_DEPTH as u32,
+            MESSAGE_TREE_MAX_BUFFER_SIZE as u32,
+        )?;
+
+        let (root, _leaf_count) = read_merkle_tree_state::<
+            MESSAGE_TREE_MAX_DEPTH,
+            MESSAGE_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        let channel = &mut ctx.accounts.channel_account;
+        channel.message_tree = ctx.accounts.merkle_tree.key();
+        channel.message_tree_root = root;
+
+        emit!(MessageTreeInitialized {
+            channel: channel.key(),
+            merkle_tree: channel.message_tree,
+            max_depth: MESSAGE_TREE_MAX_DEPTH as u32,
+            max_buffer_size: MESSAGE_TREE_MAX_BUFFER_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Message tree initialized for channel {:?}", channel.name);
+        Ok(())
+    }
+
+    /// Initialize the channel-owned concurrent Merkle tree that stores compressed participants
+    /// The tree account must be pre-allocated and owned by the account compression program
+    pub fn init_channel_participant_tree(ctx: Context<InitChannelParticipantTree>) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // Verify caller is the creator
+        if ctx.accounts.authority.key() != channel.creator {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // A channel owns exactly one participant tree for its lifetime
+        if channel.participant_tree != Pubkey::default() {
+            return Err(PodComError::MerkleTreeAlreadyInitialized.into());
+        }
+
+        init_channel_tree(
+            channel,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            PARTICIPANT_TREE_MAX_DEPTH as u32,
+            PARTICIPANT_TREE_MAX_BUFFER_SIZE as u32,
+        )?;
+
+        let (root, _leaf_count) = read_merkle_tree_state::<
+            PARTICIPANT_TREE_MAX_DEPTH,
+            PARTICIPANT_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        let channel = &mut ctx.accounts.channel_account;
+        channel.participant_tree = ctx.accounts.merkle_tree.key();
+        channel.participant_tree_root = root;
+
+        emit!(ParticipantTreeInitialized {
+            channel: channel.key(),
+            merkle_tree: channel.participant_tree,
+            max_depth: PARTICIPANT_TREE_MAX_DEPTH as u32,
+            max_buffer_size: PARTICIPANT_TREE_MAX_BUFFER_SIZE as u32,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Participant tree initialized for channel {:?}", channel.name);
+        Ok(())
+    }
+
+    /// Broadcast a compressed message to a channel with IPFS content storage
+    /// NOTICE: This function relies on audited ZK compression logic - see security notice above
+    pub fn broadcast_message_compressed(
+        ctx: Context<BroadcastMessageCompressed>,
+        content: String,
+        message_type: MessageType,
+        reply_to: Option<Pubkey>,
+        ipfs_hash: String,
+    ) -> Result<()> {
+        let participant = &ctx.accounts.participant_account;
+        let channel = &ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // SECURITY CHECKS (CRIT-01): Comprehensive validation for ZK compression
+        
+        // Validate content length for IPFS storage with stricter limits for compression
+        if content.len() > MAX_MESSAGE_CONTENT_LENGTH * 10 {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+        
+        // Validate IPFS hash format to prevent injection attacks
+        if ipfs_hash.is_empty() || ipfs_hash.len() > 100 || !ipfs_hash.chars().all(|c| c.is_alphanumeric()) {
+            return Err(PodComError::InvalidMetadataUriLength.into()); // Reusing error for invalid hash
+        }
+
+        // Verify user is an active participant with additional PDA validation
+        if !participant.is_active {
+            return Err(PodComError::NotInChannel.into());
+        }
+        
+        // Verify participant PDA derivation to prevent substitution attacks
+        let agent_account = &ctx.accounts.participant_account;
+        let (expected_participant_pda, _bump) = Pubkey::find_program_address(
+            &[b"participant", channel.key().as_ref(), agent_account.participant.as_ref()],
+            &crate::ID
+        );
+        if ctx.accounts.participant_account.key() != expected_participant_pda {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Read-only and muted participants cannot post
+        check_can_broadcast(participant)?;
+        check_channel_key_current(channel)?;
+
+        // Authority must be the agent's owner or one of its session keys
+        authorize_agent_signer(
+            &ctx.accounts.agent_account,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.session_key.as_mut(),
+            SESSION_PERMISSION_BROADCAST,
+            Some(channel.key()),
+            channel.fee_per_message,
+        )?;
+
+        // Replies must pass their parent, which the context pins to this channel, as in broadcast_message
+        if let Some(parent_key) = reply_to {
+            let parent = ctx
+                .accounts
+                .parent_message
+                .as_mut()
+                .ok_or(PodComError::InvalidReplyTarget)?;
+            record_reply(parent, parent_key)?;
+        }
+        
+        // Additional security: Verify all Light Protocol accounts are legitimate
+        // This helps prevent malicious account substitution in ZK operations
+        if ctx.accounts.system_program.key() != anchor_lang::system_program::ID {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Rate limiting (same as regular messages)
+        let rate_limit_per_minute = ctx.accounts.protocol_config.rate_limit_messages_per_minute as u64;
+        let current_time = clock.unix_timestamp;
+        let participant = &mut ctx.accounts.participant_account;
+
+        if participant.last_message_at > 0 {
+            let time_since_last = current_time - participant.last_message_at;
+            if time_since_last < 1 {
+                return Err(PodComError::RateLimitExceeded.into());
+            }
+            if time_since_last < 60 {
+                if participant.messages_sent >= rate_limit_per_minute {
+                    return Err(PodComError::RateLimitExceeded.into());
+                }
+                participant.messages_sent += 1;
+            } else {
+                participant.messages_sent = 1;
+            }
+        } else {
+            participant.messages_sent = 1;
+        }
+        participant.last_message_at = current_time;
+
+        // Paid channels debit the sender's escrow for every message
+        if ctx.accounts.channel_account.fee_per_message > 0 {
+            let sender = ctx.accounts.agent_account.pubkey;
+            charge_message_fee(
+                &mut ctx.accounts.channel_account,
+                ctx.accounts.escrow_account.as_mut(),
+                &sender,
+            )?;
+        }
+        let channel = &ctx.accounts.channel_account;
+        let participant = &ctx.accounts.participant_account;
+
+        // Create content hash using secure memory and Light Protocol's Poseidon hasher
+        let content_hash = secure_hash_data(content.as_bytes())?;
+
+        // Create compressed message data; only its hash is persisted as a tree leaf
+        let compressed_message = CompressedChannelMessage {
+            channel: channel.key(),
+            sender: participant.participant,
+            content_hash,
+            ipfs_hash: ipfs_hash.clone(),
+            message_type,
+            created_at: clock.unix_timestamp,
+            edited_at: None,
+            reply_to,
+        };
+        let leaf_hash = compressed_message.hash()?;
+        let sender = compressed_message.sender;
+
+        // Append the leaf to the channel's message tree, signed by the channel PDA
+        append_to_channel_tree(
+            &ctx.accounts.channel_account,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            leaf_hash,
+        )?;
+
+        let (root, leaf_count) = read_merkle_tree_state::<
+            MESSAGE_TREE_MAX_DEPTH,
+            MESSAGE_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        // Persist the new root so clients can prove inclusion against channel state
+        let channel = &mut ctx.accounts.channel_account;
+        channel.message_tree_root = root;
+        channel.total_compressed_messages = channel.total_compressed_messages.saturating_add(1);
+
+        emit!(CompressedMessageAppended {
+            channel: channel.key(),
+            sender,
+            merkle_tree: channel.message_tree,
+            leaf_hash,
+            leaf_index: leaf_count.saturating_sub(1),
+            root,
+            content_hash,
+            ipfs_hash: ipfs_hash.clone(),
+            message_type,
+            reply_to,
+            key_epoch: channel.key_epoch,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // Emit event for indexing
+        emit!(MessageBroadcast {
+            channel: channel.key(),
+            sender,
+            message_type,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Compressed message broadcasted to channel: {:?}, IPFS: {}",
+            channel.name,
+            ipfs_hash
+        );
+        Ok(())
+    }
+
+    /// Join a channel with compressed participant data
+    pub fn join_channel_compressed(
+        ctx: Context<JoinChannelCompressed>,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let agent = &ctx.accounts.agent_account;
+        let clock = Clock::get()?;
+
+        // SECURITY FIX: Verify agent account belongs to the authority
+        if agent.pubkey != ctx.accounts.authority.key() {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Check channel capacity
+        if channel.current_participants >= channel.max_participants {
+            return Err(PodComError::ChannelFull.into());
+        }
+
+        // For private channels, verify invitation
+        if channel.visibility == ChannelVisibility::Private {
+            let invitation = &ctx
+                .accounts
+                .invitation_account
+                .as_ref()
+                .ok_or(PodComError::PrivateChannelRequiresInvitation)?;
+
+            verify_invitation(invitation, &ctx.accounts.authority.key(), clock.unix_timestamp)?;
+            if !invitation.is_accepted {
+                return Err(PodComError::Unauthorized.into());
+            }
+        }
+
+        let compressed_participant = CompressedChannelParticipant {
+            channel: channel.key(),
+            participant: agent.key(),
+            joined_at: clock.unix_timestamp,
+            messages_sent: 0,
+            last_message_at: 0,
+            metadata_hash,
+        };
+        let leaf_hash = compressed_participant.hash()?;
+
+        // SECURITY: Consume the invitation so it cannot admit a second leaf
+        if let Some(invitation) = &mut ctx.accounts.invitation_account {
+            invitation.is_used = true;
+        }
+
+        // Record membership as a leaf in the channel's participant tree
+        append_to_channel_tree(
+            channel,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            leaf_hash,
+        )?;
+
+        let (root, leaf_count) = read_merkle_tree_state::<
+            PARTICIPANT_TREE_MAX_DEPTH,
+            PARTICIPANT_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        let leaf_index = leaf_count.saturating_sub(1);
+        let membership = &mut ctx.accounts.membership_account;
+        membership.channel = compressed_participant.channel;
+        membership.participant = compressed_participant.participant;
+        membership.joined_at = compressed_participant.joined_at;
+        membership.leaf_index = u32::try_from(leaf_index).map_err(|_| PodComError::InvalidMerkleTree)?;
+        membership.bump = ctx.bumps.membership_account;
+
+        // Update channel participant count and tree root
+        let channel = &mut ctx.accounts.channel_account;
+        channel.participant_tree_root = root;
+        channel.current_participants = channel
+            .current_participants
+            .checked_add(1)
+            .ok_or(PodComError::ChannelFull)?;
+
+        emit!(CompressedParticipantUpdated {
+            channel: channel.key(),
+            participant: compressed_participant.participant,
+            merkle_tree: channel.participant_tree,
+            leaf_hash,
+            leaf_index,
+            root,
+            joined_at: compressed_participant.joined_at,
+            messages_sent: compressed_participant.messages_sent,
+            last_message_at: compressed_participant.last_message_at,
+            metadata_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // Emit event
+        emit!(ChannelJoined {
+            channel: channel.key(),
+            participant: compressed_participant.participant,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent joined channel with compression: {:?}", channel.name);
+        Ok(())
+    }
+
+    /// Leave a channel by clearing the caller's leaf in the participant tree
+    /// The proof for `leaf_index` against `root` is passed as remaining accounts
+    pub fn leave_channel_compressed<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyCompressedParticipant<'info>>,
+        root: [u8; 32],
+        participant: CompressedChannelParticipant,
+        leaf_index: u32,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // SECURITY: The leaf must describe the caller's own membership in this channel
+        if participant.channel != channel.key()
+            || participant.participant != ctx.accounts.agent_account.key()
+            || leaf_index != ctx.accounts.membership_account.leaf_index
+        {
+            return Err(PodComError::InvalidCompressedParticipant.into());
+        }
+
+        let previous_leaf = participant.hash()?;
+
+        // An all-zero leaf is the empty node, which frees the slot and proves departure
+        replace_channel_tree_leaf(
+            channel,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            ctx.remaining_accounts,
+            root,
+            previous_leaf,
+            [0u8; 32],
+            leaf_index,
+        )?;
+
+        let (new_root, _leaf_count) = read_merkle_tree_state::<
+            PARTICIPANT_TREE_MAX_DEPTH,
+            PARTICIPANT_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        let channel = &mut ctx.accounts.channel_account;
+        channel.participant_tree_root = new_root;
+        channel.current_participants = channel
+            .current_participants
+            .checked_sub(1)
+            .ok_or(PodComError::NotInChannel)?;
+
+        let channel_key = channel.key();
+        require_channel_key_rotation(channel, channel_key, participant.participant)?;
+
+        // Free the membership marker so the agent can rejoin later
+        ctx.accounts
+            .membership_account
+            .close(ctx.accounts.authority.to_account_info())?;
+        let channel = &ctx.accounts.channel_account;
+
+        emit!(CompressedParticipantLeft {
+            channel: channel.key(),
+            participant: participant.participant,
+            merkle_tree: channel.participant_tree,
+            leaf_index: leaf_index as u64,
+            root: new_root,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Agent left channel with compression: {:?}", channel.name);
+        Ok(())
+    }
+
+    /// Replace the metadata hash on the caller's compressed participant leaf
+    /// The proof for `leaf_index` against `root` is passed as remaining accounts
+    pub fn update_participant_metadata_compressed<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyCompressedParticipant<'info>>,
+        root: [u8; 32],
+        participant: CompressedChannelParticipant,
+        leaf_index: u32,
+        new_metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        let channel = &ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // SECURITY: The leaf must describe the caller's own membership in this channel
+        if participant.channel != channel.key()
+            || participant.participant != ctx.accounts.agent_account.key()
+            || leaf_index != ctx.accounts.membership_account.leaf_index
+        {
+            return Err(PodComError::InvalidCompressedParticipant.into());
+        }
+
+        let previous_leaf = participant.hash()?;
+        let updated_participant = CompressedChannelParticipant {
+            metadata_hash: new_metadata_hash,
+            ..participant
+        };
+        let new_leaf = updated_participant.hash()?;
+
+        replace_channel_tree_leaf(
+            channel,
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.noop_program.to_account_info(),
+            ctx.remaining_accounts,
+            root,
+            previous_leaf,
+            new_leaf,
+            leaf_index,
+        )?;
+
+        let (new_root, _leaf_count) = read_merkle_tree_state::<
+            PARTICIPANT_TREE_MAX_DEPTH,
+            PARTICIPANT_TREE_MAX_BUFFER_SIZE,
+        >(&ctx.accounts.merkle_tree.to_account_info())?;
+
+        let channel = &mut ctx.accounts.channel_account;
+        channel.participant_tree_root = new_root;
+
+        emit!(CompressedParticipantUpdated {
+            channel: channel.key(),
+            participant: updated_participant.participant,
+            merkle_tree: channel.participant_tree,
+            leaf_hash: new_leaf,
+            leaf_index: leaf_index as u64,
+            root: new_root,
+            joined_at: updated_participant.joined_at,
+            messages_sent: updated_participant.messages_sent,
+            last_message_at: updated_participant.last_message_at,
+            metadata_hash: new_metadata_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Compressed participant metadata updated in channel {:?}", channel.name);
+        Ok(())
+    }
+
+    /// Batch sync compressed messages - periodically sync state to chain
+    pub fn batch_sync_compressed_messages(
+        ctx: Context<BatchSyncCompressedMessages>,
+        message_hashes: Vec<[u8; 32]>,
+        sync_timestamp: i64,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // Validate batch size (prevent spam)
+        if message_hashes.len() > 100 {
+            return Err(PodComError::RateLimitExceeded.into());
+        }
+
+        // Verify authority is channel creator or has permissions
+        if channel.creator != ctx.accounts.authority.key() {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        // Validate sync timestamp is reasonable (within 1 hour of current time)
+        let current_timestamp = clock.unix_timestamp;
+        let time_diff = (current_timestamp - sync_timestamp).abs();
+        if time_diff > 3600 {
+            return Err(PodComError::InvalidTimestamp.into());
+        }
+
+        // Light Protocol batch compression implementation
+        let mut compressed_data = Vec::new();
+        let mut total_size = 0u64;
+        
+        for (i, hash) in message_hashes.iter().enumerate() {
+            // Verify hash format and create compressed account entry
+            if hash.iter().all(|&b| b == 0) {
+                return Err(PodComError::InvalidMessageHash.into());
+            }
+
+            // Create Poseidon hash for Light Protocol compatibility
+            let compressed_hash = *blake3::hash(hash).as_bytes();
+
+            // Store compressed account data
+            compressed_data.push(compressed_hash);
+            total_size += 32; // Each hash is 32 bytes
+
+            // Emit event for indexing
+            emit!(CompressedMessageSynced {
+                channel_id: channel.key(),
+                message_hash: *hash,
+                compressed_hash,
+                batch_index: i as u32,
+                sync_timestamp,
+            });
+        }
+
+        // Update channel state with batch sync info
+        channel.last_sync_timestamp = sync_timestamp;
+        // Synced hashes are counted apart from the messages appended to the message tree
+        channel.total_synced_messages = channel.total_synced_messages.saturating_add(message_hashes.len() as u32);
+        channel.compressed_data_size = channel.compressed_data_size.saturating_add(total_size);
+
+        // Calculate compression ratio (estimated)
+        let original_size = message_hashes.len() as u64 * 1024; // Assume 1KB per message
+        let compression_ratio = if total_size > 0 {
+            (original_size * 100) / total_size
+        } else {
+            100
+        };
+
+        msg!(
+            "Batch synced {} compressed messages at timestamp: {}, compression ratio: {}%, total size: {} bytes",
+            message_hashes.len(),
+            sync_timestamp,
+            compression_ratio,
+            total_size
+        );
+        
+        Ok(())
+    }
+
+    // Rewrite an account created under a legacy layout in the current one, growing it to the
+    // current space; the payer tops up the rent. Legacy direct messages also need their sender
+    // agent, which must be migrated first.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let (encoded, space) = {
+            let data = account.try_borrow_data()?;
+            super::encode_migrated_account(account.key(), &data, ctx.accounts.sender_agent.as_ref())?
+        };
+
+        let rent_due = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if rent_due > 0 {
+            let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                &account.key(),
+                rent_due,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &transfer_instruction,
+                &[ctx.accounts.payer.to_account_info(), account.clone()],
+            )?;
+        }
+
+        account.resize(space)?;
+        let mut data = account.try_borrow_mut_data()?;
+        data[..encoded.len()].copy_from_slice(&encoded);
+        data[encoded.len()..].fill(0);
+
+        msg!("Account {:?} migrated to {} bytes", account.key(), space);
+        Ok(())
+    }
+}
+
+// Contexts
+
+#[derive(Accounts)]
+#[instruction(capabilities: u64, metadata_uri: String)]
+pub struct RegisterAgent<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = AGENT_ACCOUNT_SPACE,
+        seeds = [b"agent", signer.key().as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    /// CHECK: Tombstone of a previously closed agent at this address; may be empty
+    #[account(
+        mut,
+        seeds = [b"agent_tombstone", agent_account.key().as_ref()],
+        bump
+    )]
+    pub agent_tombstone: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct RegisterAgentV2<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = AGENT_ACCOUNT_SPACE,
+        seeds = [b"agent", signer.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    /// CHECK: Tombstone of a previously closed agent at this address; may be empty
+    #[account(
+        mut,
+        seeds = [b"agent_tombstone", agent_account.key().as_ref()],
+        bump
+    )]
+    pub agent_tombstone: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey, payload_hash: [u8; 32], message_type: MessageType)]
+pub struct SendMessage<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = MESSAGE_ACCOUNT_SPACE,
+        seeds = [
+            b"message",
+            sender_agent.key().as_ref(),
+            recipient.as_ref(),
+            &payload_hash,
+            &[match message_type { MessageType::Text => 0, MessageType::Data => 1, MessageType::Command => 2, MessageType::Response => 3, MessageType::Custom(x) => 4 + x }],
+        ],
+        bump
+    )]
+    pub message_account: Account<'info, MessageAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = AGENT_INBOX_SPACE,
+        seeds = [b"inbox", recipient_agent.key().as_ref()],
+        bump
+    )]
+    pub recipient_inbox: Account<'info, AgentInbox>,
+    // The recipient wallet's agent whose inbox indexes the message
+    #[account(
+        seeds = [b"agent", recipient.as_ref(), recipient_agent.name.as_bytes()],
+        bump = recipient_agent.bump,
+    )]
+    pub recipient_agent: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        seeds = [b"agent", sender_agent.pubkey.as_ref(), sender_agent.name.as_bytes()],
+        bump = sender_agent.bump,
+    )]
+    pub sender_agent: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        seeds = [b"session_key", sender_agent.key().as_ref(), signer.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+    // Agent owner or a session key's signer
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredMessage<'info> {
+    #[account(mut, close = payer)]
+    pub message_account: Account<'info, MessageAccount>,
+    /// CHECK: Original rent payer recorded on the message account
+    #[account(
+        mut,
+        address = message_account.payer @ PodComError::Unauthorized
+    )]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub closer: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgent<'info> {
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"agent", signer.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = AGENT_TOMBSTONE_SPACE,
+        seeds = [b"agent_tombstone", agent_account.key().as_ref()],
+        bump
+    )]
+    pub agent_tombstone: Account<'info, AgentTombstone>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAgentOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", signer.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAgentOwnership<'info> {
+    #[account(
+        mut,
+        close = previous_owner,
+        seeds = [b"agent", previous_owner.key().as_ref(), previous_agent.name.as_bytes()],
+        bump = previous_agent.bump,
+        constraint = previous_agent.pending_owner == new_owner.key() @ PodComError::Unauthorized,
+    )]
+    pub previous_agent: Account<'info, AgentAccount>,
+    /// CHECK: Previous owner wallet, refunded the old agent account's rent
+    #[account(
+        mut,
+        address = previous_agent.pubkey @ PodComError::Unauthorized
+    )]
+    pub previous_owner: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = new_owner,
+        space = AGENT_ACCOUNT_SPACE,
+        seeds = [b"agent", new_owner.key().as_ref(), previous_agent.name.as_bytes()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_signer: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = SESSION_KEY_SPACE,
+        seeds = [b"session_key", agent_account.key().as_ref(), session_signer.as_ref()],
+        bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    #[account(
+        seeds = [b"agent", owner.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ PodComError::Unauthorized,
+        seeds = [b"session_key", session_key.agent.as_ref(), session_key.session_signer.as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", signer.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMessageStatus<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message", 
+            message_account.sender.as_ref(),
+            message_account.recipient.as_ref(),
+            &message_account.payload_hash,
+            &[match &message_account.message_type {
+                MessageType::Text => 0,
+                MessageType::Data => 1,
+                MessageType::Command => 2,
+                MessageType::Response => 3,
+                MessageType::Custom(x) => 4 + x
+            }],
+        ],
+        bump = message_account.bump,
+    )]
+    pub message_account: Account<'info, MessageAccount>,
+    #[account(
+        seeds = [b"agent", signer.key().as_ref(), recipient_agent.name.as_bytes()],
+        bump = recipient_agent.bump,
+    )]
+    pub recipient_agent: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        address = message_account.sender @ PodComError::Unauthorized,
+    )]
+    pub sender_agent: Account<'info, AgentAccount>,
+    // Required for messages written to an inbox (see MessageAccount::inbox_indexed)
+    #[account(
+        mut,
+        seeds = [b"inbox", message_account.recipient_agent.as_ref()],
+        bump = recipient_inbox.bump,
+    )]
+    pub recipient_inbox: Option<Account<'info, AgentInbox>>,
+    pub signer: Signer<'info>,
+    /// CHECK: Instructions sysvar, read to find the recipient's Ed25519 receipt verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocol<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PROTOCOL_CONFIG_SPACE,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    // SECURITY: Only the program's upgrade authority may claim the protocol authority
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ PodComError::Unauthorized)]
+    pub program: Program<'info, crate::program::PodCom>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ PodComError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.authority == authority.key() @ PodComError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptProtocolAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.pending_authority == new_authority.key() @ PodComError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64)]
+pub struct CreateChannel<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = CHANNEL_ACCOUNT_SPACE,
+        seeds = [b"channel", creator.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"agent", creator.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = creator.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct DepositEscrow<'info> {
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = ESCROW_ACCOUNT_SPACE,
+        seeds = [b"escrow", channel_account.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), depositor.key().as_ref()],
+        bump = escrow_account.bump,
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimChannelFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), escrow_account.depositor.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.channel == channel_account.key() @ PodComError::Unauthorized,
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(
+        mut,
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    /// CHECK: Protocol fee recipient recorded in the protocol config
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == protocol_config.fee_recipient @ PodComError::Unauthorized
+    )]
+    pub fee_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(escrow_id: [u8; 32])]
+pub struct CreateAgentEscrow<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AGENT_ESCROW_ACCOUNT_SPACE,
+        seeds = [b"agent_escrow", payer.key().as_ref(), escrow_id.as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(
+        mut,
+        seeds = [b"agent", payer.key().as_ref(), payer_agent.name.as_bytes()],
+        bump = payer_agent.bump,
+        constraint = payer.key() == payer_agent.pubkey @ PodComError::Unauthorized,
+    )]
+    pub payer_agent: Account<'info, AgentAccount>,
+    pub payee_agent: Account<'info, AgentAccount>,
+    // Only sets registered by the protocol authority may arbitrate
+    #[account(
+        constraint = arbitrator_set.authority == protocol_config.authority @ PodComError::InvalidArbitratorSet
+    )]
+    pub arbitrator_set: Option<Account<'info, ArbitratorSet>>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleAgentEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.payer_wallet == payer.key() @ PodComError::Unauthorized,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    /// CHECK: Payee wallet recorded at escrow creation
+    #[account(
+        mut,
+        constraint = payee_wallet.key() == escrow_account.payee_wallet @ PodComError::Unauthorized
+    )]
+    pub payee_wallet: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = escrow_account.payee @ PodComError::Unauthorized,
+    )]
+    pub payee_agent: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        address = escrow_account.payer @ PodComError::Unauthorized,
+    )]
+    pub payer_agent: Account<'info, AgentAccount>,
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefundAgentEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.payer_wallet == payer.key() @ PodComError::Unauthorized,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = escrow_account.payer @ PodComError::Unauthorized,
+    )]
+    pub payer_agent: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgentEscrow<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.payer_wallet == payer.key() @ PodComError::Unauthorized,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    // Required once a dispute was resolved
+    #[account(
+        mut,
+        seeds = [b"escrow_dispute", escrow_account.key().as_ref()],
+        bump = dispute_account.bump,
+    )]
+    pub dispute_account: Option<Account<'info, EscrowDispute>>,
+    /// CHECK: Wallet that opened the dispute and paid for its account
+    #[account(mut)]
+    pub dispute_opener: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(set_id: u64)]
+pub struct CreateArbitratorSet<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ARBITRATOR_SET_SPACE,
+        seeds = [b"arbitrator_set", authority.key().as_ref(), &set_id.to_le_bytes()],
+        bump
+    )]
+    pub arbitrator_set: Account<'info, ArbitratorSet>,
+    #[account(
+        mut,
+        constraint = authority.key() == protocol_config.authority @ PodComError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct OpenEscrowDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(
+        init,
+        payer = opener,
+        space = ESCROW_DISPUTE_SPACE,
+        seeds = [b"escrow_dispute", escrow_account.key().as_ref()],
+        bump
+    )]
+    pub dispute_account: Account<'info, EscrowDispute>,
+    #[account(mut)]
+    pub opener: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitEscrowEvidence<'info> {
+    #[account(
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(
+        mut,
+        seeds = [b"escrow_dispute", escrow_account.key().as_ref()],
+        bump = dispute_account.bump,
+    )]
+    pub dispute_account: Account<'info, EscrowDispute>,
+    pub submitter: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveEscrowDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.status == AgentEscrowStatus::Disputed @ PodComError::DisputeNotOpen,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(
+        mut,
+        seeds = [b"escrow_dispute", escrow_account.key().as_ref()],
+        bump = dispute_account.bump,
+    )]
+    pub dispute_account: Account<'info, EscrowDispute>,
+    #[account(
+        constraint = arbitrator_set.key() == dispute_account.arbitrator_set @ PodComError::InvalidArbitratorSet
+    )]
+    pub arbitrator_set: Account<'info, ArbitratorSet>,
+    /// CHECK: Payee wallet recorded at escrow creation
+    #[account(
+        mut,
+        constraint = payee_wallet.key() == escrow_account.payee_wallet @ PodComError::Unauthorized
+    )]
+    pub payee_wallet: UncheckedAccount<'info>,
+    /// CHECK: Payer wallet recorded at escrow creation
+    #[account(
+        mut,
+        constraint = payer_wallet.key() == escrow_account.payer_wallet @ PodComError::Unauthorized
+    )]
+    pub payer_wallet: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = escrow_account.payer @ PodComError::Unauthorized,
+    )]
+    pub payer_agent: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        address = escrow_account.payee @ PodComError::Unauthorized,
+    )]
+    pub payee_agent: Account<'info, AgentAccount>,
+    pub arbitrator: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SettleExpiredEscrowDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent_escrow", escrow_account.payer_wallet.as_ref(), escrow_account.escrow_id.as_ref()],
+        bump = escrow_account.bump,
+        constraint = escrow_account.status == AgentEscrowStatus::Disputed @ PodComError::DisputeNotOpen,
+    )]
+    pub escrow_account: Account<'info, AgentEscrowAccount>,
+    #[account(
+        mut,
+        seeds = [b"escrow_dispute", escrow_account.key().as_ref()],
+        bump = dispute_account.bump,
+    )]
+    pub dispute_account: Account<'info, EscrowDispute>,
+    /// CHECK: Payee wallet recorded at escrow creation
+    #[account(
+        mut,
+        constraint = payee_wallet.key() == escrow_account.payee_wallet @ PodComError::Unauthorized
+    )]
+    pub payee_wallet: UncheckedAccount<'info>,
+    /// CHECK: Payer wallet recorded at escrow creation
+    #[account(
+        mut,
+        constraint = payer_wallet.key() == escrow_account.payer_wallet @ PodComError::Unauthorized
+    )]
+    pub payer_wallet: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = escrow_account.payer @ PodComError::Unauthorized,
+    )]
+    pub payer_agent: Account<'info, AgentAccount>,
+    pub settler: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
-// Contexts
+// New context structures for enhanced functionality
 
 #[derive(Accounts)]
-#[instruction(capabilities: u64, metadata_uri: String)]
-pub struct RegisterAgent<'info> {
+pub struct JoinChannel<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
     #[account(
         init,
-        payer = signer,
-        space = AGENT_ACCOUNT_SPACE,
-        seeds = [b"agent", signer.key().as_ref()],
+        payer = user,
+        space = CHANNEL_PARTICIPANT_SPACE,
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
         bump
     )]
+    pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
     pub agent_account: Account<'info, AgentAccount>,
-    #[account(mut)]
-    pub signer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-#[instruction(recipient: Pubkey, payload_hash: [u8; 32], message_type: MessageType)]
-pub struct SendMessage<'info> {
     #[account(
-        init,
-        payer = signer,
-        space = MESSAGE_ACCOUNT_SPACE,
-        seeds = [
-            b"message",
-            sender_agent.key().as_ref(),
-            recipient.as_ref(),
-            &payload_hash,
-            &[match message_type { MessageType::Text => 0, MessageType::Data => 1, MessageType::Command => 2, MessageType::Response => 3, MessageType::Custom(x) => 4 + x }],
-        ],
+        mut,
+        seeds = [b"invitation", channel_account.key().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub message_account: Account<'info, MessageAccount>,
+    pub invitation_account: Option<Account<'info, ChannelInvitation>>,
     #[account(
-        seeds = [b"agent", signer.key().as_ref()],
-        bump = sender_agent.bump,
-        constraint = signer.key() == sender_agent.pubkey @ PodComError::Unauthorized,
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), user.key().as_ref()],
+        bump
     )]
-    pub sender_agent: Account<'info, AgentAccount>,
+    pub escrow_account: Option<Account<'info, EscrowAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            b"agent",
+            inviter_agent.pubkey.as_ref(),
+            inviter_agent.name.as_bytes()
+        ],
+        bump = inviter_agent.bump,
+    )]
+    pub inviter_agent: Option<Account<'info, AgentAccount>>,
     #[account(mut)]
-    pub signer: Signer<'info>,
+    pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateAgent<'info> {
+pub struct LeaveChannel<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
     #[account(
         mut,
-        seeds = [b"agent", signer.key().as_ref()],
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+        constraint = participant_account.participant == agent_account.key() @ PodComError::Unauthorized
+    )]
+    pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
-        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
     pub agent_account: Account<'info, AgentAccount>,
-    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMessageStatus<'info> {
+pub struct LeaveAndCloseChannel<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
     #[account(
         mut,
-        seeds = [
-            b"message", 
-            message_account.sender.as_ref(),
-            message_account.recipient.as_ref(),
-            &message_account.payload_hash,
-            &[match &message_account.message_type {
-                MessageType::Text => 0,
-                MessageType::Data => 1,
-                MessageType::Command => 2,
-                MessageType::Response => 3,
-                MessageType::Custom(x) => 4 + x
-            }],
-        ],
-        bump = message_account.bump,
+        close = user,
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+        constraint = participant_account.participant == agent_account.key() @ PodComError::Unauthorized
     )]
-    pub message_account: Account<'info, MessageAccount>,
+    pub participant_account: Account<'info, ChannelParticipant>,
     #[account(
-        seeds = [b"agent", signer.key().as_ref()],
-        bump = recipient_agent.bump,
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
-    pub recipient_agent: Account<'info, AgentAccount>,
-    pub signer: Signer<'info>,
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
-#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64)]
-pub struct CreateChannel<'info> {
+#[instruction(code_signer: Pubkey)]
+pub struct CreateInviteCode<'info> {
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+        constraint = participant_account.is_active @ PodComError::NotInChannel
+    )]
+    pub participant_account: Option<Account<'info, ChannelParticipant>>,
+    #[account(
+        seeds = [b"agent", creator.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = creator.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
     #[account(
         init,
         payer = creator,
-        space = CHANNEL_ACCOUNT_SPACE,
-        seeds = [b"channel", creator.key().as_ref(), name.as_bytes()],
+        space = CHANNEL_INVITE_CODE_SPACE,
+        seeds = [b"invite_code", channel_account.key().as_ref(), code_signer.as_ref()],
         bump
     )]
-    pub channel_account: Account<'info, ChannelAccount>,
+    pub invite_code: Account<'info, ChannelInviteCode>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(mut)]
     pub creator: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64)]
-pub struct DepositEscrow<'info> {
+pub struct JoinChannelWithCode<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        mut,
+        seeds = [b"invite_code", channel_account.key().as_ref(), invite_code.code_signer.as_ref()],
+        bump = invite_code.bump
+    )]
+    pub invite_code: Account<'info, ChannelInviteCode>,
+    #[account(address = invite_code.code_signer @ PodComError::InvalidInviteCode)]
+    pub code_signer: Signer<'info>,
     #[account(
         init,
-        payer = depositor,
-        space = ESCROW_ACCOUNT_SPACE,
-        seeds = [b"escrow", channel_account.key().as_ref(), depositor.key().as_ref()],
+        payer = user,
+        space = CHANNEL_PARTICIPANT_SPACE,
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
         bump
     )]
-    pub escrow_account: Account<'info, EscrowAccount>,
-    #[account(mut)]
-    pub channel_account: Account<'info, ChannelAccount>,
+    pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"escrow", channel_account.key().as_ref(), user.key().as_ref()],
+        bump = escrow_account.bump
+    )]
+    pub escrow_account: Option<Account<'info, EscrowAccount>>,
     #[account(mut)]
-    pub depositor: Signer<'info>,
+    pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawEscrow<'info> {
+pub struct RevokeInviteCode<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"invite_code", channel_account.key().as_ref(), invite_code.code_signer.as_ref()],
+        bump = invite_code.bump
+    )]
+    pub invite_code: Account<'info, ChannelInviteCode>,
+    pub channel_account: Account<'info, ChannelAccount>,
+    /// CHECK: Wallet that paid for the invite code account
     #[account(
         mut,
+        address = invite_code.creator @ PodComError::Unauthorized
+    )]
+    pub creator: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptInvitation<'info> {
+    #[account(
+        mut,
+        seeds = [b"invitation", invitation_account.channel.as_ref(), invitee.key().as_ref()],
+        bump = invitation_account.bump
+    )]
+    pub invitation_account: Account<'info, ChannelInvitation>,
+    pub invitee: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeInvitation<'info> {
+    #[account(
+        mut,
+        close = inviter,
+        seeds = [b"invitation", channel_account.key().as_ref(), invitation_account.invitee.as_ref()],
+        bump = invitation_account.bump
+    )]
+    pub invitation_account: Account<'info, ChannelInvitation>,
+    pub channel_account: Account<'info, ChannelAccount>,
+    /// CHECK: Inviter wallet that paid for the invitation account
+    #[account(
+        mut,
+        address = invitation_account.inviter @ PodComError::Unauthorized
+    )]
+    pub inviter: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChannelInvitation<'info> {
+    #[account(
+        mut,
+        close = inviter,
+        seeds = [b"invitation", invitation_account.channel.as_ref(), invitation_account.invitee.as_ref()],
+        bump = invitation_account.bump
+    )]
+    pub invitation_account: Account<'info, ChannelInvitation>,
+    /// CHECK: Inviter wallet that paid for the invitation account
+    #[account(
+        mut,
+        address = invitation_account.inviter @ PodComError::Unauthorized
+    )]
+    pub inviter: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEscrow<'info> {
+    #[account(
+        mut,
+        close = depositor,
         seeds = [b"escrow", channel_account.key().as_ref(), depositor.key().as_ref()],
         bump = escrow_account.bump,
     )]
     pub escrow_account: Account<'info, EscrowAccount>,
-    #[account(mut)]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(mut)]
     pub depositor: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
-// New context structures for enhanced functionality
+#[derive(Accounts)]
+pub struct ModerateParticipant<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        mut,
+        constraint = target_participant.channel == channel_account.key() @ PodComError::Unauthorized
+    )]
+    pub target_participant: Account<'info, ChannelParticipant>,
+    #[account(
+        address = target_participant.participant @ PodComError::Unauthorized
+    )]
+    pub target_agent: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"participant", channel_account.key().as_ref(), moderator_agent.key().as_ref()],
+        bump = moderator_participant.bump
+    )]
+    pub moderator_participant: Option<Account<'info, ChannelParticipant>>,
+    #[account(
+        seeds = [b"agent", moderator.key().as_ref(), moderator_agent.name.as_bytes()],
+        bump = moderator_agent.bump,
+        constraint = moderator.key() == moderator_agent.pubkey @ PodComError::Unauthorized,
+    )]
+    pub moderator_agent: Account<'info, AgentAccount>,
+    pub moderator: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
 
 #[derive(Accounts)]
-pub struct JoinChannel<'info> {
-    #[account(mut)]
+#[instruction(content: String, message_type: MessageType, reply_to: Option<Pubkey>, nonce: u64)]
+pub struct BroadcastMessage<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.is_active @ PodComError::Unauthorized
+    )]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(
-        init,
-        payer = user,
-        space = CHANNEL_PARTICIPANT_SPACE,
+        mut,
         seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
-        bump
+        bump = participant_account.bump,
+        constraint = participant_account.is_active @ PodComError::NotInChannel
     )]
     pub participant_account: Account<'info, ChannelParticipant>,
     #[account(
-        seeds = [b"agent", user.key().as_ref()],
+        seeds = [b"agent", agent_account.pubkey.as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
-        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
     pub agent_account: Account<'info, AgentAccount>,
     #[account(
         mut,
-        seeds = [b"invitation", channel_account.key().as_ref(), user.key().as_ref()],
+        seeds = [b"session_key", agent_account.key().as_ref(), user.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+    #[account(
+        init,
+        payer = user,
+        space = CHANNEL_MESSAGE_SPACE,
+        seeds = [
+            b"channel_message",
+            channel_account.key().as_ref(),
+            user.key().as_ref(),
+            &nonce.to_le_bytes()
+        ],
         bump
     )]
-    pub invitation_account: Option<Account<'info, ChannelInvitation>>,
+    pub message_account: Account<'info, ChannelMessage>,
     #[account(
         mut,
-        seeds = [b"escrow", channel_account.key().as_ref(), user.key().as_ref()],
+        constraint = parent_message.channel == channel_account.key() @ PodComError::InvalidReplyTarget
+    )]
+    pub parent_message: Option<Account<'info, ChannelMessage>>,
+    // Fees are always drawn from the agent owner's escrow
+    #[account(
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), agent_account.pubkey.as_ref()],
         bump
     )]
     pub escrow_account: Option<Account<'info, EscrowAccount>>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct LeaveChannel<'info> {
-    #[account(mut)]
-    pub channel_account: Account<'info, ChannelAccount>,
+pub struct EditChannelMessage<'info> {
     #[account(
         mut,
-        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        constraint = message_account.sender == agent_account.key() @ PodComError::Unauthorized
+    )]
+    pub message_account: Account<'info, ChannelMessage>,
+    #[account(
+        address = message_account.channel @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"participant", message_account.channel.as_ref(), agent_account.key().as_ref()],
         bump = participant_account.bump,
-        constraint = participant_account.participant == agent_account.key() @ PodComError::Unauthorized
+        constraint = participant_account.is_active @ PodComError::NotInChannel
     )]
     pub participant_account: Account<'info, ChannelParticipant>,
     #[account(
-        seeds = [b"agent", user.key().as_ref()],
+        seeds = [b"agent", agent_account.pubkey.as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
-        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
     pub agent_account: Account<'info, AgentAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"session_key", agent_account.key().as_ref(), user.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
     pub user: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
-#[instruction(content: String, message_type: MessageType, reply_to: Option<Pubkey>, nonce: u64)]
-pub struct BroadcastMessage<'info> {
+pub struct DeleteChannelMessage<'info> {
     #[account(
         mut,
-        constraint = channel_account.is_active @ PodComError::Unauthorized
+        close = user,
+        constraint = message_account.sender == agent_account.key() @ PodComError::Unauthorized
     )]
-    pub channel_account: Account<'info, ChannelAccount>,
+    pub message_account: Account<'info, ChannelMessage>,
+    /// CHECK: Parent of a reply, matched against reply_to in the handler; may already be deleted
+    #[account(mut)]
+    pub parent_message: Option<UncheckedAccount<'info>>,
     #[account(
-        mut,
-        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(reaction: ReactionKind)]
+pub struct AddReaction<'info> {
+    #[account(mut)]
+    pub message_account: Account<'info, ChannelMessage>,
+    #[account(
+        seeds = [b"participant", message_account.channel.as_ref(), agent_account.key().as_ref()],
         bump = participant_account.bump,
         constraint = participant_account.is_active @ PodComError::NotInChannel
     )]
     pub participant_account: Account<'info, ChannelParticipant>,
     #[account(
-        seeds = [b"agent", user.key().as_ref()],
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
         constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
@@ -1923,19 +6924,61 @@ pub struct BroadcastMessage<'info> {
     #[account(
         init,
         payer = user,
-        space = CHANNEL_MESSAGE_SPACE,
+        space = MESSAGE_REACTION_SPACE,
         seeds = [
-            b"channel_message",
-            channel_account.key().as_ref(),
-            user.key().as_ref(),
-            &nonce.to_le_bytes()
+            b"reaction",
+            message_account.key().as_ref(),
+            agent_account.key().as_ref(),
+            &[reaction.index() as u8]
         ],
         bump
     )]
-    pub message_account: Account<'info, ChannelMessage>,
+    pub reaction_account: Account<'info, MessageReaction>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReaction<'info> {
+    /// CHECK: The reacted-to message; may already be closed by delete_channel_message
+    #[account(
+        mut,
+        address = reaction_account.message @ PodComError::Unauthorized
+    )]
+    pub message_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"agent", user.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            b"reaction",
+            reaction_account.message.as_ref(),
+            agent_account.key().as_ref(),
+            &[reaction_account.reaction.index() as u8]
+        ],
+        bump = reaction_account.bump
+    )]
+    pub reaction_account: Account<'info, MessageReaction>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
@@ -1949,7 +6992,7 @@ pub struct InviteToChannel<'info> {
     )]
     pub participant_account: Option<Account<'info, ChannelParticipant>>,
     #[account(
-        seeds = [b"agent", inviter.key().as_ref()],
+        seeds = [b"agent", inviter.key().as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
         constraint = inviter.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
@@ -1962,6 +7005,12 @@ pub struct InviteToChannel<'info> {
         bump
     )]
     pub invitation_account: Account<'info, ChannelInvitation>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(mut)]
     pub inviter: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -1979,16 +7028,56 @@ pub struct UpdateChannel<'info> {
         constraint = channel_account.creator == signer.key() @ PodComError::Unauthorized
     )]
     pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     pub signer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RotateChannelKey<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.is_active @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+        constraint = participant_account.is_active @ PodComError::NotInChannel
+    )]
+    pub participant_account: Option<Account<'info, ChannelParticipant>>,
+    #[account(
+        seeds = [b"agent", authority.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = authority.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64)]
 pub struct CreateChannelV2<'info> {
     #[account(
-        seeds = [b"agent", creator.key().as_ref()],
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(
+        seeds = [b"agent", creator.key().as_ref(), agent_account.name.as_bytes()],
         bump = agent_account.bump,
-        constraint = agent_account.reputation >= MIN_REPUTATION_FOR_CHANNELS @ PodComError::InsufficientReputation
+        constraint = agent_account.reputation >= protocol_config.min_reputation_for_channels @ PodComError::InsufficientReputation
     )]
     pub agent_account: Account<'info, AgentAccount>,
     #[account(
@@ -2016,12 +7105,64 @@ pub struct CreateChannelV2<'info> {
 // ZK COMPRESSION CONTEXT STRUCTS
 // =============================================================================
 
+#[derive(Accounts)]
+pub struct InitChannelMessageTree<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == authority.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    /// CHECK: Pre-allocated tree account, zero-checked and sized by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(content: String, message_type: MessageType, reply_to: Option<Pubkey>, ipfs_hash: String)]
 pub struct BroadcastMessageCompressed<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.message_tree != Pubkey::default() @ PodComError::MerkleTreeNotInitialized
+    )]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(mut)]
     pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        address = participant_account.participant @ PodComError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        seeds = [b"session_key", agent_account.key().as_ref(), authority.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+    #[account(
+        mut,
+        constraint = parent_message.channel == channel_account.key() @ PodComError::InvalidReplyTarget
+    )]
+    pub parent_message: Option<Account<'info, ChannelMessage>>,
+    #[account(
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), agent_account.pubkey.as_ref()],
+        bump
+    )]
+    pub escrow_account: Option<Account<'info, EscrowAccount>>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
     #[account(mut)]
     pub fee_payer: Signer<'info>,
     pub authority: Signer<'info>,
@@ -2031,14 +7172,17 @@ pub struct BroadcastMessageCompressed<'info> {
     pub compression_program: Program<'info, SplAccountCompression>,
     /// CHECK: Registered program PDA
     pub registered_program_id: AccountInfo<'info>,
-    /// CHECK: Noop program for logging
-    pub noop_program: AccountInfo<'info>,
+    pub noop_program: Program<'info, Noop>,
     /// CHECK: Account compression authority
     pub account_compression_authority: AccountInfo<'info>,
     /// CHECK: Account compression program
     pub account_compression_program: AccountInfo<'info>,
-    /// CHECK: Merkle tree account
-    pub merkle_tree: AccountInfo<'info>,
+    /// CHECK: Channel message tree, validated against the channel and by the compression program
+    #[account(
+        mut,
+        constraint = merkle_tree.key() == channel_account.message_tree @ PodComError::InvalidMerkleTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
     /// CHECK: Nullifier queue account
     pub nullifier_queue: AccountInfo<'info>,
     /// CHECK: CPI authority PDA
@@ -2048,29 +7192,135 @@ pub struct BroadcastMessageCompressed<'info> {
 #[derive(Accounts)]
 #[instruction(metadata_hash: [u8; 32])]
 pub struct JoinChannelCompressed<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = channel_account.participant_tree != Pubkey::default() @ PodComError::MerkleTreeNotInitialized
+    )]
     pub channel_account: Account<'info, ChannelAccount>,
     pub agent_account: Account<'info, AgentAccount>,
+    // SECURITY: One leaf per agent; `init` fails if the agent already holds one
+    #[account(
+        init,
+        payer = fee_payer,
+        space = COMPRESSED_MEMBERSHIP_SPACE,
+        seeds = [b"compressed_member", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump
+    )]
+    pub membership_account: Account<'info, CompressedMembership>,
+    #[account(
+        mut,
+        seeds = [b"invitation", channel_account.key().as_ref(), authority.key().as_ref()],
+        bump = invitation_account.bump
+    )]
     pub invitation_account: Option<Account<'info, ChannelInvitation>>,
     #[account(mut)]
     pub fee_payer: Signer<'info>,
     pub authority: Signer<'info>,
     /// CHECK: Light System Program
     pub system_program: Program<'info, System>,
+    pub compression_program: Program<'info, SplAccountCompression>,
     /// CHECK: Registered program PDA
     pub registered_program_id: AccountInfo<'info>,
-    /// CHECK: Noop program for logging
-    pub noop_program: AccountInfo<'info>,
+    pub noop_program: Program<'info, Noop>,
     /// CHECK: Account compression authority
     pub account_compression_authority: AccountInfo<'info>,
     /// CHECK: Account compression program
     pub account_compression_program: AccountInfo<'info>,
-    /// CHECK: Merkle tree account
-    pub merkle_tree: AccountInfo<'info>,
+    /// CHECK: Channel participant tree, validated against the channel and by the compression program
+    #[account(
+        mut,
+        constraint = merkle_tree.key() == channel_account.participant_tree @ PodComError::InvalidMerkleTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
     /// CHECK: Nullifier queue account
     pub nullifier_queue: AccountInfo<'info>,
     /// CHECK: CPI authority PDA
     pub cpi_authority_pda: AccountInfo<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitChannelParticipantTree<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == authority.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    /// CHECK: Pre-allocated tree account, zero-checked and sized by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+// Shared by leave_channel_compressed and update_participant_metadata_compressed
+// Proof nodes for the target leaf are supplied as remaining accounts
+#[derive(Accounts)]
+pub struct ModifyCompressedParticipant<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.participant_tree != Pubkey::default() @ PodComError::MerkleTreeNotInitialized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"agent", authority.key().as_ref(), agent_account.name.as_bytes()],
+        bump = agent_account.bump,
+        constraint = authority.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        seeds = [b"compressed_member", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = membership_account.bump,
+    )]
+    pub membership_account: Account<'info, CompressedMembership>,
+    // Receives the membership marker's rent when leaving
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Channel participant tree, validated against the channel and by the compression program
+    #[account(
+        mut,
+        constraint = merkle_tree.key() == channel_account.participant_tree @ PodComError::InvalidMerkleTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Legacy program account; encode_migrated_account checks its discriminator and size
+    #[account(mut, owner = crate::ID @ PodComError::AccountNotMigratable)]
+    pub account: UncheckedAccount<'info>,
+    // Sender agent of a legacy direct message, whose owner becomes its recorded rent payer
+    pub sender_agent: Option<Account<'info, AgentAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
 }
 
 #[derive(Accounts)]
@@ -2099,4 +7349,108 @@ pub struct BatchSyncCompressedMessages<'info> {
     pub nullifier_queue: AccountInfo<'info>,
     /// CHECK: CPI authority PDA
     pub cpi_authority_pda: AccountInfo<'info>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = !protocol_config.is_paused @ PodComError::ProtocolPaused
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressed_message(reply_to: Option<Pubkey>) -> CompressedChannelMessage {
+        CompressedChannelMessage {
+            channel: Pubkey::new_from_array([1; 32]),
+            sender: Pubkey::new_from_array([2; 32]),
+            content_hash: [3; 32],
+            ipfs_hash: "QmLeafHash".to_string(),
+            message_type: MessageType::Text,
+            created_at: 1_700_000_000,
+            edited_at: None,
+            reply_to,
+        }
+    }
+
+    #[test]
+    fn compressed_leaf_hash_covers_reply_to() {
+        let top_level = compressed_message(None).hash().unwrap();
+        let reply = compressed_message(Some(Pubkey::new_from_array([4; 32]))).hash().unwrap();
+        let other_reply = compressed_message(Some(Pubkey::new_from_array([5; 32]))).hash().unwrap();
+
+        assert_ne!(top_level, reply);
+        assert_ne!(reply, other_reply);
+    }
+
+    #[test]
+    fn compressed_leaf_hash_flags_optional_fields() {
+        // An edit timestamp must not be mistaken for the first bytes of a reply target
+        let mut edited = compressed_message(None);
+        edited.edited_at = Some(0);
+        let mut reply = compressed_message(Some(Pubkey::default()));
+        reply.edited_at = None;
+
+        assert_ne!(edited.hash().unwrap(), reply.hash().unwrap());
+    }
+
+    // Legacy AgentAccount bytes: discriminator, pubkey, capabilities, reputation, last_updated,
+    // metadata_uri, invites_sent, last_invite_at, bump, zero padding
+    fn legacy_agent_data(reputation: u64, metadata_uri: &str) -> Vec<u8> {
+        let mut data = AgentAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[7; 32]);
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&reputation.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&(metadata_uri.len() as u32).to_le_bytes());
+        data.extend_from_slice(metadata_uri.as_bytes());
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.extend_from_slice(&1_700_000_100i64.to_le_bytes());
+        data.push(254);
+        data.resize(legacy::AGENT_ACCOUNT_SPACE, 0);
+        data
+    }
+
+    #[test]
+    fn migrates_legacy_agent_to_current_layout() {
+        let data = legacy_agent_data(240, "https://example.com/agent");
+        let (encoded, space) = encode_migrated_account(Pubkey::default(), &data, None).unwrap();
+        assert_eq!(space, AGENT_ACCOUNT_SPACE);
+        assert!(encoded.len() <= space);
+
+        let agent = AgentAccount::try_deserialize(&mut encoded.as_slice()).unwrap();
+        assert_eq!(agent.pubkey, Pubkey::new_from_array([7; 32]));
+        assert_eq!(agent.reputation, 240);
+        assert_eq!(agent.metadata_uri, "https://example.com/agent");
+        assert!(agent.name.is_empty());
+        assert_eq!(agent.invites_sent, 3);
+        assert_eq!(agent.bump, 254);
+        assert_eq!(agent.pending_owner, Pubkey::default());
+    }
+
+    #[test]
+    fn rejects_accounts_already_in_current_layout() {
+        let mut data = legacy_agent_data(100, "https://example.com/agent");
+        data.resize(AGENT_ACCOUNT_SPACE, 0);
+        assert!(encode_migrated_account(Pubkey::default(), &data, None).is_err());
+    }
+
+    #[test]
+    fn legacy_participant_reads_as_plain_member() {
+        // Legacy ChannelParticipant: is_active and bump followed by six reserved zero bytes
+        let mut data = ChannelParticipant::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[1; 32]);
+        data.extend_from_slice(&[2; 32]);
+        data.extend_from_slice(&[0; 24]);
+        data.extend_from_slice(&[1, 253]);
+        data.resize(CHANNEL_PARTICIPANT_SPACE, 0);
+
+        let participant = ChannelParticipant::try_deserialize(&mut data.as_slice()).unwrap();
+        assert!(participant.is_active);
+        assert_eq!(participant.bump, 253);
+        assert_eq!(participant.role, ChannelRole::Member);
+        assert!(!participant.is_muted);
+        assert!(!participant.is_kicked);
+    }
 }
@@ -9,6 +9,7 @@ use std::time::Duration;
 
 // Import EscrowStatus from pod_sdk_types to avoid duplication
 pub use pod_sdk_types::EscrowStatus;
+use pod_sdk_types::MessageStatus;
 
 /// Main result type for the SDK
 pub type Result<T> = std::result::Result<T, PodComError>;
@@ -255,6 +256,10 @@ pub enum MessageError {
     #[error("Message expired: {message_id}")]
     Expired { message_id: Pubkey },
     
+    /// Status change not allowed by the message state machine
+    #[error("Invalid status transition for message {message_id}: {from} -> {to}")]
+    InvalidStatusTransition { message_id: Pubkey, from: MessageStatus, to: MessageStatus },
+    
     /// Invalid message content
     #[error("Invalid message content: {reason}")]
     InvalidContent { reason: String },
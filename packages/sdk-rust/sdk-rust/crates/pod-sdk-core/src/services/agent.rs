@@ -30,7 +30,10 @@ use crate::{
         BatchOperationResult, RequestOptions,
     },
     utils::{
-        account::{derive_agent_pda, validate_agent_account},
+        account::{
+            derive_agent_pda, derive_agent_tombstone_pda, derive_protocol_config_pda,
+            derive_session_key_pda, validate_agent_account,
+        },
         crypto::hash_message,
     },
     client::BaseService,
@@ -63,16 +66,23 @@ impl AgentService {
             
             // Derive agent PDA
             let (agent_pda, _bump) = derive_agent_pda(&owner.pubkey(), &params.name)?;
+            // Restores the reputation of an agent previously closed at this address
+            let (agent_tombstone, _) = derive_agent_tombstone_pda(&agent_pda)?;
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
             
             // Build instruction
             let ix = program
                 .request()
-                .accounts(pod_com::accounts::RegisterAgent {
+                .accounts(pod_com::accounts::RegisterAgentV2 {
                     agent_account: agent_pda,
+                    agent_tombstone,
                     signer: owner.pubkey(),
                     system_program: solana_sdk::system_program::id(),
+                    protocol_config,
                 })
-                .args(pod_com::instruction::RegisterAgent {
+                .args(pod_com::instruction::RegisterAgentV2 {
+                    name: params.name.clone(),
                     capabilities: params.capabilities,
                     metadata_uri: params.description.clone(),
                 })
@@ -117,12 +127,15 @@ impl AgentService {
                 });
             }
             
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             // Build instruction
             let ix = program
                 .request()
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
+                    protocol_config,
                 })
                 .args(pod_com::instruction::UpdateAgent {
                     capabilities: params.capabilities,
@@ -257,11 +270,14 @@ impl AgentService {
             }
             
             // Build instruction - using update_agent since pod-com doesn't have separate activate/deactivate
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             let ix = program
                 .request()
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
+                    protocol_config,
                 })
                 .args(pod_com::instruction::UpdateAgent {
                     capabilities: None, // Keep current capabilities
@@ -307,11 +323,14 @@ impl AgentService {
             }
             
             // Build instruction - using update_agent since pod-com doesn't have separate activate/deactivate
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             let ix = program
                 .request()
                 .accounts(pod_com::accounts::UpdateAgent {
                     agent_account: *agent_address,
                     signer: owner.pubkey(),
+                    protocol_config,
                 })
                 .args(pod_com::instruction::UpdateAgent {
                     capabilities: None, // Keep current capabilities
@@ -365,11 +384,21 @@ impl AgentService {
                 });
             }
             
-            // Build instruction - Note: pod-com doesn't have delete_agent, this would need custom implementation
-            // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
-                feature: "delete_agent".to_string(),
-            });
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            let (agent_tombstone, _) = derive_agent_tombstone_pda(agent_address)?;
+            
+            // Build instruction - the program refuses while escrows are still active
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::CloseAgent {
+                    agent_account: *agent_address,
+                    agent_tombstone,
+                    signer: owner.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::CloseAgent {})
+                .signer(owner);
 
             // Send transaction
             let signature = ix.send()?;
@@ -385,6 +414,180 @@ impl AgentService {
         }).await
     }
 
+    /// Start transferring an agent to a new owner wallet.
+    /// Passing `Pubkey::default()` as `new_owner` cancels a pending transfer.
+    pub async fn transfer_agent_ownership(
+        &self,
+        agent_address: &Pubkey,
+        owner: &Keypair,
+        new_owner: &Pubkey,
+    ) -> Result<()> {
+        let operation_name = "transfer_agent_ownership";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::TransferAgentOwnership {
+                    agent_account: *agent_address,
+                    signer: owner.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::TransferAgentOwnership {
+                    new_owner: *new_owner,
+                })
+                .signer(owner);
+
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                agent_address = %agent_address,
+                signature = %signature,
+                owner = %owner.pubkey(),
+                new_owner = %new_owner,
+                "Agent ownership transfer started"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Accept a pending agent transfer. The agent is re-created under the same
+    /// name at the new owner's PDA with its reputation, and the old account is closed.
+    pub async fn accept_agent_ownership(
+        &self,
+        previous_agent: &Pubkey,
+        new_owner: &Keypair,
+    ) -> Result<(Pubkey, AgentAccount)> {
+        let operation_name = "accept_agent_ownership";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            let previous = self.get_agent_account(previous_agent).await?;
+            let (agent_pda, _) = derive_agent_pda(&new_owner.pubkey(), &previous.name)?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::AcceptAgentOwnership {
+                    previous_agent: *previous_agent,
+                    previous_owner: previous.owner,
+                    agent_account: agent_pda,
+                    new_owner: new_owner.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::AcceptAgentOwnership {})
+                .signer(new_owner);
+
+            let signature = ix.send()?;
+            
+            let agent_account = self.get_agent_account(&agent_pda).await?;
+            
+            tracing::info!(
+                previous_agent = %previous_agent,
+                agent_address = %agent_pda,
+                signature = %signature,
+                new_owner = %new_owner.pubkey(),
+                "Agent ownership transferred"
+            );
+
+            Ok((agent_pda, agent_account))
+        }).await
+    }
+
+    /// Delegate message permissions for an agent to `session_signer`, so bot hosts
+    /// can post without the owner's wallet. `permissions` is a
+    /// `pod_com::SESSION_PERMISSION_*` bitmask; `channel` of `Pubkey::default()`
+    /// allows every channel, and `spend_cap` bounds message fees in lamports.
+    pub async fn create_session_key(
+        &self,
+        agent_address: &Pubkey,
+        owner: &Keypair,
+        session_signer: &Pubkey,
+        permissions: u8,
+        channel: Pubkey,
+        spend_cap: u64,
+        expires_in: i64,
+    ) -> Result<Pubkey> {
+        let operation_name = "create_session_key";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            let (session_key, _) = derive_session_key_pda(agent_address, session_signer)?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::CreateSessionKey {
+                    session_key,
+                    agent_account: *agent_address,
+                    owner: owner.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::CreateSessionKey {
+                    session_signer: *session_signer,
+                    permissions,
+                    channel,
+                    spend_cap,
+                    expires_in,
+                })
+                .signer(owner);
+
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                agent_address = %agent_address,
+                session_key = %session_key,
+                session_signer = %session_signer,
+                signature = %signature,
+                "Session key created"
+            );
+
+            Ok(session_key)
+        }).await
+    }
+
+    /// Revoke a session key; its rent is returned to the owner
+    pub async fn revoke_session_key(
+        &self,
+        agent_address: &Pubkey,
+        owner: &Keypair,
+        session_signer: &Pubkey,
+    ) -> Result<()> {
+        let operation_name = "revoke_session_key";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            let (session_key, _) = derive_session_key_pda(agent_address, session_signer)?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::RevokeSessionKey {
+                    session_key,
+                    owner: owner.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::RevokeSessionKey {})
+                .signer(owner);
+
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                agent_address = %agent_address,
+                session_key = %session_key,
+                signature = %signature,
+                "Session key revoked"
+            );
+
+            Ok(())
+        }).await
+    }
+
     /// Get agent statistics
     pub async fn get_agent_stats(&self, agent_address: &Pubkey) -> Result<AgentStats> {
         let operation_name = "get_agent_stats";
@@ -23,8 +23,15 @@ use crate::{
     error::{PodComError, Result},
     services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
     utils::{
-        account::{derive_channel_pda, validate_channel_account},
-        encryption::{generate_channel_key, derive_shared_key},
+        account::{
+            derive_agent_pda_v1, derive_channel_escrow_pda, derive_channel_pda, derive_invitation_pda,
+            derive_invite_code_pda, derive_participant_pda, derive_protocol_config_pda,
+            validate_channel_account,
+        },
+        encryption::{
+            decrypt_message, encrypt_message, generate_channel_key_material, seal_channel_key_bundle,
+            ChannelKeyBundle, ChannelKeyring,
+        },
     },
 };
 
@@ -79,14 +86,17 @@ impl ChannelService {
                 .collect();
             let (channel_pda, _bump) = derive_channel_pda(&creator.pubkey(), &channel_id)?;
             
-            // Generate encryption key for the channel
-            let encryption_key = generate_channel_key(&params.participants)?;
+            // Reputation and fee limits are checked against the protocol config
+            let (agent_pda, _) = derive_agent_pda_v1(&creator.pubkey())?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
             
             // Build instruction
             let ix = program
                 .request()
                 .accounts(pod_com::accounts::CreateChannel {
                     channel_account: channel_pda,
+                    agent_account: agent_pda,
+                    protocol_config,
                     creator: creator.pubkey(),
                     system_program: solana_sdk::system_program::id(),
                 })
@@ -197,7 +207,10 @@ impl ChannelService {
         }).await
     }
 
-    /// Remove participant from channel
+    /// Remove participant from channel.
+    ///
+    /// Private channels flag a key rotation on-chain; follow up with
+    /// [`Self::prepare_key_rotation`] and [`Self::publish_channel_key`].
     pub async fn remove_participant(
         &self,
         channel_address: &Pubkey,
@@ -234,11 +247,12 @@ impl ChannelService {
                 });
             }
             
-            // Build instruction - Note: pod-com doesn't have remove_participant, this would need custom implementation
-            // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
-                feature: "remove_participant".to_string(),
-            });
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(self.moderation_accounts(&channel_account, channel_address, admin, participant_to_remove)?)
+                .args(pod_com::instruction::KickParticipant {})
+                .signer(admin);
 
             // Send transaction
             let signature = ix.send()?;
@@ -258,6 +272,82 @@ impl ChannelService {
         }).await
     }
 
+    /// Promote or demote a participant (`participant` is the member's agent PDA)
+    pub async fn set_participant_role(
+        &self,
+        channel_address: &Pubkey,
+        admin: &Keypair,
+        participant: &Pubkey,
+        role: pod_com::ChannelRole,
+    ) -> Result<()> {
+        let operation_name = "set_participant_role";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = self.get_channel_account(channel_address).await?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(self.moderation_accounts(&channel_account, channel_address, admin, participant)?)
+                .args(pod_com::instruction::SetParticipantRole { role })
+                .signer(admin);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                admin = %admin.pubkey(),
+                participant = %participant,
+                role = ?role,
+                "Participant role updated successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Mute or unmute a participant (`participant` is the member's agent PDA)
+    pub async fn mute_participant(
+        &self,
+        channel_address: &Pubkey,
+        moderator: &Keypair,
+        participant: &Pubkey,
+        is_muted: bool,
+    ) -> Result<()> {
+        let operation_name = "mute_participant";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = self.get_channel_account(channel_address).await?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(self.moderation_accounts(&channel_account, channel_address, moderator, participant)?)
+                .args(pod_com::instruction::MuteParticipant { is_muted })
+                .signer(moderator);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                moderator = %moderator.pubkey(),
+                participant = %participant,
+                is_muted,
+                "Participant mute state updated successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
     /// Update channel settings
     pub async fn update_channel(
         &self,
@@ -285,6 +375,7 @@ impl ChannelService {
                 .request()
                 .accounts(pod_com::accounts::UpdateChannel {
                     channel_account: *channel_address,
+                    protocol_config: derive_protocol_config_pda()?.0,
                     signer: admin.pubkey(),
                 })
                 .args(pod_com::instruction::UpdateChannel {
@@ -313,6 +404,443 @@ impl ChannelService {
         }).await
     }
 
+    /// Leave a channel and close the participant account, reclaiming its rent
+    pub async fn leave_channel(
+        &self,
+        channel_address: &Pubkey,
+        member: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "leave_channel";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (agent_pda, _bump) = derive_agent_pda_v1(&member.pubkey())?;
+            let (participant_pda, _bump) = derive_participant_pda(channel_address, &agent_pda)?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::LeaveAndCloseChannel {
+                    channel_account: *channel_address,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    user: member.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::LeaveAndCloseChannel {})
+                .signer(member);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                member = %member.pubkey(),
+                "Left channel and closed participant account"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Generate the next channel key and seal it to `members` (remaining owner wallets).
+    ///
+    /// Upload the serialized bundle to IPFS, then publish its CID with
+    /// [`Self::publish_channel_key`]. The returned key belongs in the caller's keyring.
+    pub async fn prepare_key_rotation(
+        &self,
+        channel_address: &Pubkey,
+        members: &[Pubkey],
+    ) -> Result<(ChannelKeyBundle, [u8; 32])> {
+        let operation_name = "prepare_key_rotation";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = program.account::<pod_com::ChannelAccount>(*channel_address)?;
+            let key_epoch = channel_account.key_epoch.checked_add(1).ok_or_else(|| PodComError::Internal {
+                message: "Channel key epoch exhausted".to_string(),
+            })?;
+            
+            let key = generate_channel_key_material()?;
+            let bundle = seal_channel_key_bundle(channel_address, key_epoch, &key, members)?;
+            
+            Ok((bundle, key))
+        }).await
+    }
+
+    /// Publish a sealed key bundle's IPFS CID as the channel's new key epoch (creator or admins)
+    pub async fn publish_channel_key(
+        &self,
+        channel_address: &Pubkey,
+        admin: &Keypair,
+        bundle: &ChannelKeyBundle,
+        key_bundle_cid: String,
+    ) -> Result<()> {
+        let operation_name = "publish_channel_key";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = program.account::<pod_com::ChannelAccount>(*channel_address)?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&admin.pubkey())?;
+            
+            // The creator rotates without a participant record of their own
+            let participant_account = if channel_account.creator == admin.pubkey() {
+                None
+            } else {
+                Some(derive_participant_pda(channel_address, &agent_pda)?.0)
+            };
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::RotateChannelKey {
+                    channel_account: *channel_address,
+                    participant_account,
+                    agent_account: agent_pda,
+                    authority: admin.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::RotateChannelKey {
+                    key_epoch: bundle.key_epoch,
+                    key_bundle_cid: key_bundle_cid.clone(),
+                })
+                .signer(admin);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                admin = %admin.pubkey(),
+                key_epoch = bundle.key_epoch,
+                key_bundle_cid = %key_bundle_cid,
+                "Channel key rotated successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Encrypt channel content under the newest key; returns the key epoch to send it with
+    pub fn encrypt_channel_message(&self, keyring: &ChannelKeyring, plaintext: &[u8]) -> Result<(u32, Vec<u8>)> {
+        let (key_epoch, key) = keyring.latest().ok_or(PodComError::MissingEncryptionKey)?;
+        Ok((key_epoch, encrypt_message(plaintext, key)?))
+    }
+
+    /// Decrypt channel content using the key for the epoch recorded on the message
+    pub fn decrypt_channel_message(
+        &self,
+        keyring: &ChannelKeyring,
+        key_epoch: u32,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let key = keyring.key_for_epoch(key_epoch).ok_or(PodComError::MissingEncryptionKey)?;
+        decrypt_message(ciphertext, key)
+    }
+
+    /// Invite an agent wallet to a private channel; the invitation expires after `expires_in` seconds
+    pub async fn invite_participant(
+        &self,
+        channel_address: &Pubkey,
+        inviter: &Keypair,
+        invitee: &Pubkey,
+        expires_in: i64,
+    ) -> Result<Pubkey> {
+        let operation_name = "invite_participant";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = self.get_channel_account(channel_address).await?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&inviter.pubkey())?;
+            let (invitation_pda, _bump) = derive_invitation_pda(channel_address, invitee)?;
+            
+            // The creator invites without a participant record of their own
+            let participant_account = if channel_account.creator == inviter.pubkey() {
+                None
+            } else {
+                Some(derive_participant_pda(channel_address, &agent_pda)?.0)
+            };
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::InviteToChannel {
+                    channel_account: *channel_address,
+                    participant_account,
+                    agent_account: agent_pda,
+                    invitation_account: invitation_pda,
+                    protocol_config: derive_protocol_config_pda()?.0,
+                    inviter: inviter.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                })
+                .args(pod_com::instruction::InviteToChannel {
+                    invitee: *invitee,
+                    nonce: rand::thread_rng().gen(),
+                    expires_in,
+                })
+                .signer(inviter);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                invitee = %invitee,
+                expires_in,
+                "Invitation sent successfully"
+            );
+
+            Ok(invitation_pda)
+        }).await
+    }
+
+    /// Accept an invitation without joining; a later join consumes it
+    pub async fn accept_invitation(
+        &self,
+        channel_address: &Pubkey,
+        invitee: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "accept_invitation";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (invitation_pda, _bump) = derive_invitation_pda(channel_address, &invitee.pubkey())?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::AcceptInvitation {
+                    invitation_account: invitation_pda,
+                    invitee: invitee.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::AcceptInvitation {})
+                .signer(invitee);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                invitee = %invitee.pubkey(),
+                "Invitation accepted successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Revoke an outstanding invitation (inviter or channel creator); rent returns to the inviter
+    pub async fn revoke_invitation(
+        &self,
+        channel_address: &Pubkey,
+        invitee: &Pubkey,
+        authority: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "revoke_invitation";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (invitation_pda, _bump) = derive_invitation_pda(channel_address, invitee)?;
+            let invitation = program.account::<pod_com::ChannelInvitation>(invitation_pda)?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::RevokeInvitation {
+                    invitation_account: invitation_pda,
+                    channel_account: *channel_address,
+                    inviter: invitation.inviter,
+                    authority: authority.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::RevokeInvitation {})
+                .signer(authority);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                invitee = %invitee,
+                "Invitation revoked successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Create a multi-use invite code. The code is a keypair the operator hands out;
+    /// only its public key is stored on-chain.
+    pub async fn create_invite_code(
+        &self,
+        channel_address: &Pubkey,
+        creator: &Keypair,
+        code: &Pubkey,
+        max_uses: u32,
+        expires_in: i64,
+    ) -> Result<Pubkey> {
+        let operation_name = "create_invite_code";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = self.get_channel_account(channel_address).await?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&creator.pubkey())?;
+            let (invite_code_pda, _bump) = derive_invite_code_pda(channel_address, code)?;
+            
+            // The creator issues codes without a participant record of their own
+            let participant_account = if channel_account.creator == creator.pubkey() {
+                None
+            } else {
+                Some(derive_participant_pda(channel_address, &agent_pda)?.0)
+            };
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::CreateInviteCode {
+                    channel_account: *channel_address,
+                    participant_account,
+                    agent_account: agent_pda,
+                    invite_code: invite_code_pda,
+                    protocol_config: derive_protocol_config_pda()?.0,
+                    creator: creator.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                })
+                .args(pod_com::instruction::CreateInviteCode {
+                    code_signer: *code,
+                    max_uses,
+                    expires_in,
+                })
+                .signer(creator);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                invite_code = %invite_code_pda,
+                signature = %signature,
+                max_uses,
+                "Invite code created successfully"
+            );
+
+            Ok(invite_code_pda)
+        }).await
+    }
+
+    /// Join a channel using an invite code keypair handed out by an operator.
+    /// The code co-signs the join, so its secret never appears on-chain.
+    pub async fn join_channel_with_code(
+        &self,
+        channel_address: &Pubkey,
+        member: &Keypair,
+        code: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "join_channel_with_code";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let channel_account = program.account::<pod_com::ChannelAccount>(*channel_address)?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&member.pubkey())?;
+            let (participant_pda, _bump) = derive_participant_pda(channel_address, &agent_pda)?;
+            let (invite_code_pda, _bump) = derive_invite_code_pda(channel_address, &code.pubkey())?;
+            
+            // Paid channels check the member's escrow balance on join
+            let escrow_account = if channel_account.fee_per_message > 0 {
+                Some(derive_channel_escrow_pda(channel_address, &member.pubkey())?.0)
+            } else {
+                None
+            };
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::JoinChannelWithCode {
+                    channel_account: *channel_address,
+                    invite_code: invite_code_pda,
+                    code_signer: code.pubkey(),
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    escrow_account,
+                    user: member.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::JoinChannelWithCode {})
+                .signer(member)
+                .signer(code);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                invite_code = %invite_code_pda,
+                signature = %signature,
+                member = %member.pubkey(),
+                "Joined channel with invite code"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Close a used invitation; rent always returns to the inviter.
+    /// Either the inviter or the invitee may sign.
+    pub async fn close_invitation(
+        &self,
+        channel_address: &Pubkey,
+        invitee: &Pubkey,
+        authority: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "close_invitation";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (invitation_pda, _bump) = derive_invitation_pda(channel_address, invitee)?;
+            let invitation = program.account::<pod_com::ChannelInvitation>(invitation_pda)?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::CloseChannelInvitation {
+                    invitation_account: invitation_pda,
+                    inviter: invitation.inviter,
+                    authority: authority.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::CloseChannelInvitation {})
+                .signer(authority);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                signature = %signature,
+                invitee = %invitee,
+                "Invitation closed successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
     /// Archive a channel (make it read-only)
     pub async fn archive_channel(
         &self,
@@ -339,6 +867,7 @@ impl ChannelService {
                 .request()
                 .accounts(pod_com::accounts::UpdateChannel {
                     channel_account: *channel_address,
+                    protocol_config: derive_protocol_config_pda()?.0,
                     signer: admin.pubkey(),
                 })
                 .args(pod_com::instruction::UpdateChannel {
@@ -485,6 +1014,35 @@ impl ChannelService {
         }).await
     }
 
+    /// Build the account set shared by the role, kick and mute instructions
+    fn moderation_accounts(
+        &self,
+        channel: &ChannelAccount,
+        channel_address: &Pubkey,
+        moderator: &Keypair,
+        participant: &Pubkey,
+    ) -> Result<pod_com::accounts::ModerateParticipant> {
+        let (moderator_agent, _) = derive_agent_pda_v1(&moderator.pubkey())?;
+        let (target_participant, _) = derive_participant_pda(channel_address, participant)?;
+        
+        // The creator moderates without a participant record of their own
+        let moderator_participant = if channel.creator == moderator.pubkey() {
+            None
+        } else {
+            Some(derive_participant_pda(channel_address, &moderator_agent)?.0)
+        };
+        
+        Ok(pod_com::accounts::ModerateParticipant {
+            channel_account: *channel_address,
+            target_participant,
+            target_agent: *participant,
+            moderator_participant,
+            moderator_agent,
+            moderator: moderator.pubkey(),
+            protocol_config: derive_protocol_config_pda()?.0,
+        })
+    }
+
     /// Check if a user has admin privileges for a channel
     fn is_channel_admin(&self, channel: &ChannelAccount, user: &Pubkey) -> bool {
         // Channel creator is always admin
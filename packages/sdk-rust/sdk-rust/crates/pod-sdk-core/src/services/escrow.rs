@@ -21,7 +21,10 @@ use pod_sdk_types::{
 use crate::{
     error::{PodComError, Result},
     services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
-    utils::account::{derive_escrow_pda, validate_escrow_account},
+    utils::account::{
+        derive_channel_escrow_pda, derive_escrow_dispute_pda, derive_escrow_pda,
+        derive_protocol_config_pda, validate_escrow_account,
+    },
 };
 
 /// Service for managing escrow accounts
@@ -65,6 +68,8 @@ impl EscrowService {
                 .collect();
             let (escrow_pda, _bump) = derive_escrow_pda(&payer.pubkey(), &escrow_id)?;
             
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             // Build instruction
             let ix = program
                 .request()
@@ -74,6 +79,7 @@ impl EscrowService {
                     beneficiary: params.beneficiary,
                     system_program: solana_sdk::system_program::id(),
                     rent: solana_sdk::sysvar::rent::id(),
+                    protocol_config,
                 })
                 .args(pod_com::instruction::DepositEscrow {
                     escrow_id: escrow_id.clone(),
@@ -149,6 +155,8 @@ impl EscrowService {
                 });
             }
             
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             // Build instruction
             let ix = program
                 .request()
@@ -156,6 +164,7 @@ impl EscrowService {
                     escrow: *escrow_address,
                     releaser: releaser.pubkey(),
                     beneficiary: escrow_account.beneficiary,
+                    protocol_config,
                 })
                 .args(pod_com::instruction::WithdrawEscrow {
                     release_amount: params.release_amount.unwrap_or(escrow_account.amount),
@@ -213,6 +222,8 @@ impl EscrowService {
                 });
             }
             
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             // Build instruction
             let ix = program
                 .request()
@@ -220,6 +231,7 @@ impl EscrowService {
                     escrow: *escrow_address,
                     refunder: refunder.pubkey(),
                     payer: escrow_account.payer,
+                    protocol_config,
                 })
                 .args(pod_com::instruction::WithdrawEscrow {
                     refund_reason,
@@ -245,22 +257,22 @@ impl EscrowService {
         }).await
     }
 
-    /// Initiate dispute for an escrow
+    /// Initiate dispute for an agent escrow (payer or payee)
     pub async fn dispute_escrow(
         &self,
         escrow_address: &Pubkey,
         disputer: &Keypair,
         params: DisputeEscrowParams,
-    ) -> Result<EscrowAccount> {
+    ) -> Result<pod_com::AgentEscrowAccount> {
         let operation_name = "dispute_escrow";
         
         self.base.execute_operation(operation_name, async {
             let program = self.base.program()?;
             
-            let escrow_account = self.get_escrow_account(escrow_address).await?;
+            let escrow_account = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
             
             // Verify disputer is involved in the escrow
-            if escrow_account.payer != disputer.pubkey() && escrow_account.beneficiary != disputer.pubkey() {
+            if escrow_account.payer_wallet != disputer.pubkey() && escrow_account.payee_wallet != disputer.pubkey() {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "escrow".to_string(),
                     action: "dispute".to_string(),
@@ -268,19 +280,362 @@ impl EscrowService {
             }
             
             // Check escrow state
-            if escrow_account.status != EscrowStatus::Active {
-                return Err(PodComError::InvalidEscrowState {
-                    escrow_address: *escrow_address,
-                    current_state: escrow_account.status,
-                    expected_state: EscrowStatus::Active,
+            require_agent_escrow_status(escrow_address, &escrow_account, pod_com::AgentEscrowStatus::Active)?;
+            if escrow_account.arbitrator_set == Pubkey::default() {
+                return Err(PodComError::InvalidConfiguration {
+                    message: "Escrow has no arbitrator set to resolve a dispute".to_string(),
                 });
             }
             
-            // Build instruction - Note: pod-com doesn't have dispute_escrow, this would need custom implementation
-            // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
-                feature: "dispute_escrow".to_string(),
-            });
+            let (dispute_pda, _bump) = derive_escrow_dispute_pda(escrow_address)?;
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::OpenEscrowDispute {
+                    escrow_account: *escrow_address,
+                    dispute_account: dispute_pda,
+                    opener: disputer.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::OpenEscrowDispute {
+                    reason_hash: params.reason_hash,
+                })
+                .signer(disputer);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            // Fetch updated escrow account
+            let updated_account = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            
+            tracing::info!(
+                escrow_address = %escrow_address,
+                dispute_address = %dispute_pda,
+                signature = %signature,
+                disputer = %disputer.pubkey(),
+                "Escrow dispute opened successfully"
+            );
+
+            Ok(updated_account)
+        }).await
+    }
+
+    /// Submit evidence (content hash + IPFS CID) for an open escrow dispute
+    pub async fn submit_dispute_evidence(
+        &self,
+        escrow_address: &Pubkey,
+        submitter: &Keypair,
+        evidence_hash: [u8; 32],
+        ipfs_cid: String,
+    ) -> Result<pod_com::EscrowDispute> {
+        let operation_name = "submit_dispute_evidence";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let escrow_account = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            
+            // Only the parties to the escrow can submit evidence
+            if escrow_account.payer_wallet != submitter.pubkey() && escrow_account.payee_wallet != submitter.pubkey() {
+                return Err(PodComError::UnauthorizedAccess {
+                    resource: "escrow".to_string(),
+                    action: "submit_evidence".to_string(),
+                });
+            }
+            
+            // Check escrow state
+            require_agent_escrow_status(escrow_address, &escrow_account, pod_com::AgentEscrowStatus::Disputed)?;
+            
+            let (dispute_pda, _bump) = derive_escrow_dispute_pda(escrow_address)?;
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::SubmitEscrowEvidence {
+                    escrow_account: *escrow_address,
+                    dispute_account: dispute_pda,
+                    submitter: submitter.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::SubmitEscrowEvidence {
+                    evidence_hash,
+                    ipfs_cid: ipfs_cid.clone(),
+                })
+                .signer(submitter);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            // Fetch the dispute with its updated evidence count
+            let dispute_account = program.account::<pod_com::EscrowDispute>(dispute_pda)?;
+            
+            tracing::info!(
+                escrow_address = %escrow_address,
+                signature = %signature,
+                submitter = %submitter.pubkey(),
+                ipfs_cid = %ipfs_cid,
+                "Dispute evidence submitted successfully"
+            );
+
+            Ok(dispute_account)
+        }).await
+    }
+
+    /// Cast an arbitrator vote on a disputed escrow
+    ///
+    /// The escrow is split once enough arbitrators in the designated set vote
+    /// for the same `payee_share_bps`; earlier votes are only recorded, and an
+    /// arbitrator may call this again to change their vote.
+    pub async fn resolve_dispute(
+        &self,
+        escrow_address: &Pubkey,
+        arbitrator: &Keypair,
+        payee_share_bps: u16,
+    ) -> Result<pod_com::AgentEscrowAccount> {
+        let operation_name = "resolve_dispute";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            if payee_share_bps > 10_000 {
+                return Err(PodComError::InvalidConfiguration {
+                    message: "Payee share cannot exceed 10000 basis points".to_string(),
+                });
+            }
+            
+            let (dispute_pda, _bump) = derive_escrow_dispute_pda(escrow_address)?;
+            let escrow_state = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            let dispute_state = program.account::<pod_com::EscrowDispute>(dispute_pda)?;
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::ResolveEscrowDispute {
+                    escrow_account: *escrow_address,
+                    dispute_account: dispute_pda,
+                    arbitrator_set: dispute_state.arbitrator_set,
+                    payee_wallet: escrow_state.payee_wallet,
+                    payer_wallet: escrow_state.payer_wallet,
+                    payer_agent: escrow_state.payer,
+                    payee_agent: escrow_state.payee,
+                    arbitrator: arbitrator.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::ResolveEscrowDispute {
+                    payee_share_bps,
+                })
+                .signer(arbitrator);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            // Fetch updated escrow account
+            let updated_account = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            
+            tracing::info!(
+                escrow_address = %escrow_address,
+                signature = %signature,
+                arbitrator = %arbitrator.pubkey(),
+                payee_share_bps = payee_share_bps,
+                "Dispute vote submitted successfully"
+            );
+
+            Ok(updated_account)
+        }).await
+    }
+
+    /// Settle a dispute the arbitrators left undecided past the dispute timeout (payer or payee)
+    ///
+    /// The escrow is split on the median of the votes cast, or refunded to the
+    /// payer if no arbitrator voted.
+    pub async fn settle_expired_dispute(
+        &self,
+        escrow_address: &Pubkey,
+        settler: &Keypair,
+    ) -> Result<pod_com::AgentEscrowAccount> {
+        let operation_name = "settle_expired_dispute";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let escrow_state = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            if escrow_state.payer_wallet != settler.pubkey() && escrow_state.payee_wallet != settler.pubkey() {
+                return Err(PodComError::UnauthorizedAccess {
+                    resource: "escrow".to_string(),
+                    action: "settle_dispute".to_string(),
+                });
+            }
+            require_agent_escrow_status(escrow_address, &escrow_state, pod_com::AgentEscrowStatus::Disputed)?;
+            
+            let (dispute_pda, _bump) = derive_escrow_dispute_pda(escrow_address)?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::SettleExpiredEscrowDispute {
+                    escrow_account: *escrow_address,
+                    dispute_account: dispute_pda,
+                    payee_wallet: escrow_state.payee_wallet,
+                    payer_wallet: escrow_state.payer_wallet,
+                    payer_agent: escrow_state.payer,
+                    settler: settler.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::SettleExpiredEscrowDispute {})
+                .signer(settler);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            let updated_account = program.account::<pod_com::AgentEscrowAccount>(*escrow_address)?;
+            
+            tracing::info!(
+                escrow_address = %escrow_address,
+                signature = %signature,
+                settler = %settler.pubkey(),
+                "Expired escrow dispute settled successfully"
+            );
+
+            Ok(updated_account)
+        }).await
+    }
+
+    /// Claim channel fees accrued in participant escrows (channel creator only)
+    ///
+    /// Each escrow is claimed in its own transaction; escrows with nothing to
+    /// claim are skipped. Returns the total number of lamports claimed.
+    pub async fn claim_channel_fees(
+        &self,
+        channel_address: &Pubkey,
+        creator: &Keypair,
+        depositors: &[Pubkey],
+    ) -> Result<u64> {
+        let operation_name = "claim_channel_fees";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            // Verify creator privileges
+            let channel_account = program.account::<pod_com::ChannelAccount>(*channel_address)?;
+            if channel_account.creator != creator.pubkey() {
+                return Err(PodComError::UnauthorizedAccess {
+                    resource: "channel".to_string(),
+                    action: "claim_channel_fees".to_string(),
+                });
+            }
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            let config = program.account::<pod_com::ProtocolConfig>(protocol_config)?;
+            
+            let mut total_claimed = 0u64;
+            
+            for depositor in depositors {
+                let (escrow_pda, _bump) = derive_channel_escrow_pda(channel_address, depositor)?;
+                let escrow_account = program.account::<pod_com::EscrowAccount>(escrow_pda)?;
+                
+                // Nothing accrued in this escrow yet
+                if escrow_account.unclaimed_fees == 0 {
+                    continue;
+                }
+                
+                // Build instruction
+                let ix = program
+                    .request()
+                    .accounts(pod_com::accounts::ClaimChannelFees {
+                        escrow_account: escrow_pda,
+                        channel_account: *channel_address,
+                        creator: creator.pubkey(),
+                        protocol_config,
+                        fee_recipient: config.fee_recipient,
+                    })
+                    .args(pod_com::instruction::ClaimChannelFees {})
+                    .signer(creator);
+
+                // Send transaction
+                let signature = ix.send()?;
+                
+                total_claimed = total_claimed
+                    .checked_add(escrow_account.unclaimed_fees)
+                    .ok_or_else(|| PodComError::Internal {
+                        message: "Claimed fee total overflowed".to_string(),
+                    })?;
+                
+                tracing::info!(
+                    channel_address = %channel_address,
+                    escrow_address = %escrow_pda,
+                    signature = %signature,
+                    amount = escrow_account.unclaimed_fees,
+                    "Channel fees claimed from escrow"
+                );
+            }
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                creator = %creator.pubkey(),
+                total_claimed = total_claimed,
+                "Channel fees claimed successfully"
+            );
+
+            Ok(total_claimed)
+        }).await
+    }
+
+    /// Close the depositor's emptied channel escrow and reclaim its rent.
+    ///
+    /// The escrow must have no remaining balance and no unclaimed channel fees.
+    pub async fn close_channel_escrow(
+        &self,
+        channel_address: &Pubkey,
+        depositor: &Keypair,
+    ) -> Result<()> {
+        let operation_name = "close_channel_escrow";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (escrow_pda, _bump) = derive_channel_escrow_pda(channel_address, &depositor.pubkey())?;
+            let escrow_account = program.account::<pod_com::EscrowAccount>(escrow_pda)?;
+            
+            if escrow_account.amount > 0 || escrow_account.unclaimed_fees > 0 {
+                return Err(PodComError::InvalidConfiguration {
+                    message: "Escrow still holds a balance or unclaimed fees".to_string(),
+                });
+            }
+            
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::CloseEscrow {
+                    escrow_account: escrow_pda,
+                    channel_account: *channel_address,
+                    depositor: depositor.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::CloseEscrow {})
+                .signer(depositor);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                channel_address = %channel_address,
+                escrow_address = %escrow_pda,
+                signature = %signature,
+                "Channel escrow closed successfully"
+            );
+
+            Ok(())
         }).await
     }
 
@@ -533,6 +888,13 @@ impl EscrowService {
     }
 }
 
+/// Parameters for opening an escrow dispute
+#[derive(Debug, Clone)]
+pub struct DisputeEscrowParams {
+    /// Hash of the off-chain dispute reason
+    pub reason_hash: [u8; 32],
+}
+
 /// Escrow statistics
 #[derive(Debug, Clone)]
 pub struct EscrowStats {
@@ -661,6 +1023,33 @@ impl BaseService for EscrowService {
     }
 }
 
+/// Map an on-chain agent escrow status onto the SDK's escrow status
+fn sdk_escrow_status(status: pod_com::AgentEscrowStatus) -> EscrowStatus {
+    match status {
+        pod_com::AgentEscrowStatus::Active => EscrowStatus::Active,
+        pod_com::AgentEscrowStatus::Disputed => EscrowStatus::Disputed,
+        pod_com::AgentEscrowStatus::Refunded => EscrowStatus::Refunded,
+        // A resolved dispute has paid the escrow out, like a release
+        pod_com::AgentEscrowStatus::Released | pod_com::AgentEscrowStatus::Resolved => EscrowStatus::Released,
+    }
+}
+
+/// Fail unless an agent escrow is in the `expected` state
+fn require_agent_escrow_status(
+    escrow_address: &Pubkey,
+    escrow: &pod_com::AgentEscrowAccount,
+    expected: pod_com::AgentEscrowStatus,
+) -> Result<()> {
+    if escrow.status != expected {
+        return Err(PodComError::InvalidEscrowState {
+            escrow_address: *escrow_address,
+            current_state: sdk_escrow_status(escrow.status),
+            expected_state: sdk_escrow_status(expected),
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,28 +7,44 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anchor_client::Program;
+use anchor_lang::{AnchorDeserialize, Discriminator};
 use async_trait::async_trait;
+use base64::Engine;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::config::RpcTransactionConfig;
 use solana_sdk::{
     pubkey::Pubkey,
+    signature::Signature,
     signer::{keypair::Keypair, Signer},
 };
+use solana_transaction_status::UiTransactionEncoding;
 
-// Import UUID for message ID generation
-use rand::{distributions::Alphanumeric, Rng};
+// Random nonces for channel message PDAs
+use rand::Rng;
 
 // Import the actual program types
 use pod_com::{MessageAccount, ChannelAccount, AgentAccount, MessageType, MessageStatus};
 
 use crate::{
-    error::{PodComError, Result},
+    error::{MessageError, NetworkError, PodComError, Result},
     services::base::{BaseService, ServiceBase, ServiceConfig, ServiceHealth, ServiceMetrics},
     types::{
         SendMessageParams, MessageContent, FilterOptions,
         BatchOperationResult, RequestOptions,
     },
     utils::{
-        account::{derive_message_pda, validate_message_account},
+        account::{
+            derive_agent_pda_v1, derive_channel_escrow_pda, derive_channel_message_pda,
+            derive_direct_message_pda, derive_inbox_pda, derive_participant_pda, derive_protocol_config_pda, derive_reaction_pda,
+            validate_message_account,
+        },
         crypto::{encrypt_message, decrypt_message, compress_message, decompress_message, secure_hash_data},
+        encryption::{open_message_envelope, seal_message_envelope, MessageEnvelope},
     },
 };
 
@@ -46,13 +62,16 @@ impl MessageService {
         }
     }
 
-    /// Send a message to a channel
+    /// Send a message to a channel.
+    ///
+    /// Channel messages are broadcast to the channel; direct messages (which are
+    /// indexed in the recipient agent's inbox) go through [`Self::send_encrypted_message`].
     pub async fn send_message(
         &self,
         sender: &Keypair,
         channel_address: &Pubkey,
         params: SendMessageParams,
-    ) -> Result<(Pubkey, MessageAccount)> {
+    ) -> Result<(Pubkey, pod_com::ChannelMessage)> {
         let operation_name = "send_message";
         
         self.base.execute_operation(operation_name, async {
@@ -67,13 +86,11 @@ impl MessageService {
                 });
             }
             
-            // Generate message ID and derive PDA
-            let message_id: String = rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(32)
-                .map(char::from)
-                .collect();
-            let (message_pda, _bump) = derive_message_pda(channel_address, &message_id)?;
+            // The nonce keeps each of the sender's channel messages at a distinct PDA
+            let nonce: u64 = rand::thread_rng().gen();
+            let (agent_pda, _bump) = derive_agent_pda_v1(&sender.pubkey())?;
+            let (participant_pda, _bump) = derive_participant_pda(channel_address, &agent_pda)?;
+            let (message_pda, _bump) = derive_channel_message_pda(channel_address, &sender.pubkey(), nonce)?;
             
             // Encrypt message content
             let encrypted_content = encrypt_message(&params.content, &channel_account.encryption_key)?;
@@ -84,30 +101,35 @@ impl MessageService {
             } else {
                 encrypted_content
             };
-
-            // Calculate expiration timestamp
-            let expiration_timestamp = params.expiration_duration
-                .map(|duration| {
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() + duration.as_secs()
-                })
-                .unwrap_or(0); // 0 means no expiration
-
+            
+            // Paid channels draw the fee from the sender's channel escrow
+            let escrow_account = if channel_account.fee_per_message > 0 {
+                Some(derive_channel_escrow_pda(channel_address, &sender.pubkey())?.0)
+            } else {
+                None
+            };
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
             // Build instruction
             let ix = program
                 .request()
-                .accounts(pod_com::accounts::SendMessage {
+                .accounts(pod_com::accounts::BroadcastMessage {
+                    channel_account: *channel_address,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    session_key: None,
                     message_account: message_pda,
-                    sender_agent: sender.pubkey(),
-                    signer: sender.pubkey(),
+                    parent_message: None,
+                    escrow_account,
+                    protocol_config,
+                    user: sender.pubkey(),
                     system_program: solana_sdk::system_program::id(),
                 })
-                .args(pod_com::instruction::SendMessage {
-                    recipient: *channel_address,
-                    payload_hash: pod_com::secure_hash_data(&final_content).unwrap_or([0u8; 32]),
+                .args(pod_com::instruction::BroadcastMessage {
+                    content: base64::engine::general_purpose::STANDARD.encode(&final_content),
                     message_type: params.message_type,
+                    reply_to: None,
+                    _nonce: nonce,
                 })
                 .signer(sender);
 
@@ -115,14 +137,13 @@ impl MessageService {
             let signature = ix.send()?;
             
             // Fetch created message account
-            let message_account = self.get_message_account(&message_pda).await?;
+            let message_account = program.account::<pod_com::ChannelMessage>(message_pda)?;
             
             tracing::info!(
                 message_address = %message_pda,
                 channel_address = %channel_address,
                 signature = %signature,
                 sender = %sender.pubkey(),
-                message_id = %message_id,
                 "Message sent successfully"
             );
 
@@ -195,6 +216,27 @@ impl MessageService {
         }).await
     }
 
+    /// Read a recipient agent's direct-message inbox, newest first, with a single account fetch.
+    ///
+    /// The program keeps the latest `pod_com::INBOX_CAPACITY` messages; older ones have
+    /// been overwritten in the ring buffer. An agent with no inbox yet has no messages.
+    pub async fn get_inbox(&self, recipient_agent: &Pubkey) -> Result<Vec<pod_com::InboxEntry>> {
+        let operation_name = "get_inbox";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (inbox_pda, _bump) = derive_inbox_pda(recipient_agent)?;
+            let inbox = match program.account::<pod_com::AgentInbox>(inbox_pda) {
+                Ok(inbox) => inbox,
+                Err(anchor_client::ClientError::AccountNotFound) => return Ok(Vec::new()),
+                Err(e) => return Err(e.into()),
+            };
+            
+            Ok(inbox_entries_newest_first(&inbox))
+        }).await
+    }
+
     /// List messages in a channel
     pub async fn list_channel_messages(
         &self,
@@ -252,53 +294,594 @@ impl MessageService {
         }).await
     }
 
-    /// React to a message
+    /// Fetch a thread: the root message and every reply beneath it, oldest first.
+    ///
+    /// Covers both `ChannelMessage` accounts and compressed messages, which are
+    /// read from the channel's `CompressedMessageAppended` events. A compressed
+    /// message's id (as used in `reply_to`) is its leaf hash.
+    pub async fn get_thread(&self, channel_address: &Pubkey, root: &Pubkey) -> Result<Thread> {
+        let operation_name = "get_thread";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            // Regular replies always carry their thread root; compressed ones only link to a parent
+            let mut candidates: Vec<ThreadMessage> = program
+                .accounts::<pod_com::ChannelMessage>(vec![RpcFilterType::Memcmp(
+                    Memcmp::new_base58_encoded(CHANNEL_MESSAGE_THREAD_ROOT_OFFSET, root.as_ref()),
+                )])
+                .await?
+                .into_iter()
+                .filter(|(_, message)| message.channel == *channel_address)
+                .map(|(address, message)| ThreadMessage::Regular { address, message })
+                .collect();
+            candidates.extend(
+                self.compressed_channel_messages(program, channel_address, CompressedScan::default())
+                    .await?
+                    .into_iter()
+                    .map(ThreadMessage::Compressed),
+            );
+            
+            let root_index = candidates
+                .iter()
+                .position(|message| message.id() == *root)
+                .ok_or_else(|| PodComError::Internal {
+                    message: format!("Thread root {} not found in channel {}", root, channel_address),
+                })?;
+            let root_message = candidates.swap_remove(root_index);
+            
+            // Walk reply links outwards from the root until no new replies attach
+            let mut members = std::collections::HashSet::from([*root]);
+            let mut replies = Vec::new();
+            loop {
+                let (attached, rest): (Vec<_>, Vec<_>) = candidates
+                    .into_iter()
+                    .partition(|message| message.reply_to().is_some_and(|parent| members.contains(&parent)));
+                candidates = rest;
+                if attached.is_empty() {
+                    break;
+                }
+                members.extend(attached.iter().map(ThreadMessage::id));
+                replies.extend(attached);
+            }
+            replies.sort_by_key(ThreadMessage::created_at);
+            
+            Ok(Thread { root: root_message, replies })
+        }).await
+    }
+
+    /// List direct replies to `message` (regular or compressed), oldest first
+    pub async fn list_replies(
+        &self,
+        channel_address: &Pubkey,
+        message: &Pubkey,
+        page: ReplyPage,
+    ) -> Result<Vec<ThreadMessage>> {
+        let operation_name = "list_replies";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            // `reply_to` is an Option, so match its Some tag followed by the parent key
+            let mut reply_to_bytes = vec![1u8];
+            reply_to_bytes.extend_from_slice(message.as_ref());
+            
+            let mut replies: Vec<ThreadMessage> = program
+                .accounts::<pod_com::ChannelMessage>(vec![RpcFilterType::Memcmp(
+                    Memcmp::new_base58_encoded(CHANNEL_MESSAGE_REPLY_TO_OFFSET, &reply_to_bytes),
+                )])
+                .await?
+                .into_iter()
+                .filter(|(_, reply)| reply.channel == *channel_address)
+                .map(|(address, message)| ThreadMessage::Regular { address, message })
+                .collect();
+            
+            // The scan runs oldest first, so no compressed reply past the page window is needed
+            let mut scan = CompressedScan {
+                reply_to: Some(*message),
+                not_before: None,
+                max_matches: Some(page.offset.saturating_add(page.limit)),
+            };
+            // A regular parent bounds it further: nothing replies to it before it existed,
+            // and its reply_count says how many compressed replies there are at most
+            if let Ok(parent) = program.account::<pod_com::ChannelMessage>(*message) {
+                let compressed_replies = (parent.reply_count as usize).saturating_sub(replies.len());
+                scan.not_before = Some(parent.created_at);
+                scan.max_matches = scan.max_matches.map(|max| max.min(compressed_replies));
+            }
+            
+            if scan.max_matches != Some(0) {
+                replies.extend(
+                    self.compressed_channel_messages(program, channel_address, scan)
+                        .await?
+                        .into_iter()
+                        .map(ThreadMessage::Compressed),
+                );
+            }
+            replies.sort_by_key(ThreadMessage::created_at);
+            
+            Ok(replies.into_iter().skip(page.offset).take(page.limit).collect())
+        }).await
+    }
+
+    /// Read compressed messages appended to a channel from its transaction logs, oldest first.
+    ///
+    /// Signatures are paged through `getSignaturesForAddress` (newest first, back to
+    /// `scan.not_before`); transactions are then fetched oldest first until
+    /// `scan.max_matches` messages have been kept.
+    async fn compressed_channel_messages(
+        &self,
+        program: &Program<Arc<Keypair>>,
+        channel_address: &Pubkey,
+        scan: CompressedScan,
+    ) -> Result<Vec<pod_com::CompressedMessageAppended>> {
+        let rpc = program.rpc();
+        let rpc_failed = |method: &str, reason: String| NetworkError::RpcFailed {
+            method: method.to_string(),
+            reason,
+        };
+        
+        let mut signatures = Vec::new();
+        let mut before = None;
+        'pages: loop {
+            let page = rpc
+                .get_signatures_for_address_with_config(
+                    channel_address,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: None,
+                        commitment: None,
+                    },
+                )
+                .await
+                .map_err(|e| rpc_failed("getSignaturesForAddress", e.to_string()))?;
+            if page.is_empty() {
+                break;
+            }
+            for status in page {
+                let signature = status
+                    .signature
+                    .parse::<Signature>()
+                    .map_err(|e| rpc_failed("getSignaturesForAddress", e.to_string()))?;
+                before = Some(signature);
+                if scan.not_before.is_some_and(|not_before| {
+                    status.block_time.is_some_and(|block_time| block_time < not_before)
+                }) {
+                    break 'pages;
+                }
+                if status.err.is_none() {
+                    signatures.push(signature);
+                }
+            }
+        }
+        
+        // Light Protocol instructions use address lookup tables, so accept versioned transactions
+        let transaction_config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        };
+        let mut messages = Vec::new();
+        for signature in signatures.iter().rev() {
+            if scan.max_matches.is_some_and(|max| messages.len() >= max) {
+                break;
+            }
+            let transaction = rpc
+                .get_transaction_with_config(signature, transaction_config)
+                .await
+                .map_err(|e| rpc_failed("getTransaction", e.to_string()))?;
+            
+            let logs: Option<Vec<String>> = transaction
+                .transaction
+                .meta
+                .and_then(|meta| meta.log_messages.into());
+            messages.extend(
+                logs.unwrap_or_default()
+                    .iter()
+                    .filter_map(|line| decode_compressed_message_event(line))
+                    .filter(|event| event.channel == *channel_address)
+                    .filter(|event| scan.reply_to.is_none() || event.reply_to == scan.reply_to),
+            );
+        }
+        if let Some(max) = scan.max_matches {
+            messages.truncate(max);
+        }
+        
+        Ok(messages)
+    }
+
+    /// React to a channel message; returns the message's updated reaction counts
     pub async fn react_to_message(
         &self,
         message_address: &Pubkey,
         reactor: &Keypair,
-        reaction: String,
-    ) -> Result<MessageAccount> {
+        reaction: pod_com::ReactionKind,
+    ) -> Result<Vec<(pod_com::ReactionKind, u16)>> {
         let operation_name = "react_to_message";
         
         self.base.execute_operation(operation_name, async {
             let program = self.base.program()?;
             
-            let message_account = self.get_message_account(message_address).await?;
+            let message_account = program.account::<pod_com::ChannelMessage>(*message_address)?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&reactor.pubkey())?;
             
-            // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(message_account.channel)?;
-            if !channel_account.participants.contains(&reactor.pubkey()) {
+            // Only active channel members can react, and only once per reaction kind
+            let (participant_pda, _bump) = derive_participant_pda(&message_account.channel, &agent_pda)?;
+            let (reaction_pda, _bump) = derive_reaction_pda(message_address, &agent_pda, reaction)?;
+            if self.has_reacted(message_address, &reactor.pubkey(), reaction).await? {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "message".to_string(),
-                    action: "react".to_string(),
+                    action: "react_twice".to_string(),
                 });
             }
             
-            // Build instruction - Note: pod-com doesn't have react_to_message, this would need custom implementation
-            // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
-                feature: "react_to_message".to_string(),
-            });
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::AddReaction {
+                    message_account: *message_address,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    reaction_account: reaction_pda,
+                    user: reactor.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::AddReaction { reaction })
+                .signer(reactor);
 
             // Send transaction
             let signature = ix.send()?;
             
-            // Fetch updated message account
-            let updated_account = self.get_message_account(message_address).await?;
-            
             tracing::info!(
                 message_address = %message_address,
                 signature = %signature,
                 reactor = %reactor.pubkey(),
+                reaction = ?reaction,
                 "Message reaction added successfully"
             );
 
-            Ok(updated_account)
+            self.get_reaction_counts(message_address).await
+        }).await
+    }
+
+    /// Withdraw a reaction, reclaiming the reaction record's rent.
+    /// Also works after the message itself has been deleted.
+    pub async fn remove_reaction(
+        &self,
+        message_address: &Pubkey,
+        reactor: &Keypair,
+        reaction: pod_com::ReactionKind,
+    ) -> Result<()> {
+        let operation_name = "remove_reaction";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (agent_pda, _bump) = derive_agent_pda_v1(&reactor.pubkey())?;
+            let (reaction_pda, _bump) = derive_reaction_pda(message_address, &agent_pda, reaction)?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::RemoveReaction {
+                    message_account: *message_address,
+                    agent_account: agent_pda,
+                    reaction_account: reaction_pda,
+                    user: reactor.pubkey(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::RemoveReaction {})
+                .signer(reactor);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                message_address = %message_address,
+                signature = %signature,
+                reactor = %reactor.pubkey(),
+                reaction = ?reaction,
+                "Message reaction removed successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Reaction counts for a channel message, one entry per reaction kind
+    pub async fn get_reaction_counts(&self, message_address: &Pubkey) -> Result<Vec<(pod_com::ReactionKind, u16)>> {
+        let operation_name = "get_reaction_counts";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let message_account = program.account::<pod_com::ChannelMessage>(*message_address)?;
+            
+            Ok(pod_com::ReactionKind::ALL
+                .iter()
+                .map(|kind| (*kind, message_account.reaction_counts[kind.index()]))
+                .collect())
         }).await
     }
 
-    /// Delete a message (sender only)
+    /// Whether the agent owned by `reactor` has left `reaction` on a message
+    pub async fn has_reacted(
+        &self,
+        message_address: &Pubkey,
+        reactor: &Pubkey,
+        reaction: pod_com::ReactionKind,
+    ) -> Result<bool> {
+        let operation_name = "has_reacted";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let (agent_pda, _bump) = derive_agent_pda_v1(reactor)?;
+            let (reaction_pda, _bump) = derive_reaction_pda(message_address, &agent_pda, reaction)?;
+            
+            Ok(program.account::<pod_com::MessageReaction>(reaction_pda).is_ok())
+        }).await
+    }
+
+    /// Seal `plaintext` for the agent owned by `recipient` (X25519 ECDH envelope)
+    pub fn seal_message(&self, recipient: &Pubkey, plaintext: &[u8]) -> Result<MessageEnvelope> {
+        seal_message_envelope(plaintext, recipient)
+    }
+
+    /// Open an envelope addressed to `recipient`'s agent
+    pub fn open_message(&self, recipient: &Keypair, envelope: &MessageEnvelope) -> Result<Vec<u8>> {
+        open_message_envelope(envelope, recipient)
+    }
+
+    /// Send an end-to-end encrypted direct message to the agent owned by `recipient`.
+    ///
+    /// Only the envelope header and the ciphertext hash go on-chain; the returned
+    /// envelope's ciphertext must be delivered off-chain (e.g. via IPFS).
+    pub async fn send_encrypted_message(
+        &self,
+        sender: &Keypair,
+        recipient: &Pubkey,
+        plaintext: &[u8],
+        message_type: MessageType,
+    ) -> Result<(Pubkey, MessageEnvelope)> {
+        let operation_name = "send_encrypted_message";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let envelope = self.seal_message(recipient, plaintext)?;
+            let payload_hash = solana_sdk::keccak::hash(&envelope.ciphertext).to_bytes();
+            
+            // Direct messages address the recipient's wallet, which also signs its receipts
+            let (sender_agent, _) = derive_agent_pda_v1(&sender.pubkey())?;
+            let (recipient_agent, _) = derive_agent_pda_v1(recipient)?;
+            let (message_pda, _) = derive_direct_message_pda(
+                &sender_agent,
+                recipient,
+                &payload_hash,
+                message_type_seed(&message_type),
+            )?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::SendMessage {
+                    message_account: message_pda,
+                    recipient_inbox: derive_inbox_pda(&recipient_agent)?.0,
+                    recipient_agent,
+                    sender_agent,
+                    session_key: None,
+                    signer: sender.pubkey(),
+                    system_program: solana_sdk::system_program::id(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::SendEncryptedMessage {
+                    recipient: *recipient,
+                    payload_hash,
+                    message_type,
+                    ephemeral_pubkey: envelope.ephemeral_pubkey,
+                    envelope_nonce: envelope.nonce,
+                })
+                .signer(sender);
+
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                message_address = %message_pda,
+                recipient = %recipient,
+                signature = %signature,
+                sender = %sender.pubkey(),
+                "Encrypted message sent successfully"
+            );
+
+            Ok((message_pda, envelope))
+        }).await
+    }
+
+    /// Acknowledge a direct message as `Delivered` or `Read` with a signed receipt.
+    ///
+    /// The recipient signs [`pod_com::message_receipt_bytes`] and the signature is
+    /// verified on-chain by an Ed25519Program instruction placed before the status update.
+    pub async fn acknowledge_message(
+        &self,
+        message_address: &Pubkey,
+        recipient: &Keypair,
+        status: MessageStatus,
+    ) -> Result<()> {
+        if !matches!(status, MessageStatus::Delivered | MessageStatus::Read) {
+            return Err(PodComError::UnauthorizedAccess {
+                resource: "message".to_string(),
+                action: "acknowledge".to_string(),
+            });
+        }
+        
+        self.update_message_status(message_address, recipient, status).await
+    }
+
+    /// Move a direct message to `status`, validating the transition client-side first.
+    ///
+    /// `Delivered` and `Read` must be signed by the recipient and carry a receipt;
+    /// `Failed` and `Expired` may be set by either the sender or the recipient.
+    pub async fn update_message_status(
+        &self,
+        message_address: &Pubkey,
+        signer: &Keypair,
+        status: MessageStatus,
+    ) -> Result<()> {
+        let operation_name = "update_message_status";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let message_account = program.account::<MessageAccount>(*message_address)?;
+            
+            // Reject transitions the program would refuse before paying for a transaction
+            let from = sdk_message_status(&message_account.status);
+            let to = sdk_message_status(&status);
+            if !from.can_transition_to(&to) {
+                return Err(MessageError::InvalidStatusTransition {
+                    message_id: *message_address,
+                    from,
+                    to,
+                }.into());
+            }
+            
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)
+                .map_err(|e| PodComError::Internal { message: e.to_string() })?
+                .as_secs() as i64;
+            let is_expired = now > message_account.expires_at;
+            if is_expired != (status == MessageStatus::Expired) {
+                return Err(MessageError::InvalidStatusTransition {
+                    message_id: *message_address,
+                    from,
+                    to,
+                }.into());
+            }
+            
+            let sender_agent = program.account::<AgentAccount>(message_account.sender)?;
+            let is_recipient = message_account.recipient == signer.pubkey();
+            let allowed = match status {
+                MessageStatus::Delivered | MessageStatus::Read => is_recipient,
+                _ => is_recipient || sender_agent.pubkey == signer.pubkey(),
+            };
+            if !allowed {
+                return Err(PodComError::UnauthorizedAccess {
+                    resource: "message".to_string(),
+                    action: "update_status".to_string(),
+                });
+            }
+            
+            // The program seeds `recipient_agent` from the signer; it is only compared with the
+            // message's recipient for receipts
+            let (recipient_agent, _bump) = derive_agent_pda_v1(&signer.pubkey())?;
+            // The inbox that indexed the message belongs to the recipient agent recorded at send time
+            let recipient_inbox = if message_account.inbox_indexed {
+                Some(derive_inbox_pda(&message_account.recipient_agent)?.0)
+            } else {
+                None
+            };
+            
+            let mut request = program.request();
+            if matches!(status, MessageStatus::Delivered | MessageStatus::Read) {
+                let receipt = pod_com::message_receipt_bytes(message_address, &message_account.payload_hash, &status);
+                let receipt_signature: [u8; 64] = signer.sign_message(&receipt).into();
+                // The receipt verification must come immediately before the status update
+                request = request.instruction(
+                    solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature(
+                        &receipt,
+                        &receipt_signature,
+                        &signer.pubkey().to_bytes(),
+                    ),
+                );
+            }
+            
+            let ix = request
+                .accounts(pod_com::accounts::UpdateMessageStatus {
+                    message_account: *message_address,
+                    recipient_agent,
+                    sender_agent: message_account.sender,
+                    recipient_inbox,
+                    signer: signer.pubkey(),
+                    instructions_sysvar: solana_sdk::sysvar::instructions::id(),
+                    protocol_config: derive_protocol_config_pda()?.0,
+                })
+                .args(pod_com::instruction::UpdateMessageStatus { new_status: status.clone() })
+                .signer(signer);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                message_address = %message_address,
+                signature = %signature,
+                signer = %signer.pubkey(),
+                status = %to,
+                "Message status updated successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Edit a channel message within the protocol's edit window (sender only)
+    pub async fn edit_message(
+        &self,
+        message_address: &Pubkey,
+        sender: &Keypair,
+        new_content: String,
+    ) -> Result<()> {
+        let operation_name = "edit_message";
+        
+        self.base.execute_operation(operation_name, async {
+            let program = self.base.program()?;
+            
+            let message_account = program.account::<pod_com::ChannelMessage>(*message_address)?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&sender.pubkey())?;
+            
+            // Channel messages record the sender's agent PDA, not the wallet
+            if message_account.sender != agent_pda {
+                return Err(PodComError::UnauthorizedAccess {
+                    resource: "message".to_string(),
+                    action: "edit".to_string(),
+                });
+            }
+            
+            let (participant_pda, _bump) = derive_participant_pda(&message_account.channel, &agent_pda)?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::EditChannelMessage {
+                    message_account: *message_address,
+                    channel_account: message_account.channel,
+                    participant_account: participant_pda,
+                    agent_account: agent_pda,
+                    session_key: None,
+                    user: sender.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::EditChannelMessage { new_content })
+                .signer(sender);
+
+            // Send transaction
+            let signature = ix.send()?;
+            
+            tracing::info!(
+                message_address = %message_address,
+                signature = %signature,
+                sender = %sender.pubkey(),
+                "Message edited successfully"
+            );
+
+            Ok(())
+        }).await
+    }
+
+    /// Delete a message (sender only); the account is closed and rent refunded to the sender.
+    /// Deleting a reply also decrements its parent's reply count.
     pub async fn delete_message(
         &self,
         message_address: &Pubkey,
@@ -309,21 +892,31 @@ impl MessageService {
         self.base.execute_operation(operation_name, async {
             let program = self.base.program()?;
             
-            let message_account = self.get_message_account(message_address).await?;
+            let message_account = program.account::<pod_com::ChannelMessage>(*message_address)?;
+            let (agent_pda, _bump) = derive_agent_pda_v1(&sender.pubkey())?;
             
-            // Verify sender is the message author
-            if message_account.sender != sender.pubkey() {
+            // Channel messages record the sender's agent PDA, not the wallet
+            if message_account.sender != agent_pda {
                 return Err(PodComError::UnauthorizedAccess {
                     resource: "message".to_string(),
                     action: "delete".to_string(),
                 });
             }
             
-            // Build instruction - Note: pod-com doesn't have delete_message, this would need custom implementation
-            // For now, we'll return an error indicating this feature needs implementation
-            return Err(PodComError::NotImplemented {
-                feature: "delete_message".to_string(),
-            });
+            let (protocol_config, _) = derive_protocol_config_pda()?;
+            
+            // Build instruction
+            let ix = program
+                .request()
+                .accounts(pod_com::accounts::DeleteChannelMessage {
+                    message_account: *message_address,
+                    parent_message: message_account.reply_to,
+                    agent_account: agent_pda,
+                    user: sender.pubkey(),
+                    protocol_config,
+                })
+                .args(pod_com::instruction::DeleteChannelMessage {})
+                .signer(sender);
 
             // Send transaction
             let signature = ix.send()?;
@@ -339,52 +932,122 @@ impl MessageService {
         }).await
     }
 
-    /// Clean up expired messages for a channel
+    /// Clean up expired messages.
+    ///
+    /// Direct messages are not tied to a channel, so `channel_address` is ignored and
+    /// every expired message is closed.
+    #[deprecated(note = "use `close_expired_messages`, which can bound the number of closes")]
     pub async fn cleanup_expired_messages(
         &self,
-        channel_address: &Pubkey,
+        _channel_address: &Pubkey,
+        cleaner: &Keypair,
+    ) -> Result<Vec<Pubkey>> {
+        self.close_expired_messages(cleaner, None).await
+    }
+
+    /// Close expired direct messages, returning their rent to the original payers.
+    /// Closes are batched several per transaction; returns the closed message addresses.
+    ///
+    /// Only each message's `expires_at` is downloaded to find candidates; full accounts
+    /// are fetched for at most `max_messages` of them.
+    pub async fn close_expired_messages(
+        &self,
         cleaner: &Keypair,
+        max_messages: Option<usize>,
     ) -> Result<Vec<Pubkey>> {
-        let operation_name = "cleanup_expired_messages";
+        let operation_name = "close_expired_messages";
         
         self.base.execute_operation(operation_name, async {
             let program = self.base.program()?;
-            
-            // Verify channel access
-            let channel_account = program.account::<ChannelAccount>(*channel_address)?;
-            if !channel_account.participants.contains(&cleaner.pubkey()) {
-                return Err(PodComError::UnauthorizedAccess {
-                    resource: "channel".to_string(),
-                    action: "cleanup_messages".to_string(),
-                });
-            }
+            let rpc = program.rpc();
             
             let current_timestamp = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
-                .as_secs();
+                .as_secs() as i64;
+            
+            // Memcmp can only match bytes, so slice out expires_at and compare it here
+            let expiry_slices = rpc
+                .get_program_accounts_with_config(
+                    &pod_com::ID,
+                    RpcProgramAccountsConfig {
+                        filters: Some(vec![
+                            RpcFilterType::DataSize(MESSAGE_ACCOUNT_SIZE as u64),
+                            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, MessageAccount::DISCRIMINATOR)),
+                        ]),
+                        account_config: RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64),
+                            data_slice: Some(UiDataSliceConfig {
+                                offset: MESSAGE_EXPIRES_AT_OFFSET,
+                                length: 8,
+                            }),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
+                    },
+                )
+                .await
+                .map_err(|e| NetworkError::RpcFailed {
+                    method: "getProgramAccounts".to_string(),
+                    reason: e.to_string(),
+                })?;
+            let mut expired: Vec<Pubkey> = expiry_slices
+                .into_iter()
+                .filter(|(_, account)| {
+                    account.data.get(..8).is_some_and(|bytes| {
+                        current_timestamp > i64::from_le_bytes(bytes.try_into().unwrap())
+                    })
+                })
+                .map(|(address, _)| address)
+                .collect();
+            if let Some(max_messages) = max_messages {
+                expired.truncate(max_messages);
+            }
             
-            // Find expired messages
-            let all_messages = self.list_channel_messages(channel_address, cleaner, None, None).await?;
+            let (protocol_config, _) = derive_protocol_config_pda()?;
             let mut cleaned_messages = Vec::new();
             
-            for (message_address, message_account) in all_messages {
-                if message_account.expiration_timestamp > 0 && 
-                   current_timestamp > message_account.expiration_timestamp {
-                    
-                    // Build cleanup instruction - Note: pod-com doesn't have cleanup_expired_message
-                    // For now, we'll skip this message and continue
-                    tracing::info!("Skipping cleanup of expired message - feature not implemented in pod-com program");
-                    continue;
+            for batch in expired.chunks(CLEANUP_BATCH_SIZE) {
+                // Build one close instruction per message and send them together
+                let mut request = program.request();
+                let mut closing = Vec::with_capacity(batch.len());
+                for message_address in batch {
+                    // Skip messages another cleaner closed in the meantime
+                    let Ok(message_account) = program.account::<MessageAccount>(*message_address) else {
+                        continue;
+                    };
+                    closing.push(*message_address);
+                    let instructions = program
+                        .request()
+                        .accounts(pod_com::accounts::CloseExpiredMessage {
+                            message_account: *message_address,
+                            payer: message_account.payer,
+                            closer: cleaner.pubkey(),
+                            protocol_config,
+                        })
+                        .args(pod_com::instruction::CloseExpiredMessage {})
+                        .instructions()?;
+                    for ix in instructions {
+                        request = request.instruction(ix);
+                    }
+                }
 
-                    // Send transaction
-                    let _signature = ix.send()?;
-                    cleaned_messages.push(message_address);
+                if closing.is_empty() {
+                    continue;
                 }
+
+                // Send transaction
+                let signature = request.signer(cleaner).send()?;
+                
+                tracing::debug!(
+                    signature = %signature,
+                    batch_size = closing.len(),
+                    "Expired message batch closed"
+                );
+                cleaned_messages.extend(closing);
             }
             
             tracing::info!(
-                channel_address = %channel_address,
                 cleaned_count = cleaned_messages.len(),
                 cleaner = %cleaner.pubkey(),
                 "Expired messages cleaned up successfully"
@@ -447,6 +1110,126 @@ impl MessageService {
     }
 }
 
+/// Message type byte used in direct message PDA seeds
+fn message_type_seed(message_type: &MessageType) -> u8 {
+    match message_type {
+        MessageType::Text => 0,
+        MessageType::Data => 1,
+        MessageType::Command => 2,
+        MessageType::Response => 3,
+        MessageType::Custom(x) => 4 + x,
+    }
+}
+
+/// Unroll an inbox ring buffer into its live entries, newest first
+pub fn inbox_entries_newest_first(inbox: &pod_com::AgentInbox) -> Vec<pod_com::InboxEntry> {
+    let capacity = pod_com::INBOX_CAPACITY as u64;
+    let count = inbox.total_received.min(capacity);
+    (1..=count)
+        .map(|age| inbox.entries[((inbox.total_received - age) % capacity) as usize].clone())
+        .collect()
+}
+
+/// Map an on-chain message status onto the SDK type, which shares its transition table
+pub fn sdk_message_status(status: &MessageStatus) -> pod_sdk_types::MessageStatus {
+    match status {
+        MessageStatus::Pending => pod_sdk_types::MessageStatus::Pending,
+        MessageStatus::Delivered => pod_sdk_types::MessageStatus::Delivered,
+        MessageStatus::Read => pod_sdk_types::MessageStatus::Read,
+        MessageStatus::Failed => pod_sdk_types::MessageStatus::Failed,
+        MessageStatus::Expired => pod_sdk_types::MessageStatus::Expired,
+    }
+}
+
+/// Check a recipient's receipt signature, as emitted in `MessageStatusChanged`
+pub fn verify_delivery_receipt(
+    message_address: &Pubkey,
+    payload_hash: &[u8; 32],
+    status: &MessageStatus,
+    recipient: &Pubkey,
+    receipt_signature: &[u8; 64],
+) -> bool {
+    let receipt = pod_com::message_receipt_bytes(message_address, payload_hash, status);
+    Signature::from(*receipt_signature).verify(recipient.as_ref(), &receipt)
+}
+
+/// Decode a `CompressedMessageAppended` event from a `Program data:` log line
+fn decode_compressed_message_event(line: &str) -> Option<pod_com::CompressedMessageAppended> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(line.strip_prefix("Program data: ")?)
+        .ok()?;
+    let mut body = data.strip_prefix(pod_com::CompressedMessageAppended::DISCRIMINATOR)?;
+    pod_com::CompressedMessageAppended::deserialize(&mut body).ok()
+}
+
+/// A message in a reply chain, stored either as an account or as a compressed leaf
+pub enum ThreadMessage {
+    /// `ChannelMessage` account
+    Regular {
+        address: Pubkey,
+        message: pod_com::ChannelMessage,
+    },
+    /// Compressed message, as recorded by its append event
+    Compressed(pod_com::CompressedMessageAppended),
+}
+
+impl ThreadMessage {
+    /// Id replies refer to: the account address, or the leaf hash of a compressed message
+    pub fn id(&self) -> Pubkey {
+        match self {
+            ThreadMessage::Regular { address, .. } => *address,
+            ThreadMessage::Compressed(event) => Pubkey::new_from_array(event.leaf_hash),
+        }
+    }
+
+    /// Parent message id, if this is a reply
+    pub fn reply_to(&self) -> Option<Pubkey> {
+        match self {
+            ThreadMessage::Regular { message, .. } => message.reply_to,
+            ThreadMessage::Compressed(event) => event.reply_to,
+        }
+    }
+
+    /// Creation timestamp (Unix seconds)
+    pub fn created_at(&self) -> i64 {
+        match self {
+            ThreadMessage::Regular { message, .. } => message.created_at,
+            ThreadMessage::Compressed(event) => event.timestamp,
+        }
+    }
+}
+
+/// A thread root with all of its direct and nested replies
+pub struct Thread {
+    pub root: ThreadMessage,
+    /// Replies at any depth, oldest first
+    pub replies: Vec<ThreadMessage>,
+}
+
+/// Bounds for scanning a channel's transaction logs for compressed messages
+#[derive(Debug, Clone, Copy, Default)]
+struct CompressedScan {
+    /// Only keep messages replying to this id
+    reply_to: Option<Pubkey>,
+    /// Stop at transactions older than this unix timestamp
+    not_before: Option<i64>,
+    /// Stop once this many messages have been kept
+    max_matches: Option<usize>,
+}
+
+/// Offset/limit window for [`MessageService::list_replies`]
+#[derive(Debug, Clone, Copy)]
+pub struct ReplyPage {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for ReplyPage {
+    fn default() -> Self {
+        Self { offset: 0, limit: 50 }
+    }
+}
+
 /// Message statistics
 #[derive(Debug, Clone)]
 pub struct MessageStats {
@@ -584,6 +1367,11 @@ impl BaseService for MessageService {
 
 // Constants for message handling
 const MAX_UNCOMPRESSED_MESSAGE_SIZE: usize = 8192; // 8KB
+const CLEANUP_BATCH_SIZE: usize = 8; // close_expired_message instructions per transaction
+const MESSAGE_ACCOUNT_SIZE: usize = 248; // MessageAccount space, discriminator included
+const MESSAGE_EXPIRES_AT_OFFSET: usize = 8 + 32 * 5 + 8; // discriminator, five keys/hashes, created_at
+const CHANNEL_MESSAGE_THREAD_ROOT_OFFSET: usize = 8 + 32 + 32; // discriminator + channel + sender
+const CHANNEL_MESSAGE_REPLY_TO_OFFSET: usize = CHANNEL_MESSAGE_THREAD_ROOT_OFFSET + 32;
 
 #[cfg(test)]
 mod tests {
@@ -597,4 +1385,95 @@ mod tests {
         assert_eq!(service.service_name(), "message");
         assert_eq!(service.health_check(), ServiceHealth::NotInitialized);
     }
+
+    #[test]
+    fn test_delivery_receipt_verification() {
+        let recipient = Keypair::new();
+        let message = Pubkey::new_unique();
+        let payload_hash = [9u8; 32];
+
+        let receipt = pod_com::message_receipt_bytes(&message, &payload_hash, &MessageStatus::Read);
+        let signature: [u8; 64] = recipient.sign_message(&receipt).into();
+
+        assert!(verify_delivery_receipt(&message, &payload_hash, &MessageStatus::Read, &recipient.pubkey(), &signature));
+        // A read receipt does not double as a delivery receipt, nor for another payload
+        assert!(!verify_delivery_receipt(&message, &payload_hash, &MessageStatus::Delivered, &recipient.pubkey(), &signature));
+        assert!(!verify_delivery_receipt(&message, &[0u8; 32], &MessageStatus::Read, &recipient.pubkey(), &signature));
+    }
+
+    #[test]
+    fn test_message_status_table_matches_program() {
+        use anchor_lang::AnchorSerialize;
+
+        let onchain = [
+            MessageStatus::Pending,
+            MessageStatus::Delivered,
+            MessageStatus::Read,
+            MessageStatus::Failed,
+            MessageStatus::Expired,
+        ];
+
+        for from in &onchain {
+            let sdk_from = sdk_message_status(from);
+            // Same Borsh discriminant on both sides
+            assert_eq!(from.try_to_vec().unwrap(), borsh::to_vec(&sdk_from).unwrap());
+            assert_eq!(from.is_terminal(), sdk_from.is_terminal());
+
+            for to in &onchain {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    sdk_from.can_transition_to(&sdk_message_status(to)),
+                    "{:?} -> {:?}", from, to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_inbox_entries_newest_first() {
+        // A zeroed account body is an empty inbox
+        let body = vec![0u8; 32 + 8 + 48 * pod_com::INBOX_CAPACITY + 1 + 7];
+        let mut inbox = pod_com::AgentInbox::deserialize(&mut body.as_slice()).unwrap();
+        assert!(inbox_entries_newest_first(&inbox).is_empty());
+
+        // Wrap the ring buffer by three messages
+        let sent: Vec<Pubkey> = (0..pod_com::INBOX_CAPACITY + 3).map(|_| Pubkey::new_unique()).collect();
+        for (i, message) in sent.iter().enumerate() {
+            inbox.entries[i % pod_com::INBOX_CAPACITY].message = *message;
+            inbox.total_received += 1;
+        }
+
+        let entries = inbox_entries_newest_first(&inbox);
+        assert_eq!(entries.len(), pod_com::INBOX_CAPACITY);
+        assert_eq!(entries[0].message, *sent.last().unwrap());
+        assert_eq!(entries.last().unwrap().message, sent[3]);
+    }
+
+    #[test]
+    fn test_decode_compressed_message_event() {
+        use anchor_lang::AnchorSerialize;
+
+        let event = pod_com::CompressedMessageAppended {
+            channel: Pubkey::new_unique(),
+            sender: Pubkey::new_unique(),
+            merkle_tree: Pubkey::new_unique(),
+            leaf_hash: [7u8; 32],
+            leaf_index: 3,
+            root: [1u8; 32],
+            content_hash: [2u8; 32],
+            ipfs_hash: "bafy".to_string(),
+            message_type: MessageType::Text,
+            reply_to: Some(Pubkey::new_unique()),
+            key_epoch: 0,
+            timestamp: 1_700_000_000,
+        };
+        let mut data = pod_com::CompressedMessageAppended::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        let line = format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(&data));
+
+        let decoded = decode_compressed_message_event(&line).expect("event should decode");
+        assert_eq!(decoded.reply_to, event.reply_to);
+        assert_eq!(ThreadMessage::Compressed(decoded).id(), Pubkey::new_from_array([7u8; 32]));
+        assert!(decode_compressed_message_event("Program log: hello").is_none());
+    }
 } 
\ No newline at end of file
@@ -74,13 +74,34 @@ pub struct ZKCompressionAccount {
     pub created_at: i64,
 }
 
-/// Derive agent PDA
+/// Derive a named (v2) agent PDA (`[b"agent", owner, name]`).
+/// An empty name derives the same address as [`derive_agent_pda_v1`].
 pub fn derive_agent_pda(owner: &Pubkey, name: &str) -> Result<(Pubkey, u8)> {
     let seeds = &[b"agent", owner.as_ref(), name.as_bytes()];
     let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
     Ok((pda, bump))
 }
 
+/// Derive the program's original wallet-keyed agent PDA (`[b"agent", wallet]`)
+pub fn derive_agent_pda_v1(wallet: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"agent", wallet.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive the tombstone PDA that carries a closed agent's reputation (`[b"agent_tombstone", agent]`)
+pub fn derive_agent_tombstone_pda(agent: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"agent_tombstone", agent.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive protocol config PDA (singleton holding governance-tunable limits)
+pub fn derive_protocol_config_pda() -> Result<(Pubkey, u8)> {
+    let (pda, bump) = Pubkey::find_program_address(&[b"protocol_config"], &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
 /// Derive channel PDA
 pub fn derive_channel_pda(creator: &Pubkey, channel_id: &str) -> Result<(Pubkey, u8)> {
     let seeds = &[b"channel", creator.as_ref(), channel_id.as_bytes()];
@@ -95,6 +116,38 @@ pub fn derive_message_pda(channel: &Pubkey, message_id: &str) -> Result<(Pubkey,
     Ok((pda, bump))
 }
 
+/// Derive a channel message PDA (matches the program's
+/// `[b"channel_message", channel, author_wallet, nonce]` seeds)
+pub fn derive_channel_message_pda(channel: &Pubkey, author: &Pubkey, nonce: u64) -> Result<(Pubkey, u8)> {
+    let seeds = &[
+        b"channel_message".as_ref(),
+        channel.as_ref(),
+        author.as_ref(),
+        &nonce.to_le_bytes(),
+    ];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive a direct message PDA (matches the program's
+/// `[b"message", sender_agent, recipient, payload_hash, [message_type]]` seeds)
+pub fn derive_direct_message_pda(
+    sender_agent: &Pubkey,
+    recipient: &Pubkey,
+    payload_hash: &[u8; 32],
+    message_type_seed: u8,
+) -> Result<(Pubkey, u8)> {
+    let seeds = &[
+        b"message".as_ref(),
+        sender_agent.as_ref(),
+        recipient.as_ref(),
+        payload_hash.as_ref(),
+        &[message_type_seed],
+    ];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
 /// Derive escrow PDA
 pub fn derive_escrow_pda(payer: &Pubkey, escrow_id: &str) -> Result<(Pubkey, u8)> {
     let seeds = &[b"escrow", payer.as_ref(), escrow_id.as_bytes()];
@@ -102,6 +155,72 @@ pub fn derive_escrow_pda(payer: &Pubkey, escrow_id: &str) -> Result<(Pubkey, u8)
     Ok((pda, bump))
 }
 
+/// Derive channel escrow PDA (matches the program's `[b"escrow", channel, depositor]` seeds)
+pub fn derive_channel_escrow_pda(channel: &Pubkey, depositor: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"escrow", channel.as_ref(), depositor.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive escrow dispute PDA (matches the program's `[b"escrow_dispute", escrow]` seeds)
+pub fn derive_escrow_dispute_pda(escrow: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"escrow_dispute", escrow.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive channel participant PDA (matches the program's `[b"participant", channel, agent]` seeds)
+pub fn derive_participant_pda(channel: &Pubkey, agent: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"participant", channel.as_ref(), agent.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive channel invitation PDA (matches the program's `[b"invitation", channel, invitee]` seeds)
+pub fn derive_invitation_pda(channel: &Pubkey, invitee: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"invitation", channel.as_ref(), invitee.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive invite code PDA (matches the program's `[b"invite_code", channel, code_signer]` seeds)
+pub fn derive_invite_code_pda(channel: &Pubkey, code_signer: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"invite_code", channel.as_ref(), code_signer.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive session key PDA (matches the program's `[b"session_key", agent, session_signer]` seeds)
+pub fn derive_session_key_pda(agent: &Pubkey, session_signer: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"session_key", agent.as_ref(), session_signer.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive a recipient agent's inbox index PDA (matches the program's `[b"inbox", recipient_agent]` seeds)
+pub fn derive_inbox_pda(recipient_agent: &Pubkey) -> Result<(Pubkey, u8)> {
+    let seeds = &[b"inbox", recipient_agent.as_ref()];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
+/// Derive message reaction PDA (matches the program's
+/// `[b"reaction", message, reactor_agent, [reaction index]]` seeds)
+pub fn derive_reaction_pda(
+    message: &Pubkey,
+    reactor_agent: &Pubkey,
+    reaction: pod_com::ReactionKind,
+) -> Result<(Pubkey, u8)> {
+    let seeds = &[
+        b"reaction".as_ref(),
+        message.as_ref(),
+        reactor_agent.as_ref(),
+        &[reaction.index() as u8],
+    ];
+    let (pda, bump) = Pubkey::find_program_address(seeds, &crate::PROGRAM_ID);
+    Ok((pda, bump))
+}
+
 /// Derive IPFS metadata PDA
 pub fn derive_ipfs_metadata_pda(uploader: &Pubkey, metadata_id: &str) -> Result<(Pubkey, u8)> {
     let seeds = &[b"ipfs_metadata", uploader.as_ref(), metadata_id.as_bytes()];
@@ -767,6 +886,63 @@ mod tests {
         assert_ne!(pda1, pda3);
     }
 
+    #[test]
+    fn test_channel_escrow_pda_derivation() {
+        let channel = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+
+        let (pda1, bump1) = derive_channel_escrow_pda(&channel, &depositor).unwrap();
+        let (pda2, bump2) = derive_channel_escrow_pda(&channel, &depositor).unwrap();
+        assert_eq!(pda1, pda2);
+        assert_eq!(bump1, bump2);
+
+        // Each depositor gets a distinct escrow per channel
+        let (pda3, _) = derive_channel_escrow_pda(&channel, &Pubkey::new_unique()).unwrap();
+        assert_ne!(pda1, pda3);
+
+        // Disputes are keyed by escrow address
+        let (dispute1, _) = derive_escrow_dispute_pda(&pda1).unwrap();
+        let (dispute2, _) = derive_escrow_dispute_pda(&pda3).unwrap();
+        assert_ne!(dispute1, dispute2);
+    }
+
+    #[test]
+    fn test_named_agent_pda_derivation() {
+        let owner = Pubkey::new_unique();
+
+        let (alpha, _) = derive_agent_pda(&owner, "alpha").unwrap();
+        let (beta, _) = derive_agent_pda(&owner, "beta").unwrap();
+        let (v1, _) = derive_agent_pda_v1(&owner).unwrap();
+        assert_ne!(alpha, beta);
+        assert_ne!(alpha, v1);
+        assert_eq!(derive_agent_pda(&owner, "").unwrap().0, v1);
+    }
+
+    #[test]
+    fn test_invite_code_pda_derivation() {
+        let channel = Pubkey::new_unique();
+        let code_a = Pubkey::new_unique();
+        let code_b = Pubkey::new_unique();
+
+        let (pda_a, _) = derive_invite_code_pda(&channel, &code_a).unwrap();
+        let (pda_b, _) = derive_invite_code_pda(&channel, &code_b).unwrap();
+        assert_ne!(pda_a, pda_b);
+        assert_eq!(pda_a, derive_invite_code_pda(&channel, &code_a).unwrap().0);
+    }
+
+    #[test]
+    fn test_reaction_pda_derivation() {
+        use pod_com::ReactionKind;
+
+        let message = Pubkey::new_unique();
+        let reactor = Pubkey::new_unique();
+
+        let (thumbs_up, _) = derive_reaction_pda(&message, &reactor, ReactionKind::ThumbsUp).unwrap();
+        let (heart, _) = derive_reaction_pda(&message, &reactor, ReactionKind::Heart).unwrap();
+        assert_ne!(thumbs_up, heart);
+        assert_eq!(thumbs_up, derive_reaction_pda(&message, &reactor, ReactionKind::ThumbsUp).unwrap().0);
+    }
+
     #[test]
     fn test_account_size_validation() {
         let test_data = [0u8; 100];
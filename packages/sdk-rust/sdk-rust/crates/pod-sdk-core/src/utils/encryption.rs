@@ -2,9 +2,82 @@
 //!
 //! Encryption and decryption utilities for the PoD Protocol.
 
+use std::collections::BTreeMap;
+
 use crate::error::Result;
-use pod_sdk_crypto::{SymmetricEncryption, KeyDerivation, CryptoError, Hash};
-use solana_sdk::pubkey::Pubkey;
+use pod_sdk_crypto::{SymmetricEncryption, KeyDerivation, KeyExchange, CryptoError, Hash, SecureRng};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signer::{keypair::Keypair, Signer}};
+
+/// HKDF context for direct-message envelope keys
+const MESSAGE_ENVELOPE_INFO: &[u8] = b"PoD-Protocol-Message-Envelope-v1";
+
+/// End-to-end encrypted direct-message payload.
+///
+/// `ephemeral_pubkey` and `nonce` are stored on the message account by
+/// `send_encrypted_message`; the ciphertext travels off-chain and is bound to
+/// the account through its `payload_hash`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageEnvelope {
+    /// Sender's one-time X25519 public key
+    pub ephemeral_pubkey: [u8; 32],
+    /// ChaCha20Poly1305 nonce
+    pub nonce: [u8; 12],
+    /// Encrypted payload (authenticated, with the ephemeral key as associated data)
+    pub ciphertext: Vec<u8>,
+}
+
+/// One epoch of a private channel's group key, sealed to every member.
+///
+/// The bundle is uploaded to IPFS and its CID published on the channel with
+/// `rotate_channel_key`; members open their own entry with
+/// [`open_channel_key_bundle`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelKeyBundle {
+    /// Channel the key belongs to
+    pub channel: Pubkey,
+    /// Epoch recorded on every message encrypted under this key
+    pub key_epoch: u32,
+    /// The channel key sealed to each member wallet
+    pub wrapped_keys: Vec<WrappedChannelKey>,
+}
+
+/// A channel key sealed to a single member
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedChannelKey {
+    /// Member's owner wallet
+    pub member: Pubkey,
+    /// Channel key sealed with [`seal_message_envelope`]
+    pub envelope: MessageEnvelope,
+}
+
+/// Channel keys a member has opened, indexed by key epoch
+#[derive(Debug, Clone, Default)]
+pub struct ChannelKeyring {
+    keys: BTreeMap<u32, [u8; 32]>,
+}
+
+impl ChannelKeyring {
+    /// Create an empty keyring
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the key for `key_epoch`
+    pub fn insert(&mut self, key_epoch: u32, key: [u8; 32]) {
+        self.keys.insert(key_epoch, key);
+    }
+
+    /// Key a message stamped with `key_epoch` was encrypted under
+    pub fn key_for_epoch(&self, key_epoch: u32) -> Option<&[u8; 32]> {
+        self.keys.get(&key_epoch)
+    }
+
+    /// Newest key, used for outgoing messages
+    pub fn latest(&self) -> Option<(u32, &[u8; 32])> {
+        self.keys.iter().next_back().map(|(epoch, key)| (*epoch, key))
+    }
+}
 
 /// Encrypt message content using AES-256-GCM
 pub fn encrypt_message(content: &[u8], key: &[u8]) -> Result<Vec<u8>> {
@@ -106,7 +179,11 @@ pub fn decrypt_content(content: &[u8], key: &[u8]) -> Result<Vec<u8>> {
     Ok(plaintext)
 }
 
-/// Generate channel encryption key from participant public keys
+/// Generate channel encryption key from participant public keys.
+///
+/// The key is computed from public keys only and never changes when members
+/// leave. Private channels should use [`generate_channel_key_material`] with
+/// [`seal_channel_key_bundle`] instead.
 pub fn generate_channel_key(participants: &[Pubkey]) -> Result<Vec<u8>> {
     if participants.is_empty() {
         return Err(crate::error::PodError::CryptoError("No participants provided".to_string()));
@@ -132,7 +209,113 @@ pub fn generate_channel_key(participants: &[Pubkey]) -> Result<Vec<u8>> {
     Ok(channel_key)
 }
 
-/// Derive shared key between participants using HKDF
+/// Generate a random key for a new channel key epoch
+pub fn generate_channel_key_material() -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    SecureRng::new()
+        .and_then(|mut rng| rng.fill_bytes(&mut key))
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Channel key generation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Seal `key` to every member wallet for publication as epoch `key_epoch`
+pub fn seal_channel_key_bundle(
+    channel: &Pubkey,
+    key_epoch: u32,
+    key: &[u8; 32],
+    members: &[Pubkey],
+) -> Result<ChannelKeyBundle> {
+    if members.is_empty() {
+        return Err(crate::error::PodError::CryptoError("No participants provided".to_string()));
+    }
+    
+    let wrapped_keys = members
+        .iter()
+        .map(|member| {
+            Ok(WrappedChannelKey {
+                member: *member,
+                envelope: seal_message_envelope(key, member)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    Ok(ChannelKeyBundle { channel: *channel, key_epoch, wrapped_keys })
+}
+
+/// Open `member`'s entry in a channel key bundle
+pub fn open_channel_key_bundle(bundle: &ChannelKeyBundle, member: &Keypair) -> Result<[u8; 32]> {
+    let wrapped = bundle
+        .wrapped_keys
+        .iter()
+        .find(|wrapped| wrapped.member == member.pubkey())
+        .ok_or_else(|| crate::error::PodError::CryptoError(
+            format!("No key for member in epoch {}", bundle.key_epoch),
+        ))?;
+    
+    open_message_envelope(&wrapped.envelope, member)?
+        .try_into()
+        .map_err(|_| crate::error::PodError::CryptoError("Invalid key length".to_string()))
+}
+
+/// Seal `plaintext` for `recipient` (the recipient agent's owner wallet).
+///
+/// Uses ECDH between a fresh ephemeral X25519 key and the recipient's ed25519
+/// key converted to X25519, so only the recipient's keypair can open it.
+pub fn seal_message_envelope(plaintext: &[u8], recipient: &Pubkey) -> Result<MessageEnvelope> {
+    let recipient_x25519 = KeyExchange::ed25519_public_to_x25519(&recipient.to_bytes())
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Invalid recipient key: {}", e)))?;
+    let (ephemeral_secret, ephemeral_pubkey) = KeyExchange::generate_ephemeral()
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Ephemeral key generation failed: {}", e)))?;
+    
+    let key = envelope_key(&ephemeral_secret, &recipient_x25519, &ephemeral_pubkey, &recipient_x25519)?;
+    let nonce = SymmetricEncryption::generate_nonce()
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Nonce generation failed: {}", e)))?;
+    
+    let ciphertext = SymmetricEncryption::encrypt_chacha20poly1305(&key, &nonce, plaintext, Some(&ephemeral_pubkey))
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Encryption failed: {}", e)))?;
+    
+    Ok(MessageEnvelope { ephemeral_pubkey, nonce, ciphertext })
+}
+
+/// Open an envelope sealed for `recipient` by [`seal_message_envelope`]
+pub fn open_message_envelope(envelope: &MessageEnvelope, recipient: &Keypair) -> Result<Vec<u8>> {
+    let seed: [u8; 32] = recipient.to_bytes()[..32].try_into()
+        .map_err(|_| crate::error::PodError::CryptoError("Invalid keypair".to_string()))?;
+    let recipient_secret = KeyExchange::ed25519_secret_to_x25519(&seed);
+    let recipient_x25519 = KeyExchange::ed25519_public_to_x25519(&recipient.pubkey().to_bytes())
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Invalid recipient key: {}", e)))?;
+    
+    let key = envelope_key(&recipient_secret, &envelope.ephemeral_pubkey, &envelope.ephemeral_pubkey, &recipient_x25519)?;
+    
+    SymmetricEncryption::decrypt_chacha20poly1305(&key, &envelope.nonce, &envelope.ciphertext, Some(&envelope.ephemeral_pubkey))
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Decryption failed: {}", e)))
+}
+
+/// Derive the envelope key from the ECDH secret, bound to both public keys
+fn envelope_key(
+    secret: &[u8; 32],
+    peer_public: &[u8; 32],
+    ephemeral_pubkey: &[u8; 32],
+    recipient_x25519: &[u8; 32],
+) -> Result<[u8; 32]> {
+    let shared = KeyExchange::diffie_hellman(secret, peer_public)
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Key agreement failed: {}", e)))?;
+    
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_pubkey);
+    salt[32..].copy_from_slice(recipient_x25519);
+    
+    let key = KeyDerivation::hkdf_sha256(&shared, Some(&salt), MESSAGE_ENVELOPE_INFO, 32)
+        .map_err(|e| crate::error::PodError::CryptoError(format!("Envelope key derivation failed: {}", e)))?;
+    key.try_into()
+        .map_err(|_| crate::error::PodError::CryptoError("Invalid key length".to_string()))
+}
+
+/// Derive shared key between participants using HKDF.
+///
+/// The key is computed from public keys only, so anyone who knows the
+/// participants can derive it. Use [`seal_message_envelope`] for confidential
+/// direct messages.
 pub fn derive_shared_key(participants: &[Pubkey]) -> Result<Vec<u8>> {
     if participants.len() < 2 {
         return Err(crate::error::PodError::CryptoError("At least 2 participants required".to_string()));
@@ -242,6 +425,46 @@ mod tests {
         assert_ne!(key1, key3);
     }
 
+    #[test]
+    fn test_message_envelope_roundtrip() {
+        let recipient = Keypair::new();
+        let message = b"Only the recipient agent can read this";
+        
+        let envelope = seal_message_envelope(message, &recipient.pubkey()).expect("Sealing should succeed");
+        assert_ne!(envelope.ciphertext, message.to_vec());
+        
+        let opened = open_message_envelope(&envelope, &recipient).expect("Opening should succeed");
+        assert_eq!(message, opened.as_slice());
+        
+        // Anyone else's keypair fails authentication
+        assert!(open_message_envelope(&envelope, &Keypair::new()).is_err());
+    }
+
+    #[test]
+    fn test_channel_key_bundle_rotation() {
+        let channel = Pubkey::new_unique();
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        
+        let key1 = generate_channel_key_material().expect("Key generation should succeed");
+        let bundle1 = seal_channel_key_bundle(&channel, 1, &key1, &[alice.pubkey(), bob.pubkey()])
+            .expect("Sealing should succeed");
+        assert_eq!(open_channel_key_bundle(&bundle1, &bob).unwrap(), key1);
+        
+        // Bob leaves: the next epoch is sealed to Alice only
+        let key2 = generate_channel_key_material().expect("Key generation should succeed");
+        assert_ne!(key1, key2);
+        let bundle2 = seal_channel_key_bundle(&channel, 2, &key2, &[alice.pubkey()])
+            .expect("Sealing should succeed");
+        assert!(open_channel_key_bundle(&bundle2, &bob).is_err());
+        
+        let mut keyring = ChannelKeyring::new();
+        keyring.insert(1, open_channel_key_bundle(&bundle1, &alice).unwrap());
+        keyring.insert(2, open_channel_key_bundle(&bundle2, &alice).unwrap());
+        assert_eq!(keyring.key_for_epoch(1), Some(&key1));
+        assert_eq!(keyring.latest(), Some((2, &key2)));
+    }
+
     #[test]
     fn test_password_key_derivation() {
         let password = "test_password_123";
@@ -397,6 +397,55 @@ impl KeyDerivation {
     }
 }
 
+/// X25519 key agreement over Solana ed25519 keys
+pub struct KeyExchange;
+
+impl KeyExchange {
+    /// Convert an ed25519 public key to its X25519 (Montgomery) form
+    pub fn ed25519_public_to_x25519(public_key: &[u8; 32]) -> Result<[u8; 32], CryptoError> {
+        use curve25519_dalek::edwards::CompressedEdwardsY;
+        
+        let point = CompressedEdwardsY(*public_key)
+            .decompress()
+            .ok_or(CryptoError::InvalidPublicKey)?;
+        
+        // Small-order points would make the shared secret predictable
+        if point.is_small_order() {
+            return Err(CryptoError::InvalidPublicKey);
+        }
+        
+        Ok(point.to_montgomery().to_bytes())
+    }
+    
+    /// Convert an ed25519 secret seed to the matching X25519 secret scalar
+    pub fn ed25519_secret_to_x25519(secret_seed: &[u8; 32]) -> [u8; 32] {
+        use sha2::{Digest, Sha512};
+        
+        // Same expansion ed25519 uses for signing; x25519 clamps the scalar on use
+        let hash = Sha512::digest(secret_seed);
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&hash[..32]);
+        scalar
+    }
+    
+    /// Generate an ephemeral X25519 keypair, returned as (secret, public)
+    pub fn generate_ephemeral() -> Result<([u8; 32], [u8; 32]), CryptoError> {
+        let mut secret = [0u8; 32];
+        SecureRng::new()?.fill_bytes(&mut secret)?;
+        let public = x25519_dalek::x25519(secret, x25519_dalek::X25519_BASEPOINT_BYTES);
+        Ok((secret, public))
+    }
+    
+    /// X25519 Diffie-Hellman; rejects non-contributory (all-zero) results
+    pub fn diffie_hellman(secret: &[u8; 32], public: &[u8; 32]) -> Result<[u8; 32], CryptoError> {
+        let shared = x25519_dalek::x25519(*secret, *public);
+        if utils::constant_time_eq(&shared, &[0u8; 32]) {
+            return Err(CryptoError::InvalidPublicKey);
+        }
+        Ok(shared)
+    }
+}
+
 /// Utility functions
 pub mod utils {
     use super::*;
@@ -646,6 +695,28 @@ mod tests {
         assert_ne!(buffer1, buffer2);
     }
 
+    #[test]
+    fn test_x25519_key_agreement_from_ed25519() {
+        use solana_sdk::signer::Signer;
+        
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let seed = |kp: &Keypair| -> [u8; 32] { kp.to_bytes()[..32].try_into().unwrap() };
+        let public = |kp: &Keypair| -> [u8; 32] { kp.pubkey().to_bytes() };
+        
+        let alice_secret = KeyExchange::ed25519_secret_to_x25519(&seed(&alice));
+        let bob_secret = KeyExchange::ed25519_secret_to_x25519(&seed(&bob));
+        let alice_public = KeyExchange::ed25519_public_to_x25519(&public(&alice)).unwrap();
+        let bob_public = KeyExchange::ed25519_public_to_x25519(&public(&bob)).unwrap();
+        
+        let shared_ab = KeyExchange::diffie_hellman(&alice_secret, &bob_public).unwrap();
+        let shared_ba = KeyExchange::diffie_hellman(&bob_secret, &alice_public).unwrap();
+        assert_eq!(shared_ab, shared_ba);
+        
+        // Low-order inputs are rejected
+        assert!(KeyExchange::diffie_hellman(&alice_secret, &[0u8; 32]).is_err());
+    }
+
     #[test]
     fn test_constant_time_comparison() {
         let data1 = b"Hello, World!";
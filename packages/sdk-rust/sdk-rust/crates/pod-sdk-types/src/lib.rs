@@ -514,6 +514,38 @@ pub enum MessageStatus {
     Expired,
 }
 
+impl MessageStatus {
+    /// All statuses, in on-chain discriminant order
+    pub const ALL: [MessageStatus; 5] = [
+        MessageStatus::Pending,
+        MessageStatus::Delivered,
+        MessageStatus::Read,
+        MessageStatus::Failed,
+        MessageStatus::Expired,
+    ];
+
+    /// Check whether the program accepts moving from this status to `next`.
+    ///
+    /// Mirrors the on-chain table: Pending -> Delivered -> Read, and
+    /// Pending/Delivered -> Failed or Expired.
+    pub fn can_transition_to(&self, next: &MessageStatus) -> bool {
+        matches!(
+            (self, next),
+            (MessageStatus::Pending, MessageStatus::Delivered)
+                | (MessageStatus::Delivered, MessageStatus::Read)
+                | (
+                    MessageStatus::Pending | MessageStatus::Delivered,
+                    MessageStatus::Failed | MessageStatus::Expired
+                )
+        )
+    }
+
+    /// Check whether no further transitions are possible
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, MessageStatus::Read | MessageStatus::Failed | MessageStatus::Expired)
+    }
+}
+
 impl fmt::Display for MessageStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -770,6 +802,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_message_status_transitions() {
+        assert!(MessageStatus::Pending.can_transition_to(&MessageStatus::Delivered));
+        assert!(MessageStatus::Delivered.can_transition_to(&MessageStatus::Read));
+        assert!(MessageStatus::Delivered.can_transition_to(&MessageStatus::Failed));
+        assert!(MessageStatus::Pending.can_transition_to(&MessageStatus::Expired));
+
+        assert!(!MessageStatus::Pending.can_transition_to(&MessageStatus::Read));
+        assert!(!MessageStatus::Read.can_transition_to(&MessageStatus::Failed));
+        assert!(!MessageStatus::Delivered.can_transition_to(&MessageStatus::Pending));
+
+        for status in MessageStatus::ALL {
+            let has_next = MessageStatus::ALL.iter().any(|next| status.can_transition_to(next));
+            assert_eq!(status.is_terminal(), !has_next);
+        }
+    }
+
     #[test]
     fn test_message_type_display() {
         assert_eq!(MessageType::Text.to_string(), "text");
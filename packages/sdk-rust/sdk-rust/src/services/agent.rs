@@ -3,7 +3,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::Signature,
     transaction::Transaction,
@@ -14,7 +14,7 @@ use solana_client::rpc_client::RpcClient;
 use chrono::{DateTime, Utc};
 
 use crate::{Config, PodError, Agent};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{BaseService, ServiceContext, TransactionResult, account_utils, instruction_utils};
 
 /// Agent capabilities represented as a bitmask
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -202,6 +202,66 @@ impl AgentService {
         self.send_transaction(&context, vec![instruction]).await
     }
 
+    /// Start transferring the wallet's agent to `new_owner`.
+    /// Passing `Pubkey::default()` cancels a pending transfer.
+    pub async fn transfer_ownership(&self, new_owner: &Pubkey) -> Result<TransactionResult, PodError> {
+        let context = self.get_context()?;
+
+        if !context.has_wallet() {
+            return Err(PodError::InvalidConfig("Wallet required for ownership transfer".to_string()));
+        }
+
+        let wallet_pubkey = context.wallet_pubkey().unwrap();
+        let program_id = &context.config.program_id;
+        let (agent_pda, _) = account_utils::derive_agent_pda(program_id, &wallet_pubkey)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+
+        let instruction = instruction_utils::create_program_instruction(
+            program_id,
+            "transfer_agent_ownership",
+            vec![
+                AccountMeta::new(agent_pda, false),
+                AccountMeta::new_readonly(wallet_pubkey, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            new_owner.as_ref(),
+        );
+
+        self.send_transaction(&context, vec![instruction]).await
+    }
+
+    /// Accept a pending transfer of `previous_owner`'s agent to the wallet.
+    /// The agent moves to the wallet's PDA with its reputation intact.
+    pub async fn accept_ownership(&self, previous_owner: &Pubkey) -> Result<TransactionResult, PodError> {
+        let context = self.get_context()?;
+
+        if !context.has_wallet() {
+            return Err(PodError::InvalidConfig("Wallet required to accept ownership".to_string()));
+        }
+
+        let wallet_pubkey = context.wallet_pubkey().unwrap();
+        let program_id = &context.config.program_id;
+        let (previous_agent, _) = account_utils::derive_agent_pda(program_id, previous_owner)?;
+        let (agent_pda, _) = account_utils::derive_agent_pda(program_id, &wallet_pubkey)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+
+        let instruction = instruction_utils::create_program_instruction(
+            program_id,
+            "accept_agent_ownership",
+            vec![
+                AccountMeta::new(previous_agent, false),
+                AccountMeta::new(*previous_owner, false),
+                AccountMeta::new(agent_pda, false),
+                AccountMeta::new(wallet_pubkey, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[],
+        );
+
+        self.send_transaction(&context, vec![instruction]).await
+    }
+
     /// Get agent information by public key
     pub async fn get_agent(&self, agent_pubkey: &Pubkey) -> Result<AgentInfo, PodError> {
         let context = self.get_context()?;
@@ -299,8 +359,24 @@ impl AgentService {
         wallet_pubkey: &Pubkey,
         agent_pda: &Pubkey,
     ) -> Result<Instruction, PodError> {
-        // Placeholder instruction for agent deregistration
-        Ok(system_instruction::transfer(wallet_pubkey, agent_pda, 0))
+        let program_id = &self.get_context()?.config.program_id;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+        let (agent_tombstone, _) = account_utils::derive_agent_tombstone_pda(program_id, agent_pda)?;
+
+        // Closes the agent account and refunds its rent to the wallet; the tombstone keeps
+        // the reputation for a later re-registration
+        Ok(instruction_utils::create_program_instruction(
+            program_id,
+            "close_agent",
+            vec![
+                AccountMeta::new(*agent_pda, false),
+                AccountMeta::new(agent_tombstone, false),
+                AccountMeta::new(*wallet_pubkey, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[],
+        ))
     }
 
     async fn send_transaction(
@@ -81,13 +81,34 @@ impl TransactionResult {
 /// Common instruction building utilities
 pub mod instruction_utils {
     use solana_sdk::{
-        instruction::Instruction,
+        hash::hashv,
+        instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
         system_instruction,
         sysvar,
     };
     use crate::PodError;
 
+    /// Compute the 8-byte Anchor discriminator for a program instruction
+    pub fn instruction_discriminator(instruction_name: &str) -> [u8; 8] {
+        let hash = hashv(&[b"global:", instruction_name.as_bytes()]);
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+
+    /// Build a PoD program instruction from its name and Borsh-encoded arguments
+    pub fn create_program_instruction(
+        program_id: &Pubkey,
+        instruction_name: &str,
+        accounts: Vec<AccountMeta>,
+        args: &[u8],
+    ) -> Instruction {
+        let mut data = instruction_discriminator(instruction_name).to_vec();
+        data.extend_from_slice(args);
+        Instruction::new_with_bytes(*program_id, &data, accounts)
+    }
+
     /// Create a system transfer instruction
     pub fn create_transfer_instruction(
         from: &Pubkey,
@@ -151,6 +172,30 @@ pub mod account_utils {
             .map(|(pubkey, bump)| (pubkey, bump))
     }
 
+    /// Derive a recipient agent's direct-message inbox PDA
+    pub fn derive_inbox_pda(
+        program_id: &Pubkey,
+        recipient_agent: &Pubkey,
+    ) -> Result<(Pubkey, u8), PodError> {
+        Pubkey::find_program_address(
+            &[b"inbox", recipient_agent.as_ref()],
+            program_id,
+        ).map_err(|e| PodError::Solana(format!("Failed to derive inbox PDA: {}", e)))
+            .map(|(pubkey, bump)| (pubkey, bump))
+    }
+
+    /// Derive the tombstone PDA that keeps a closed agent's reputation
+    pub fn derive_agent_tombstone_pda(
+        program_id: &Pubkey,
+        agent_pda: &Pubkey,
+    ) -> Result<(Pubkey, u8), PodError> {
+        Pubkey::find_program_address(
+            &[b"agent_tombstone", agent_pda.as_ref()],
+            program_id,
+        ).map_err(|e| PodError::Solana(format!("Failed to derive agent tombstone PDA: {}", e)))
+            .map(|(pubkey, bump)| (pubkey, bump))
+    }
+
     /// Derive channel PDA
     pub fn derive_channel_pda(
         program_id: &Pubkey,
@@ -163,17 +208,46 @@ pub mod account_utils {
             .map(|(pubkey, bump)| (pubkey, bump))
     }
 
-    /// Derive escrow PDA
+    /// Hash an escrow ID into the 32-byte seed stored on the agent escrow account
+    pub fn escrow_id_seed(escrow_id: &str) -> [u8; 32] {
+        *blake3::hash(escrow_id.as_bytes()).as_bytes()
+    }
+
+    /// Derive agent-to-agent escrow PDA; escrow IDs are scoped to the payer's wallet
     pub fn derive_escrow_pda(
         program_id: &Pubkey,
+        payer: &Pubkey,
         escrow_id: &str,
     ) -> Result<(Pubkey, u8), PodError> {
         Pubkey::find_program_address(
-            &[b"escrow", escrow_id.as_bytes()],
+            &[b"agent_escrow", payer.as_ref(), &escrow_id_seed(escrow_id)],
             program_id,
         ).map_err(|e| PodError::Solana(format!("Failed to derive escrow PDA: {}", e)))
             .map(|(pubkey, bump)| (pubkey, bump))
     }
+
+    /// Derive dispute PDA for an agent-to-agent escrow
+    pub fn derive_escrow_dispute_pda(
+        program_id: &Pubkey,
+        escrow_pda: &Pubkey,
+    ) -> Result<(Pubkey, u8), PodError> {
+        Pubkey::find_program_address(
+            &[b"escrow_dispute", escrow_pda.as_ref()],
+            program_id,
+        ).map_err(|e| PodError::Solana(format!("Failed to derive escrow dispute PDA: {}", e)))
+            .map(|(pubkey, bump)| (pubkey, bump))
+    }
+
+    /// Derive the program-wide protocol config PDA
+    pub fn derive_protocol_config_pda(
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), PodError> {
+        Pubkey::find_program_address(
+            &[b"protocol_config"],
+            program_id,
+        ).map_err(|e| PodError::Solana(format!("Failed to derive protocol config PDA: {}", e)))
+            .map(|(pubkey, bump)| (pubkey, bump))
+    }
 }
 
 #[cfg(test)]
@@ -214,6 +288,15 @@ mod tests {
         assert!(validate_pubkey(&invalid_key).is_err());
     }
 
+    #[test]
+    fn test_instruction_discriminator() {
+        use crate::services::base::instruction_utils::instruction_discriminator;
+
+        let release = instruction_discriminator("release_agent_escrow");
+        assert_eq!(release, instruction_discriminator("release_agent_escrow"));
+        assert_ne!(release, instruction_discriminator("refund_agent_escrow_after_timeout"));
+    }
+
     #[test]
     fn test_agent_pda_derivation() {
         use crate::services::base::account_utils::derive_agent_pda;
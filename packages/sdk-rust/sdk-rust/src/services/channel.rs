@@ -35,6 +35,20 @@ pub enum MemberRole {
     ReadOnly,
 }
 
+impl MemberRole {
+    /// Decode the `role` byte stored on a `ChannelParticipant` account.
+    /// The channel creator is reported as `Owner` by callers, never by the account itself.
+    pub fn from_onchain(role: u8) -> Option<Self> {
+        match role {
+            0 => Some(MemberRole::Member),
+            1 => Some(MemberRole::ReadOnly),
+            2 => Some(MemberRole::Moderator),
+            3 => Some(MemberRole::Admin),
+            _ => None,
+        }
+    }
+}
+
 /// Channel member information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelMember {
@@ -108,6 +122,8 @@ pub struct ChannelSettings {
     pub message_retention_days: Option<u32>,
     pub max_members: Option<u32>,
     pub rate_limit_per_minute: Option<u32>,
+    /// Lamports debited from the sender's channel escrow for every message
+    pub fee_per_message: u64,
 }
 
 impl Default for ChannelSettings {
@@ -119,6 +135,7 @@ impl Default for ChannelSettings {
             message_retention_days: Some(30),
             max_members: Some(1000),
             rate_limit_per_minute: Some(60),
+            fee_per_message: 0,
         }
     }
 }
@@ -314,6 +331,12 @@ impl ChannelService {
             return Err(PodError::InvalidConfig("No permission to send messages in this channel".to_string()));
         }
 
+        // Surface the per-message cost before the escrow is debited
+        let fee = self.get_message_fee(channel_id).await?;
+        if fee > 0 {
+            tracing::info!("Sending to channel {} costs {} lamports", channel_id, fee);
+        }
+
         // Generate message ID
         let message_id = self.generate_message_id(channel_id, &sender, &content);
 
@@ -353,6 +376,12 @@ impl ChannelService {
         self.deserialize_channel_data(&account_data.data, channel_id, &channel_pda)
     }
 
+    /// Get the lamports charged to the sender's escrow for each message in a channel
+    pub async fn get_message_fee(&self, channel_id: &str) -> Result<u64, PodError> {
+        let channel_info = self.get_channel(channel_id).await?;
+        Ok(channel_info.settings.fee_per_message)
+    }
+
     /// Get channel members
     pub async fn get_channel_members(
         &self,
@@ -664,20 +693,56 @@ impl ChannelService {
         channel_id: &str,
         channel_pda: &Pubkey,
     ) -> Result<ChannelInfo, PodError> {
-        // This would deserialize the actual on-chain channel data
-        // For now, return a placeholder
+        // On-chain ChannelAccount layout (after the 8-byte account discriminator):
+        // creator, fee_per_message, escrow_balance, accrued_fees, created_at,
+        // max_participants, current_participants, name, description, visibility, is_active,
+        // last_sync_timestamp, total_compressed_messages, ...
+        let bytes_at = |offset: usize, len: usize| -> Result<&[u8], PodError> {
+            data.get(offset..offset + len)
+                .ok_or_else(|| PodError::Solana("Channel account data too short".to_string()))
+        };
+        let u32_at = |offset: usize| -> Result<u32, PodError> {
+            Ok(u32::from_le_bytes(bytes_at(offset, 4)?.try_into().unwrap()))
+        };
+        let u64_at = |offset: usize| -> Result<u64, PodError> {
+            Ok(u64::from_le_bytes(bytes_at(offset, 8)?.try_into().unwrap()))
+        };
+        let string_at = |offset: usize| -> Result<(String, usize), PodError> {
+            let len = u32_at(offset)? as usize;
+            let text = String::from_utf8_lossy(bytes_at(offset + 4, len)?).into_owned();
+            Ok((text, offset + 4 + len))
+        };
+        let timestamp = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0).unwrap_or_else(Utc::now);
+
+        let owner = Pubkey::new_from_array(bytes_at(8, 32)?.try_into().unwrap());
+        let fee_per_message = u64_at(40)?;
+        let created_at = u64_at(64)? as i64;
+        let max_participants = u32_at(72)?;
+        let current_participants = u32_at(76)?;
+        let (name, offset) = string_at(80)?;
+        let (description, offset) = string_at(offset)?;
+        // ChannelVisibility: Public = 0, Private = 1
+        let is_public = bytes_at(offset, 1)?[0] == 0;
+        let last_sync_timestamp = u64_at(offset + 2)? as i64;
+        let total_compressed_messages = u64_at(offset + 10)?;
+
         Ok(ChannelInfo {
             id: channel_id.to_string(),
-            name: "Channel".to_string(),
-            description: "A channel".to_string(),
-            channel_type: ChannelType::Public,
-            owner: Pubkey::new_unique(),
+            name,
+            description,
+            channel_type: if is_public { ChannelType::Public } else { ChannelType::Private },
+            owner,
             pda: *channel_pda,
-            settings: ChannelSettings::default(),
-            member_count: 0,
-            message_count: 0,
-            created_at: Utc::now(),
-            last_activity: Utc::now(),
+            settings: ChannelSettings {
+                is_public,
+                max_members: Some(max_participants),
+                fee_per_message,
+                ..ChannelSettings::default()
+            },
+            member_count: current_participants as u64,
+            message_count: total_compressed_messages,
+            created_at: timestamp(created_at),
+            last_activity: timestamp(last_sync_timestamp.max(created_at)),
             metadata: HashMap::new(),
         })
     }
@@ -731,6 +796,47 @@ mod tests {
         assert!(!readonly_perms.can_send_messages);
     }
 
+    #[test]
+    fn test_member_role_from_onchain() {
+        assert_eq!(MemberRole::from_onchain(0), Some(MemberRole::Member));
+        assert_eq!(MemberRole::from_onchain(1), Some(MemberRole::ReadOnly));
+        assert_eq!(MemberRole::from_onchain(3), Some(MemberRole::Admin));
+        assert_eq!(MemberRole::from_onchain(4), None);
+    }
+
+    #[test]
+    fn test_channel_settings_default_is_free() {
+        let settings = ChannelSettings::default();
+        assert_eq!(settings.fee_per_message, 0);
+    }
+
+    #[test]
+    fn test_deserialize_channel_fee() {
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&2_500u64.to_le_bytes()); // fee_per_message
+        data.extend_from_slice(&[0u8; 16]); // escrow_balance, accrued_fees
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&100u32.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        for text in ["fleet", "ops"] {
+            data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            data.extend_from_slice(text.as_bytes());
+        }
+        data.extend_from_slice(&[1, 1]); // Private, active
+        data.extend_from_slice(&[0u8; 16]);
+
+        let pda = Pubkey::new_unique();
+        let info = ChannelService::new().deserialize_channel_data(&data, "fleet", &pda).unwrap();
+        assert_eq!(info.settings.fee_per_message, 2_500);
+        assert_eq!(info.owner, owner);
+        assert_eq!(info.name, "fleet");
+        assert_eq!(info.member_count, 3);
+        assert!(!info.settings.is_public);
+        assert!(ChannelService::new().deserialize_channel_data(&data[..60], "fleet", &pda).is_err());
+    }
+
     #[test]
     fn test_channel_creation_data() {
         let mut metadata = HashMap::new();
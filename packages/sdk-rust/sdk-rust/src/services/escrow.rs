@@ -3,7 +3,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::Signature,
     transaction::Transaction,
@@ -15,7 +15,7 @@ use solana_client::rpc_client::RpcClient;
 use chrono::{DateTime, Utc, Duration};
 
 use crate::{Config, PodError};
-use super::{BaseService, ServiceContext, TransactionResult, account_utils};
+use super::{BaseService, ServiceContext, TransactionResult, account_utils, instruction_utils};
 
 /// Escrow status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -105,6 +105,8 @@ pub struct EscrowCreationData {
     pub conditions: EscrowConditions,
     pub milestones: Vec<EscrowMilestone>,
     pub expiry_date: Option<DateTime<Utc>>,
+    /// On-chain arbitrator set that may resolve disputes on this escrow
+    pub arbitrator_set: Option<Pubkey>,
     pub metadata: HashMap<String, String>,
 }
 
@@ -124,6 +126,7 @@ impl EscrowCreationData {
             conditions: EscrowConditions::default(),
             milestones: Vec::new(),
             expiry_date: Some(Utc::now() + Duration::days(30)),
+            arbitrator_set: None,
             metadata: HashMap::new(),
         }
     }
@@ -145,6 +148,7 @@ impl EscrowCreationData {
             conditions: EscrowConditions::default(),
             milestones,
             expiry_date: Some(Utc::now() + Duration::days(60)),
+            arbitrator_set: None,
             metadata: HashMap::new(),
         }
     }
@@ -159,6 +163,11 @@ impl EscrowCreationData {
         self
     }
 
+    pub fn with_arbitrator_set(mut self, arbitrator_set: Pubkey) -> Self {
+        self.arbitrator_set = Some(arbitrator_set);
+        self
+    }
+
     pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
         self.metadata = metadata;
         self
@@ -272,6 +281,7 @@ impl EscrowService {
         // Derive escrow PDA
         let (escrow_pda, bump) = account_utils::derive_escrow_pda(
             &context.config.program_id,
+            &payer,
             &escrow_id,
         )?;
 
@@ -346,7 +356,7 @@ impl EscrowService {
         self.validate_release_conditions(&escrow_info, &releaser)?;
 
         // Create release instruction
-        let instruction = self.create_release_instruction(&releaser, &escrow_info.pda, escrow_id)?;
+        let instruction = self.create_release_instruction(&releaser, &escrow_info)?;
 
         // Send transaction
         let result = self.send_transaction(&context, vec![instruction]).await?;
@@ -374,14 +384,13 @@ impl EscrowService {
         let escrow_info = self.get_escrow(escrow_id).await?;
         
         // Validate milestone completion
-        self.validate_milestone_completion(&escrow_info, milestone_id, &completer)?;
+        let milestone_index = self.validate_milestone_completion(&escrow_info, milestone_id, &completer)?;
 
         // Create complete milestone instruction
         let instruction = self.create_complete_milestone_instruction(
             &completer,
-            &escrow_info.pda,
-            escrow_id,
-            milestone_id,
+            &escrow_info,
+            milestone_index,
         )?;
 
         // Send transaction
@@ -392,7 +401,79 @@ impl EscrowService {
         Ok(result)
     }
 
-    /// Initiate a dispute for an escrow
+    /// Refund unreleased escrow funds to the payer once the escrow has expired
+    pub async fn refund_escrow(&self, escrow_id: &str) -> Result<TransactionResult, PodError> {
+        let context = self.get_context()?;
+        
+        if !context.has_wallet() {
+            return Err(PodError::InvalidConfig("Wallet required to refund escrow".to_string()));
+        }
+
+        let refunder = context.wallet_pubkey().unwrap();
+        
+        // Get escrow info
+        let escrow_info = self.get_escrow(escrow_id).await?;
+        
+        // Validate refund conditions
+        self.validate_refund_conditions(&escrow_info, &refunder)?;
+
+        // Create refund instruction
+        let instruction = self.create_refund_instruction(&refunder, &escrow_info)?;
+
+        // Send transaction
+        let result = self.send_transaction(&context, vec![instruction]).await?;
+        
+        tracing::info!("Escrow {} refunded to payer", escrow_id);
+
+        Ok(result)
+    }
+
+    /// Close a released, refunded or resolved escrow and reclaim its rent
+    pub async fn close_escrow(&self, escrow_id: &str) -> Result<TransactionResult, PodError> {
+        let context = self.get_context()?;
+        
+        if !context.has_wallet() {
+            return Err(PodError::InvalidConfig("Wallet required to close escrow".to_string()));
+        }
+
+        let closer = context.wallet_pubkey().unwrap();
+        
+        // Get escrow info
+        let escrow_info = self.get_escrow(escrow_id).await?;
+        
+        // Resolved escrows also close their dispute, refunding its opener
+        let dispute = match escrow_info.status {
+            EscrowStatus::Released | EscrowStatus::Expired => None,
+            EscrowStatus::Completed => {
+                let (dispute_pda, _) = account_utils::derive_escrow_dispute_pda(
+                    &context.config.program_id,
+                    &escrow_info.pda,
+                )?;
+                let dispute_data = context.rpc_client
+                    .get_account(&dispute_pda)
+                    .map_err(|e| PodError::Solana(format!("Failed to fetch escrow dispute: {}", e)))?;
+                if dispute_data.data.len() < 8 + 96 {
+                    return Err(PodError::Solana("Escrow dispute data too short".to_string()));
+                }
+                // EscrowDispute: escrow, arbitrator_set, opened_by
+                let opened_by = Pubkey::new_from_array(dispute_data.data[72..104].try_into().unwrap());
+                Some((dispute_pda, opened_by))
+            }
+            _ => return Err(PodError::InvalidConfig("Escrow is not settled yet".to_string())),
+        };
+
+        // Create close instruction
+        let instruction = self.create_close_instruction(&closer, &escrow_info.pda, dispute)?;
+
+        // Send transaction
+        let result = self.send_transaction(&context, vec![instruction]).await?;
+        
+        tracing::info!("Escrow {} closed", escrow_id);
+
+        Ok(result)
+    }
+
+    /// Initiate a dispute for an escrow funded by the connected wallet
     pub async fn initiate_dispute(
         &self,
         escrow_id: &str,
@@ -404,18 +485,39 @@ impl EscrowService {
             return Err(PodError::InvalidConfig("Wallet required to initiate dispute".to_string()));
         }
 
+        let payer = context.wallet_pubkey().unwrap();
+        self.initiate_dispute_for(&payer, escrow_id, reason).await
+    }
+
+    /// Initiate a dispute for an escrow funded by `payer`, as either of its parties
+    pub async fn initiate_dispute_for(
+        &self,
+        payer: &Pubkey,
+        escrow_id: &str,
+        reason: String,
+    ) -> Result<TransactionResult, PodError> {
+        let context = self.get_context()?;
+        
+        if !context.has_wallet() {
+            return Err(PodError::InvalidConfig("Wallet required to initiate dispute".to_string()));
+        }
+
         let initiator = context.wallet_pubkey().unwrap();
         
         // Get escrow info
-        let escrow_info = self.get_escrow(escrow_id).await?;
+        let escrow_info = self.get_escrow_for_payer(payer, escrow_id).await?;
         
         // Validate dispute initiation
         if escrow_info.payer != initiator && escrow_info.recipient != initiator {
             return Err(PodError::InvalidConfig("Only payer or recipient can initiate dispute".to_string()));
         }
 
+        if escrow_info.status != EscrowStatus::Funded && escrow_info.status != EscrowStatus::InProgress {
+            return Err(PodError::InvalidConfig("Escrow is not in a disputable state".to_string()));
+        }
+
         // Create dispute instruction
-        let instruction = self.create_dispute_instruction(&initiator, &escrow_info.pda, escrow_id, &reason)?;
+        let instruction = self.create_dispute_instruction(&initiator, &escrow_info.pda, &reason)?;
 
         // Send transaction
         let result = self.send_transaction(&context, vec![instruction]).await?;
@@ -452,12 +554,22 @@ impl EscrowService {
         Ok(result)
     }
 
-    /// Get escrow information
+    /// Get information on an escrow funded by the connected wallet
     pub async fn get_escrow(&self, escrow_id: &str) -> Result<EscrowInfo, PodError> {
         let context = self.get_context()?;
         
+        let payer = context.wallet_pubkey()
+            .ok_or_else(|| PodError::InvalidConfig("Wallet required to look up escrow".to_string()))?;
+        self.get_escrow_for_payer(&payer, escrow_id).await
+    }
+
+    /// Get information on an escrow funded by `payer`
+    pub async fn get_escrow_for_payer(&self, payer: &Pubkey, escrow_id: &str) -> Result<EscrowInfo, PodError> {
+        let context = self.get_context()?;
+        
         let (escrow_pda, _) = account_utils::derive_escrow_pda(
             &context.config.program_id,
+            payer,
             escrow_id,
         )?;
 
@@ -555,15 +667,27 @@ impl EscrowService {
             return Err(PodError::InvalidConfig("Escrow is not in a releasable state".to_string()));
         }
 
-        if escrow_info.conditions.requires_payer_confirmation && escrow_info.payer != *releaser {
-            return Err(PodError::InvalidConfig("Payer confirmation required for release".to_string()));
+        // The program only lets the payer release funds to the recipient
+        if escrow_info.payer != *releaser {
+            return Err(PodError::InvalidConfig("Only payer can release escrow".to_string()));
         }
 
-        if escrow_info.conditions.requires_recipient_confirmation && escrow_info.recipient != *releaser {
-            return Err(PodError::InvalidConfig("Recipient confirmation required for release".to_string()));
+        Ok(())
+    }
+
+    fn validate_refund_conditions(&self, escrow_info: &EscrowInfo, refunder: &Pubkey) -> Result<(), PodError> {
+        if escrow_info.status != EscrowStatus::Funded && escrow_info.status != EscrowStatus::InProgress {
+            return Err(PodError::InvalidConfig("Escrow is not in a refundable state".to_string()));
         }
 
-        Ok(())
+        if escrow_info.payer != *refunder {
+            return Err(PodError::InvalidConfig("Only payer can refund escrow".to_string()));
+        }
+
+        match escrow_info.expiry_date {
+            Some(expiry) if expiry <= Utc::now() => Ok(()),
+            _ => Err(PodError::InvalidConfig("Escrow has not expired yet".to_string())),
+        }
     }
 
     fn validate_milestone_completion(
@@ -571,25 +695,26 @@ impl EscrowService {
         escrow_info: &EscrowInfo,
         milestone_id: &str,
         completer: &Pubkey,
-    ) -> Result<(), PodError> {
+    ) -> Result<u8, PodError> {
         if escrow_info.escrow_type != EscrowType::MilestonePayment {
             return Err(PodError::InvalidConfig("Not a milestone-based escrow".to_string()));
         }
 
-        let milestone = escrow_info.milestones.iter()
-            .find(|m| m.id == milestone_id)
+        let milestone_index = escrow_info.milestones.iter()
+            .position(|m| m.id == milestone_id)
             .ok_or_else(|| PodError::InvalidConfig("Milestone not found".to_string()))?;
+        let milestone = &escrow_info.milestones[milestone_index];
 
         if milestone.status != MilestoneStatus::Pending && milestone.status != MilestoneStatus::InProgress {
             return Err(PodError::InvalidConfig("Milestone is not in a completable state".to_string()));
         }
 
-        // Typically only recipient can mark milestones as complete
-        if escrow_info.recipient != *completer {
-            return Err(PodError::InvalidConfig("Only recipient can complete milestones".to_string()));
+        // Completing a milestone pays it out, so only the payer can approve it
+        if escrow_info.payer != *completer {
+            return Err(PodError::InvalidConfig("Only payer can complete milestones".to_string()));
         }
 
-        Ok(())
+        Ok(milestone_index as u8)
     }
 
     fn validate_escrow_cancellation(&self, escrow_info: &EscrowInfo, canceller: &Pubkey) -> Result<(), PodError> {
@@ -622,14 +747,43 @@ impl EscrowService {
         creation_data: &EscrowCreationData,
     ) -> Result<Instruction, PodError> {
         let context = self.get_context()?;
-        
-        // Placeholder instruction for escrow creation
-        Ok(system_instruction::create_account(
-            payer,
-            escrow_pda,
-            1000000, // Minimum rent-exempt amount
-            1024,    // Account data size
-            &context.config.program_id,
+        let program_id = &context.config.program_id;
+
+        let (payer_agent, _) = account_utils::derive_agent_pda(program_id, payer)?;
+        let (payee_agent, _) = account_utils::derive_agent_pda(program_id, &creation_data.recipient)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+
+        // Milestones default to a single payout when none are given
+        let expires_at = creation_data.expiry_date
+            .unwrap_or_else(|| Utc::now() + Duration::days(30))
+            .timestamp();
+        let milestone_amounts: Vec<u64> = creation_data.milestones.iter()
+            .map(|m| m.amount_lamports)
+            .collect();
+
+        // Borsh-encode (escrow_id: [u8; 32], amount: u64, expires_at: i64, milestone_amounts: Vec<u64>)
+        let mut args = account_utils::escrow_id_seed(escrow_id).to_vec();
+        args.extend_from_slice(&creation_data.amount_lamports.to_le_bytes());
+        args.extend_from_slice(&expires_at.to_le_bytes());
+        args.extend_from_slice(&(milestone_amounts.len() as u32).to_le_bytes());
+        for amount in &milestone_amounts {
+            args.extend_from_slice(&amount.to_le_bytes());
+        }
+
+        Ok(instruction_utils::create_program_instruction(
+            program_id,
+            "create_agent_escrow",
+            vec![
+                AccountMeta::new(*escrow_pda, false),
+                AccountMeta::new(payer_agent, false),
+                AccountMeta::new_readonly(payee_agent, false),
+                // Anchor treats the program ID as "None" for optional accounts
+                AccountMeta::new_readonly(creation_data.arbitrator_set.unwrap_or(*program_id), false),
+                AccountMeta::new_readonly(protocol_config, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            &args,
         ))
     }
 
@@ -646,33 +800,139 @@ impl EscrowService {
     fn create_release_instruction(
         &self,
         releaser: &Pubkey,
-        escrow_pda: &Pubkey,
-        escrow_id: &str,
+        escrow_info: &EscrowInfo,
     ) -> Result<Instruction, PodError> {
-        // Placeholder instruction for releasing escrow
-        Ok(system_instruction::transfer(releaser, releaser, 0))
+        let context = self.get_context()?;
+        let (payee_agent, _) = account_utils::derive_agent_pda(&context.config.program_id, &escrow_info.recipient)?;
+        let (payer_agent, _) = account_utils::derive_agent_pda(&context.config.program_id, &escrow_info.payer)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(&context.config.program_id)?;
+
+        Ok(instruction_utils::create_program_instruction(
+            &context.config.program_id,
+            "release_agent_escrow",
+            vec![
+                AccountMeta::new(escrow_info.pda, false),
+                AccountMeta::new(escrow_info.recipient, false),
+                AccountMeta::new(payee_agent, false),
+                AccountMeta::new(payer_agent, false),
+                AccountMeta::new_readonly(*releaser, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[],
+        ))
     }
 
     fn create_complete_milestone_instruction(
         &self,
         completer: &Pubkey,
+        escrow_info: &EscrowInfo,
+        milestone_index: u8,
+    ) -> Result<Instruction, PodError> {
+        let context = self.get_context()?;
+        let (payee_agent, _) = account_utils::derive_agent_pda(&context.config.program_id, &escrow_info.recipient)?;
+        let (payer_agent, _) = account_utils::derive_agent_pda(&context.config.program_id, &escrow_info.payer)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(&context.config.program_id)?;
+
+        Ok(instruction_utils::create_program_instruction(
+            &context.config.program_id,
+            "complete_agent_escrow_milestone",
+            vec![
+                AccountMeta::new(escrow_info.pda, false),
+                AccountMeta::new(escrow_info.recipient, false),
+                AccountMeta::new(payee_agent, false),
+                AccountMeta::new(payer_agent, false),
+                AccountMeta::new_readonly(*completer, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[milestone_index],
+        ))
+    }
+
+    fn create_refund_instruction(
+        &self,
+        refunder: &Pubkey,
+        escrow_info: &EscrowInfo,
+    ) -> Result<Instruction, PodError> {
+        let context = self.get_context()?;
+        let (payer_agent, _) = account_utils::derive_agent_pda(&context.config.program_id, &escrow_info.payer)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(&context.config.program_id)?;
+
+        Ok(instruction_utils::create_program_instruction(
+            &context.config.program_id,
+            "refund_agent_escrow_after_timeout",
+            vec![
+                AccountMeta::new(escrow_info.pda, false),
+                AccountMeta::new(*refunder, true),
+                AccountMeta::new(payer_agent, false),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[],
+        ))
+    }
+
+    fn create_close_instruction(
+        &self,
+        payer: &Pubkey,
         escrow_pda: &Pubkey,
-        escrow_id: &str,
-        milestone_id: &str,
+        dispute: Option<(Pubkey, Pubkey)>,
     ) -> Result<Instruction, PodError> {
-        // Placeholder instruction for completing milestone
-        Ok(system_instruction::transfer(completer, completer, 0))
+        let context = self.get_context()?;
+        let program_id = &context.config.program_id;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+
+        // Anchor treats the program ID as "None" for optional accounts
+        let (dispute_pda, dispute_opener) = match dispute {
+            Some((dispute_pda, opened_by)) => (
+                AccountMeta::new(dispute_pda, false),
+                AccountMeta::new(opened_by, false),
+            ),
+            None => (
+                AccountMeta::new_readonly(*program_id, false),
+                AccountMeta::new_readonly(*program_id, false),
+            ),
+        };
+
+        Ok(instruction_utils::create_program_instruction(
+            program_id,
+            "close_agent_escrow",
+            vec![
+                AccountMeta::new(*escrow_pda, false),
+                dispute_pda,
+                dispute_opener,
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            &[],
+        ))
     }
 
     fn create_dispute_instruction(
         &self,
         initiator: &Pubkey,
         escrow_pda: &Pubkey,
-        escrow_id: &str,
         reason: &str,
     ) -> Result<Instruction, PodError> {
-        // Placeholder instruction for initiating dispute
-        Ok(system_instruction::transfer(initiator, initiator, 0))
+        let context = self.get_context()?;
+        let program_id = &context.config.program_id;
+
+        let (dispute_pda, _) = account_utils::derive_escrow_dispute_pda(program_id, escrow_pda)?;
+        let (protocol_config, _) = account_utils::derive_protocol_config_pda(program_id)?;
+
+        // Only the reason hash goes on-chain; the text itself stays off-chain
+        let reason_hash = blake3::hash(reason.as_bytes());
+
+        Ok(instruction_utils::create_program_instruction(
+            program_id,
+            "open_escrow_dispute",
+            vec![
+                AccountMeta::new(*escrow_pda, false),
+                AccountMeta::new(dispute_pda, false),
+                AccountMeta::new(*initiator, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(protocol_config, false),
+            ],
+            reason_hash.as_bytes(),
+        ))
     }
 
     fn create_cancel_instruction(
@@ -721,25 +981,76 @@ impl EscrowService {
         escrow_id: &str,
         escrow_pda: &Pubkey,
     ) -> Result<EscrowInfo, PodError> {
-        // This would deserialize the actual on-chain escrow data
-        // For now, return a placeholder
+        // On-chain AgentEscrowAccount layout (after the 8-byte account discriminator)
+        const MAX_MILESTONES: usize = 8;
+        const ACCOUNT_LEN: usize = 8 + 32 * 6 + 8 * 4 + 9 * MAX_MILESTONES + 3;
+
+        if data.len() < ACCOUNT_LEN {
+            return Err(PodError::Solana("Escrow account data too short".to_string()));
+        }
+
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let i64_at = |offset: usize| i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let timestamp = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0).unwrap_or_else(Utc::now);
+
+        let payer_wallet = pubkey_at(8 + 64);
+        let payee_wallet = pubkey_at(8 + 96);
+        let amount = u64_at(168);
+        let released_amount = u64_at(176);
+        let created_at = i64_at(184);
+        let expires_at = i64_at(192);
+        let milestone_count = (data[232 + 9 * MAX_MILESTONES] as usize).min(MAX_MILESTONES);
+        let status_byte = data[233 + 9 * MAX_MILESTONES];
+
+        let milestones: Vec<EscrowMilestone> = (0..milestone_count)
+            .map(|index| {
+                let offset = 232 + 9 * index;
+                let completed = data[offset + 8] != 0;
+                EscrowMilestone {
+                    id: index.to_string(),
+                    description: String::new(),
+                    amount_lamports: u64_at(offset),
+                    due_date: None,
+                    completion_criteria: Vec::new(),
+                    status: if completed { MilestoneStatus::Completed } else { MilestoneStatus::Pending },
+                    completed_at: None,
+                }
+            })
+            .collect();
+
+        // AgentEscrowStatus: Active = 0, Released = 1, Refunded = 2, Disputed = 3, Resolved = 4
+        let status = match status_byte {
+            0 if released_amount > 0 => EscrowStatus::InProgress,
+            0 => EscrowStatus::Funded,
+            1 => EscrowStatus::Released,
+            2 => EscrowStatus::Expired,
+            3 => EscrowStatus::Disputed,
+            4 => EscrowStatus::Completed,
+            _ => return Err(PodError::Solana("Unknown escrow status".to_string())),
+        };
+
         Ok(EscrowInfo {
             id: escrow_id.to_string(),
             pda: *escrow_pda,
-            payer: Pubkey::new_unique(),
-            recipient: Pubkey::new_unique(),
+            payer: payer_wallet,
+            recipient: payee_wallet,
             title: "Escrow".to_string(),
             description: "An escrow".to_string(),
-            escrow_type: EscrowType::SimplePayment,
-            status: EscrowStatus::Created,
-            amount_lamports: 1000000,
-            amount_released: 0,
+            escrow_type: if milestones.is_empty() {
+                EscrowType::SimplePayment
+            } else {
+                EscrowType::MilestonePayment
+            },
+            status,
+            amount_lamports: amount,
+            amount_released: released_amount,
             conditions: EscrowConditions::default(),
-            milestones: Vec::new(),
-            created_at: Utc::now(),
-            funded_at: None,
+            milestones,
+            created_at: timestamp(created_at),
+            funded_at: Some(timestamp(created_at)),
             completed_at: None,
-            expiry_date: Some(Utc::now() + Duration::days(30)),
+            expiry_date: Some(timestamp(expires_at)),
             dispute_info: None,
             metadata: HashMap::new(),
         })
@@ -852,6 +1163,34 @@ mod tests {
         assert_eq!(filter.status, Some(EscrowStatus::Funded));
     }
 
+    #[test]
+    fn test_deserialize_agent_escrow_account() {
+        let service = EscrowService::new();
+        let payer = Pubkey::new_unique();
+        let payee = Pubkey::new_unique();
+
+        let mut data = vec![0u8; 8 + 32 * 6 + 8 * 4 + 9 * 8 + 3];
+        data[72..104].copy_from_slice(payer.as_ref());
+        data[104..136].copy_from_slice(payee.as_ref());
+        data[168..176].copy_from_slice(&1_000u64.to_le_bytes());
+        data[176..184].copy_from_slice(&400u64.to_le_bytes());
+        data[232..240].copy_from_slice(&400u64.to_le_bytes());
+        data[240] = 1;
+        data[241..249].copy_from_slice(&600u64.to_le_bytes());
+        data[304] = 2;
+
+        let info = service
+            .deserialize_escrow_data(&data, "esc_test", &Pubkey::new_unique())
+            .unwrap();
+        assert_eq!(info.payer, payer);
+        assert_eq!(info.recipient, payee);
+        assert_eq!(info.status, EscrowStatus::InProgress);
+        assert_eq!(info.escrow_type, EscrowType::MilestonePayment);
+        assert_eq!(info.milestones.len(), 2);
+        assert_eq!(info.milestones[0].status, MilestoneStatus::Completed);
+        assert_eq!(info.milestones[1].amount_lamports, 600);
+    }
+
     #[test]
     fn test_escrow_conditions_default() {
         let conditions = EscrowConditions::default();
@@ -10,7 +10,11 @@ use solana_sdk::{
     system_instruction,
     signer::Signer,
 };
-use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
 use chrono::{DateTime, Utc};
 use blake3::Hasher;
 
@@ -269,16 +273,41 @@ impl MessageService {
         }
 
         let wallet_pubkey = context.wallet_pubkey().unwrap();
+        let (agent_pda, _) = account_utils::derive_agent_pda(&context.config.program_id, &wallet_pubkey)?;
+        let (inbox_pda, _) = account_utils::derive_inbox_pda(&context.config.program_id, &agent_pda)?;
         
-        // This would use getProgramAccounts to fetch all messages for this wallet
         tracing::info!(
-            "Getting inbox for {} with limit: {:?}, offset: {:?}",
+            "Getting inbox {} for {} with limit: {:?}, offset: {:?}",
+            inbox_pda,
             wallet_pubkey,
             limit,
             offset
         );
         
-        Ok(vec![])
+        // An agent that has never received a direct message has no inbox yet
+        let inbox = context.rpc_client
+            .get_account_with_commitment(&inbox_pda, context.rpc_client.commitment())
+            .map_err(|e| PodError::Solana(format!("Failed to fetch inbox account: {}", e)))?
+            .value;
+        let Some(inbox) = inbox else {
+            return Ok(vec![]);
+        };
+        let entries = decode_inbox_entries(&inbox.data)?;
+        
+        // The inbox holds each entry's current status; message accounts may have been closed since
+        let addresses: Vec<Pubkey> = entries.iter().map(|(address, _)| *address).collect();
+        let accounts = context.rpc_client
+            .get_multiple_accounts(&addresses)
+            .map_err(|e| PodError::Solana(format!("Failed to fetch message accounts: {}", e)))?;
+        let messages = entries
+            .into_iter()
+            .zip(accounts)
+            .filter_map(|((address, status), account)| {
+                account.and_then(|account| decode_message_info(&address, &account.data, Some(status)))
+            })
+            .collect();
+        
+        Ok(paginate(apply_filter(messages, filter.as_ref()), limit, offset))
     }
 
     /// Get sent messages for the current wallet (outbox)
@@ -295,6 +324,7 @@ impl MessageService {
         }
 
         let wallet_pubkey = context.wallet_pubkey().unwrap();
+        let (agent_pda, _) = account_utils::derive_agent_pda(&context.config.program_id, &wallet_pubkey)?;
         
         tracing::info!(
             "Getting outbox for {} with limit: {:?}, offset: {:?}",
@@ -303,7 +333,26 @@ impl MessageService {
             offset
         );
         
-        Ok(vec![])
+        // Direct messages record the sending agent PDA right after the discriminator
+        let accounts = context.rpc_client
+            .get_program_accounts_with_config(
+                &context.config.program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(MESSAGE_ACCOUNT_LEN as u64),
+                        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, agent_pda.as_ref())),
+                    ]),
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| PodError::Solana(format!("Failed to fetch sent messages: {}", e)))?;
+        let mut messages: Vec<MessageInfo> = accounts
+            .into_iter()
+            .filter_map(|(address, account)| decode_message_info(&address, &account.data, None))
+            .collect();
+        messages.sort_by(|a, b| b.message.timestamp.cmp(&a.message.timestamp));
+        
+        Ok(paginate(apply_filter(messages, filter.as_ref()), limit, offset))
     }
 
     /// Mark a message as read
@@ -459,6 +508,98 @@ impl MessageService {
     }
 }
 
+// On-chain MessageAccount and AgentInbox layouts (after the 8-byte account discriminator)
+const MESSAGE_ACCOUNT_LEN: usize = 248;
+const INBOX_CAPACITY: usize = 16;
+const INBOX_ENTRY_LEN: usize = 48;
+const INBOX_ENTRIES_OFFSET: usize = 48;
+
+/// Decode an AgentInbox ring buffer into (message, status byte) pairs, newest first
+fn decode_inbox_entries(data: &[u8]) -> Result<Vec<(Pubkey, u8)>, PodError> {
+    if data.len() < INBOX_ENTRIES_OFFSET + INBOX_CAPACITY * INBOX_ENTRY_LEN {
+        return Err(PodError::Solana("Inbox account data too short".to_string()));
+    }
+    let total_received = u64::from_le_bytes(data[40..48].try_into().unwrap());
+    let count = total_received.min(INBOX_CAPACITY as u64);
+    
+    Ok((1..=count)
+        .map(|age| {
+            let slot = ((total_received - age) % INBOX_CAPACITY as u64) as usize;
+            let offset = INBOX_ENTRIES_OFFSET + slot * INBOX_ENTRY_LEN;
+            let message = Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+            (message, data[offset + 40])
+        })
+        .collect())
+}
+
+/// Decode a direct MessageAccount; `status` overrides the account's own status byte
+fn decode_message_info(address: &Pubkey, data: &[u8], status: Option<u8>) -> Option<MessageInfo> {
+    if data.len() < MESSAGE_ACCOUNT_LEN {
+        return None;
+    }
+    let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+    let i64_at = |offset: usize| i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    let timestamp = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0).unwrap_or_else(Utc::now);
+    
+    // MessageType::Custom carries an extra byte before the status
+    let status_offset = if data[200] == 4 { 202 } else { 201 };
+    // MessageStatus: Pending = 0, Delivered = 1, Read = 2, Failed = 3, Expired = 4
+    let status = match status.unwrap_or(data[status_offset]) {
+        0 => MessageStatus::Pending,
+        1 => MessageStatus::Delivered,
+        2 => MessageStatus::Read,
+        3 => MessageStatus::Failed,
+        4 => MessageStatus::Expired,
+        _ => return None,
+    };
+    let encrypted = data[136..168].iter().any(|byte| *byte != 0);
+    
+    Some(MessageInfo {
+        message: Message {
+            id: address.to_string(),
+            from: pubkey_at(8).to_string(),
+            to: pubkey_at(40).to_string(),
+            // Payloads live off-chain; only their hash is stored
+            content: String::new(),
+            timestamp: timestamp(i64_at(168)),
+            message_type: MessageType::Direct,
+        },
+        pda: *address,
+        status,
+        priority: MessagePriority::Normal,
+        encryption: if encrypted { MessageEncryption::ChaCha20Poly1305 } else { MessageEncryption::None },
+        hash: hex::encode(&data[104..136]),
+        size_bytes: data.len() as u64,
+        expiry_time: Some(timestamp(i64_at(176))),
+        delivery_attempts: 0,
+        created_slot: 0,
+        delivered_slot: None,
+    })
+}
+
+fn apply_filter(messages: Vec<MessageInfo>, filter: Option<&MessageFilter>) -> Vec<MessageInfo> {
+    let Some(filter) = filter else {
+        return messages;
+    };
+    messages
+        .into_iter()
+        .filter(|info| filter.from.is_none_or(|from| info.message.from == from.to_string()))
+        .filter(|info| filter.to.is_none_or(|to| info.message.to == to.to_string()))
+        .filter(|info| filter.status.as_ref().is_none_or(|status| info.status == *status))
+        .filter(|info| filter.priority.as_ref().is_none_or(|priority| info.priority == *priority))
+        .filter(|info| filter.after_time.is_none_or(|after| info.message.timestamp > after))
+        .filter(|info| filter.before_time.is_none_or(|before| info.message.timestamp < before))
+        .collect()
+}
+
+fn paginate(messages: Vec<MessageInfo>, limit: Option<usize>, offset: Option<usize>) -> Vec<MessageInfo> {
+    messages
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
 /// Message statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageStats {
@@ -503,6 +644,23 @@ impl Default for MessageService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_decode_inbox_entries_newest_first() {
+        let mut data = vec![0u8; INBOX_ENTRIES_OFFSET + INBOX_CAPACITY * INBOX_ENTRY_LEN + 8];
+        let messages: Vec<Pubkey> = (0..INBOX_CAPACITY + 2).map(|_| Pubkey::new_unique()).collect();
+        for (index, message) in messages.iter().enumerate() {
+            let offset = INBOX_ENTRIES_OFFSET + (index % INBOX_CAPACITY) * INBOX_ENTRY_LEN;
+            data[offset..offset + 32].copy_from_slice(message.as_ref());
+        }
+        data[40..48].copy_from_slice(&(messages.len() as u64).to_le_bytes());
+
+        let entries = decode_inbox_entries(&data).unwrap();
+        assert_eq!(entries.len(), INBOX_CAPACITY);
+        assert_eq!(entries[0].0, messages[messages.len() - 1]);
+        assert_eq!(entries[INBOX_CAPACITY - 1].0, messages[2]);
+        assert!(decode_inbox_entries(&data[..100]).is_err());
+    }
+
     #[test]
     fn test_message_options_default() {
         let options = MessageOptions::default();
@@ -0,0 +1,149 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  participantPDA,
+  escrowPDA,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  inviteToChannel,
+  depositEscrow,
+  moderate,
+} from "./program-utils";
+
+describe("Closing finished accounts", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+  let muted: Keypair;
+  let mutedAgent: PublicKey;
+  let kicked: Keypair;
+  let kickedAgent: PublicKey;
+  let channel: PublicKey;
+
+  const leaveAndClose = (wallet: Keypair, agent: PublicKey) =>
+    program.methods
+      .leaveAndCloseChannel()
+      .accountsPartial({
+        channelAccount: channel,
+        participantAccount: participantPDA(channel, agent),
+        agentAccount: agent,
+        user: wallet.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([wallet])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+    [muted, mutedAgent] = await registerAgent();
+    [kicked, kickedAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent);
+    await joinChannel(channel, member, memberAgent);
+    await joinChannel(channel, muted, mutedAgent);
+    await joinChannel(channel, kicked, kickedAgent);
+  });
+
+  test("closes the participant record of a member who leaves", async () => {
+    const before = await program.account.channelAccount.fetch(channel);
+
+    await leaveAndClose(member, memberAgent);
+
+    expect(
+      await provider.connection.getAccountInfo(participantPDA(channel, memberAgent)),
+    ).toBeNull();
+    const after = await program.account.channelAccount.fetch(channel);
+    expect(after.currentParticipants).toBe(before.currentParticipants - 1);
+
+    // A closed record is a clean slate, so the member can come back
+    await joinChannel(channel, member, memberAgent);
+  });
+
+  test("keeps the records of muted and kicked participants", async () => {
+    await program.methods
+      .muteParticipant(true)
+      .accountsPartial(moderate(channel, creator, creatorAgent, mutedAgent))
+      .signers([creator])
+      .rpc();
+    await program.methods
+      .kickParticipant()
+      .accountsPartial(moderate(channel, creator, creatorAgent, kickedAgent))
+      .signers([creator])
+      .rpc();
+
+    await expectError(leaveAndClose(muted, mutedAgent), "ParticipantUnderModeration");
+    await expectError(leaveAndClose(kicked, kickedAgent), "ParticipantUnderModeration");
+  });
+
+  test("closes a channel escrow only once it is empty", async () => {
+    const escrow = escrowPDA(channel, member.publicKey);
+    await depositEscrow(channel, member, 10_000);
+
+    const closeEscrow = () =>
+      program.methods
+        .closeEscrow()
+        .accountsPartial({
+          escrowAccount: escrow,
+          channelAccount: channel,
+          depositor: member.publicKey,
+          protocolConfig: protocolConfigPDA,
+        })
+        .signers([member])
+        .rpc();
+
+    await expectError(closeEscrow(), "AccountNotClosable");
+
+    await program.methods
+      .withdrawEscrow(new BN(10_000))
+      .accountsPartial({
+        escrowAccount: escrow,
+        channelAccount: channel,
+        depositor: member.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([member])
+      .rpc();
+    await closeEscrow();
+
+    expect(await provider.connection.getAccountInfo(escrow)).toBeNull();
+  });
+
+  test("closes an invitation only once it has been used", async () => {
+    const privateChannel = await createChannel(creator, creatorAgent, 0, { private: {} });
+    const [invitee, inviteeAgent] = await registerAgent();
+    const invitation = await inviteToChannel(
+      privateChannel,
+      creator,
+      creatorAgent,
+      invitee.publicKey,
+    );
+
+    const closeInvitation = () =>
+      program.methods
+        .closeChannelInvitation()
+        .accountsPartial({
+          invitationAccount: invitation,
+          inviter: creator.publicKey,
+          authority: invitee.publicKey,
+          protocolConfig: protocolConfigPDA,
+        })
+        .signers([invitee])
+        .rpc();
+
+    await expectError(closeInvitation(), "AccountNotClosable");
+
+    await joinChannel(privateChannel, invitee, inviteeAgent, null, invitation);
+    await closeInvitation();
+
+    expect(await provider.connection.getAccountInfo(invitation)).toBeNull();
+  });
+});
@@ -0,0 +1,140 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  pda,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+} from "./program-utils";
+
+describe("Agent escrow", () => {
+  let payer: Keypair;
+  let payerAgent: PublicKey;
+  let payee: Keypair;
+  let payeeAgent: PublicKey;
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [payer, payerAgent] = await registerAgent();
+    [payee, payeeAgent] = await registerAgent();
+  });
+
+  const createEscrow = async (amount: number, milestones: number[]) => {
+    const escrowId = Keypair.generate().publicKey.toBytes();
+    const escrow = pda(
+      Buffer.from("agent_escrow"),
+      payer.publicKey.toBuffer(),
+      Buffer.from(escrowId),
+    );
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+
+    await program.methods
+      .createAgentEscrow(
+        Array.from(escrowId),
+        new BN(amount),
+        new BN(expiresAt),
+        milestones.map((milestone) => new BN(milestone)),
+      )
+      .accountsPartial({
+        escrowAccount: escrow,
+        payerAgent,
+        payeeAgent,
+        arbitratorSet: null,
+        protocolConfig: protocolConfigPDA,
+        payer: payer.publicKey,
+      })
+      .signers([payer])
+      .rpc();
+
+    return escrow;
+  };
+
+  const settleAccounts = (escrow: PublicKey) => ({
+    escrowAccount: escrow,
+    payeeWallet: payee.publicKey,
+    payeeAgent,
+    payerAgent,
+    payer: payer.publicKey,
+    protocolConfig: protocolConfigPDA,
+  });
+
+  const closeEscrow = (escrow: PublicKey) =>
+    program.methods
+      .closeAgentEscrow()
+      .accountsPartial({
+        escrowAccount: escrow,
+        disputeAccount: null,
+        disputeOpener: null,
+        payer: payer.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+
+  test("releases a plain escrow to the payee", async () => {
+    const escrow = await createEscrow(100_000, []);
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+
+    await program.methods
+      .releaseAgentEscrow()
+      .accountsPartial(settleAccounts(escrow))
+      .signers([payer])
+      .rpc();
+
+    const account = await program.account.agentEscrowAccount.fetch(escrow);
+    expect("released" in account.status).toBe(true);
+    expect(await provider.connection.getBalance(payee.publicKey)).toBe(
+      payeeBefore + 100_000,
+    );
+
+    const agent = await program.account.agentAccount.fetch(payerAgent);
+    expect(agent.activeEscrows).toBe(0);
+  });
+
+  test("pays milestones one at a time and settles on the last", async () => {
+    const escrow = await createEscrow(100_000, [40_000, 60_000]);
+    const completeMilestone = (index: number) =>
+      program.methods
+        .completeAgentEscrowMilestone(index)
+        .accountsPartial(settleAccounts(escrow))
+        .signers([payer])
+        .rpc();
+
+    await completeMilestone(0);
+    let account = await program.account.agentEscrowAccount.fetch(escrow);
+    expect(account.releasedAmount.toNumber()).toBe(40_000);
+    expect("active" in account.status).toBe(true);
+
+    await expectError(completeMilestone(0), "InvalidEscrowMilestone");
+
+    await completeMilestone(1);
+    account = await program.account.agentEscrowAccount.fetch(escrow);
+    expect(account.releasedAmount.toNumber()).toBe(100_000);
+    expect("released" in account.status).toBe(true);
+  });
+
+  test("closes only settled escrows and refunds their rent to the payer", async () => {
+    const escrow = await createEscrow(50_000, []);
+    await expectError(closeEscrow(escrow), "AccountNotClosable");
+
+    await program.methods
+      .releaseAgentEscrow()
+      .accountsPartial(settleAccounts(escrow))
+      .signers([payer])
+      .rpc();
+
+    const rent = await provider.connection.getBalance(escrow);
+    const payerBefore = await provider.connection.getBalance(payer.publicKey);
+    await closeEscrow(escrow);
+
+    expect(await provider.connection.getAccountInfo(escrow)).toBeNull();
+    // The payer also covers the transaction fee
+    expect(await provider.connection.getBalance(payer.publicKey)).toBeGreaterThan(
+      payerBefore + rent - 10_000,
+    );
+  });
+});
@@ -0,0 +1,142 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  agentPDA,
+  agentTombstonePDA,
+  pda,
+  fund,
+  METADATA_URI,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  inviteToChannel,
+} from "./program-utils";
+
+describe("Agent deregistration and ownership transfer", () => {
+  let inviter: Keypair;
+  let inviterAgent: PublicKey;
+
+  const closeAgent = (wallet: Keypair, agent: PublicKey) =>
+    program.methods
+      .closeAgent()
+      .accountsPartial({
+        agentAccount: agent,
+        agentTombstone: agentTombstonePDA(agent),
+        signer: wallet.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([wallet])
+      .rpc();
+
+  const reregister = (wallet: Keypair) =>
+    program.methods
+      .registerAgent(new BN(1), METADATA_URI)
+      .accountsPartial({
+        agentAccount: agentPDA(wallet.publicKey),
+        agentTombstone: agentTombstonePDA(agentPDA(wallet.publicKey)),
+        signer: wallet.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([wallet])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [inviter, inviterAgent] = await registerAgent();
+  });
+
+  test("keeps reputation across closing and re-registering", async () => {
+    // Earn some reputation first so a reset to the default would show
+    const channel = await createChannel(inviter, inviterAgent, 0, { private: {} });
+    const [member, memberAgent] = await registerAgent();
+    const invitation = await inviteToChannel(channel, inviter, inviterAgent, member.publicKey);
+    await joinChannel(channel, member, memberAgent, null, invitation, inviterAgent);
+    const earned = (await program.account.agentAccount.fetch(inviterAgent)).reputation;
+    expect(earned.toNumber()).toBeGreaterThan(100);
+
+    await closeAgent(inviter, inviterAgent);
+    expect(await provider.connection.getAccountInfo(inviterAgent)).toBeNull();
+    const tombstone = await program.account.agentTombstone.fetch(
+      agentTombstonePDA(inviterAgent),
+    );
+    expect(tombstone.reputation.toNumber()).toBe(earned.toNumber());
+
+    await reregister(inviter);
+
+    const agent = await program.account.agentAccount.fetch(inviterAgent);
+    expect(agent.reputation.toNumber()).toBe(earned.toNumber());
+    // The tombstone is consumed and its rent returned
+    expect(
+      await provider.connection.getAccountInfo(agentTombstonePDA(inviterAgent)),
+    ).toBeNull();
+  });
+
+  test("refuses to close an agent with unsettled escrows", async () => {
+    const [payer, payerAgent] = await registerAgent();
+    const [, payeeAgent] = await registerAgent();
+    const escrowId = Keypair.generate().publicKey.toBytes();
+
+    await program.methods
+      .createAgentEscrow(
+        Array.from(escrowId),
+        new BN(10_000),
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        [],
+      )
+      .accountsPartial({
+        escrowAccount: pda(
+          Buffer.from("agent_escrow"),
+          payer.publicKey.toBuffer(),
+          Buffer.from(escrowId),
+        ),
+        payerAgent,
+        payeeAgent,
+        arbitratorSet: null,
+        protocolConfig: protocolConfigPDA,
+        payer: payer.publicKey,
+      })
+      .signers([payer])
+      .rpc();
+
+    await expectError(closeAgent(payer, payerAgent), "AgentHasActiveEscrows");
+  });
+
+  test("moves an agent and its reputation to a new wallet", async () => {
+    const [owner, ownerAgent] = await registerAgent();
+    const newOwner = Keypair.generate();
+    await fund(newOwner.publicKey);
+    const movedAgent = agentPDA(newOwner.publicKey);
+
+    await program.methods
+      .transferAgentOwnership(newOwner.publicKey)
+      .accountsPartial({
+        agentAccount: ownerAgent,
+        signer: owner.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([owner])
+      .rpc();
+    await program.methods
+      .acceptAgentOwnership()
+      .accountsPartial({
+        previousAgent: ownerAgent,
+        previousOwner: owner.publicKey,
+        agentAccount: movedAgent,
+        newOwner: newOwner.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([newOwner])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(ownerAgent)).toBeNull();
+    const agent = await program.account.agentAccount.fetch(movedAgent);
+    expect(agent.pubkey.equals(newOwner.publicKey)).toBe(true);
+    expect(agent.reputation.toNumber()).toBe(100);
+  });
+});
@@ -0,0 +1,78 @@
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  escrowPDA,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  depositEscrow,
+  broadcast,
+} from "./program-utils";
+
+describe("Channel fee claims", () => {
+  const FEE = 200_000;
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+  let channel: PublicKey;
+  let escrow: PublicKey;
+  let feeRecipient: PublicKey;
+
+  const claim = (signer: Keypair) =>
+    program.methods
+      .claimChannelFees()
+      .accountsPartial({
+        escrowAccount: escrow,
+        channelAccount: channel,
+        creator: signer.publicKey,
+        protocolConfig: protocolConfigPDA,
+        feeRecipient,
+      })
+      .signers([signer])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent, FEE);
+    escrow = escrowPDA(channel, member.publicKey);
+    feeRecipient = (await program.account.protocolConfig.fetch(protocolConfigPDA))
+      .feeRecipient;
+
+    await depositEscrow(channel, member, 5 * FEE);
+    await joinChannel(channel, member, memberAgent, escrow);
+    await broadcast(channel, member, memberAgent, "paid message", escrow);
+  });
+
+  test("only the channel creator can claim", async () => {
+    await expectError(claim(member), "Unauthorized");
+  });
+
+  test("pays out the accrued fees and leaves the member's balance alone", async () => {
+    const escrowLamportsBefore = await provider.connection.getBalance(escrow);
+
+    await claim(creator);
+
+    const escrowAccount = await program.account.escrowAccount.fetch(escrow);
+    expect(escrowAccount.unclaimedFees.toNumber()).toBe(0);
+    expect(escrowAccount.amount.toNumber()).toBe(4 * FEE);
+    expect(await provider.connection.getBalance(escrow)).toBe(
+      escrowLamportsBefore - FEE,
+    );
+
+    const channelAccount = await program.account.channelAccount.fetch(channel);
+    expect(channelAccount.accruedFees.toNumber()).toBe(0);
+    expect(channelAccount.escrowBalance.toNumber()).toBe(4 * FEE);
+  });
+
+  test("rejects a claim with nothing accrued", async () => {
+    await expectError(claim(creator), "InsufficientFunds");
+  });
+});
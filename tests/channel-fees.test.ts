@@ -0,0 +1,90 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  program,
+  protocolConfigPDA,
+  escrowPDA,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  depositEscrow,
+  broadcast,
+} from "./program-utils";
+
+describe("Channel message fees", () => {
+  const FEE = 1_000;
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+  let channel: PublicKey;
+  let escrow: PublicKey;
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent, FEE);
+    escrow = escrowPDA(channel, member.publicKey);
+  });
+
+  const withdraw = (amount: number) =>
+    program.methods
+      .withdrawEscrow(new BN(amount))
+      .accountsPartial({
+        escrowAccount: escrow,
+        channelAccount: channel,
+        depositor: member.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([member])
+      .rpc();
+
+  test("opens the escrow on the first deposit and tops it up after", async () => {
+    await depositEscrow(channel, member, 5_000);
+    await depositEscrow(channel, member, 2_000);
+
+    const escrowAccount = await program.account.escrowAccount.fetch(escrow);
+    expect(escrowAccount.amount.toNumber()).toBe(7_000);
+    expect(escrowAccount.depositor.equals(member.publicKey)).toBe(true);
+
+    const channelAccount = await program.account.channelAccount.fetch(channel);
+    expect(channelAccount.escrowBalance.toNumber()).toBe(7_000);
+  });
+
+  test("rejects posts to a paid channel without the sender's escrow", async () => {
+    await joinChannel(channel, member, memberAgent, escrow);
+    await expectError(
+      broadcast(channel, member, memberAgent, "free ride"),
+      "InsufficientFunds",
+    );
+  });
+
+  test("moves each fee from the spendable balance to accrued fees", async () => {
+    await broadcast(channel, member, memberAgent, "paid message", escrow);
+
+    const escrowAccount = await program.account.escrowAccount.fetch(escrow);
+    expect(escrowAccount.amount.toNumber()).toBe(7_000 - FEE);
+    expect(escrowAccount.unclaimedFees.toNumber()).toBe(FEE);
+
+    const channelAccount = await program.account.channelAccount.fetch(channel);
+    expect(channelAccount.escrowBalance.toNumber()).toBe(7_000 - FEE);
+    expect(channelAccount.accruedFees.toNumber()).toBe(FEE);
+  });
+
+  test("withdraws the spendable balance but never the accrued fees", async () => {
+    await expectError(withdraw(7_000), "InsufficientFunds");
+    await withdraw(7_000 - FEE);
+
+    const escrowAccount = await program.account.escrowAccount.fetch(escrow);
+    expect(escrowAccount.amount.toNumber()).toBe(0);
+    expect(escrowAccount.unclaimedFees.toNumber()).toBe(FEE);
+
+    const channelAccount = await program.account.channelAccount.fetch(channel);
+    expect(channelAccount.escrowBalance.toNumber()).toBe(0);
+    expect(channelAccount.accruedFees.toNumber()).toBe(FEE);
+  });
+});
@@ -0,0 +1,200 @@
+import * as anchor from "@coral-xyz/anchor";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  ComputeBudgetProgram,
+} from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  participantPDA,
+  expectError,
+  sleep,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  broadcast,
+} from "./program-utils";
+
+const ACCOUNT_COMPRESSION_PROGRAM_ID = new PublicKey(
+  "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK",
+);
+const NOOP_PROGRAM_ID = new PublicKey(
+  "noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV",
+);
+
+// Mirrors MESSAGE_TREE_MAX_DEPTH / MESSAGE_TREE_MAX_BUFFER_SIZE in the program
+const MESSAGE_TREE_MAX_DEPTH = 20;
+const MESSAGE_TREE_MAX_BUFFER_SIZE = 64;
+
+// Same layout as spl-account-compression's getConcurrentMerkleTreeAccountSize (no canopy)
+const treeAccountSize = (maxDepth: number, maxBufferSize: number) => {
+  const header = 2 + 54;
+  const changeLog = 32 + 32 * maxDepth + 4 + 4;
+  const rightmostPath = 32 * maxDepth + 32 + 4 + 4;
+  return header + 8 + 8 + 8 + maxBufferSize * changeLog + rightmostPath;
+};
+
+describe("Channel message tree", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+  let channel: PublicKey;
+  const merkleTree = Keypair.generate();
+
+  const initTree = () =>
+    program.methods
+      .initChannelMessageTree()
+      .accountsPartial({
+        channelAccount: channel,
+        merkleTree: merkleTree.publicKey,
+        authority: creator.publicKey,
+        compressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        noopProgram: NOOP_PROGRAM_ID,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+
+  const compressedBroadcast = (
+    replyTo: PublicKey | null = null,
+    parentMessage: PublicKey | null = null,
+  ) =>
+    program.methods
+      .broadcastMessageCompressed("compressed hello", { text: {} }, replyTo, "QmCompressedHello")
+      .accountsPartial({
+        channelAccount: channel,
+        participantAccount: participantPDA(channel, memberAgent),
+        agentAccount: memberAgent,
+        sessionKey: null,
+        parentMessage,
+        escrowAccount: null,
+        protocolConfig: protocolConfigPDA,
+        feePayer: member.publicKey,
+        authority: member.publicKey,
+        compressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        registeredProgramId: program.programId,
+        noopProgram: NOOP_PROGRAM_ID,
+        accountCompressionAuthority: channel,
+        accountCompressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        merkleTree: merkleTree.publicKey,
+        nullifierQueue: merkleTree.publicKey,
+        cpiAuthorityPda: channel,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+      .signers([member])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent);
+    await joinChannel(channel, member, memberAgent);
+    await joinChannel(channel, creator, creatorAgent);
+
+    // The tree account is allocated up front and handed to the compression program
+    const space = treeAccountSize(MESSAGE_TREE_MAX_DEPTH, MESSAGE_TREE_MAX_BUFFER_SIZE);
+    const allocTree = SystemProgram.createAccount({
+      fromPubkey: creator.publicKey,
+      newAccountPubkey: merkleTree.publicKey,
+      lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+      space,
+      programId: ACCOUNT_COMPRESSION_PROGRAM_ID,
+    });
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(allocTree),
+      [creator, merkleTree],
+    );
+  });
+
+  test("rejects compressed posts before the tree exists", async () => {
+    await expectError(compressedBroadcast(), "MerkleTreeNotInitialized");
+  });
+
+  test("initializes the tree once with the channel PDA as authority", async () => {
+    await initTree();
+
+    const channelAccount = await program.account.channelAccount.fetch(channel);
+    expect(channelAccount.messageTree.equals(merkleTree.publicKey)).toBe(true);
+    expect(channelAccount.messageTreeRoot.some((byte: number) => byte !== 0)).toBe(true);
+
+    await expectError(initTree(), "MerkleTreeAlreadyInitialized");
+  });
+
+  test("appends compressed messages and tracks the new root", async () => {
+    const before = await program.account.channelAccount.fetch(channel);
+
+    await compressedBroadcast();
+    const after = await program.account.channelAccount.fetch(channel);
+    expect(after.totalCompressedMessages.toNumber()).toBe(
+      before.totalCompressedMessages.toNumber() + 1,
+    );
+    expect(after.messageTreeRoot).not.toEqual(before.messageTreeRoot);
+
+    // Wait out the one-second posting cooldown
+    await sleep(1_500);
+    await compressedBroadcast();
+
+    const latest = await program.account.channelAccount.fetch(channel);
+    expect(latest.totalCompressedMessages.toNumber()).toBe(
+      before.totalCompressedMessages.toNumber() + 2,
+    );
+    expect(latest.messageTreeRoot).not.toEqual(after.messageTreeRoot);
+  });
+
+  test("requires a reply's parent message from the same channel", async () => {
+    const parent = await broadcast(channel, creator, creatorAgent, "thread starter");
+    await sleep(1_500);
+
+    await expectError(compressedBroadcast(parent, null), "InvalidReplyTarget");
+
+    // A regular message from another channel can't be replied to here
+    const otherChannel = await createChannel(creator, creatorAgent);
+    await joinChannel(otherChannel, creator, creatorAgent);
+    const foreignParent = await broadcast(otherChannel, creator, creatorAgent, "elsewhere");
+    await expectError(
+      compressedBroadcast(foreignParent, foreignParent),
+      "InvalidReplyTarget",
+    );
+
+    await compressedBroadcast(parent, parent);
+    const parentAccount = await program.account.channelMessage.fetch(parent);
+    expect(parentAccount.replyCount).toBe(1);
+  });
+
+  test("counts batch-synced hashes apart from tree appends", async () => {
+    const before = await program.account.channelAccount.fetch(channel);
+    const hashes = [1, 2, 3].map((seed) => Array(32).fill(seed));
+
+    await program.methods
+      .batchSyncCompressedMessages(hashes, new anchor.BN(Math.floor(Date.now() / 1000)))
+      .accountsPartial({
+        channelAccount: channel,
+        feePayer: creator.publicKey,
+        authority: creator.publicKey,
+        compressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        registeredProgramId: program.programId,
+        noopProgram: NOOP_PROGRAM_ID,
+        accountCompressionAuthority: channel,
+        accountCompressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        merkleTree: merkleTree.publicKey,
+        nullifierQueue: merkleTree.publicKey,
+        cpiAuthorityPda: channel,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+
+    const after = await program.account.channelAccount.fetch(channel);
+    expect(after.totalSyncedMessages).toBe(before.totalSyncedMessages + 3);
+    expect(after.totalCompressedMessages.toNumber()).toBe(
+      before.totalCompressedMessages.toNumber(),
+    );
+  });
+});
@@ -0,0 +1,126 @@
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  program,
+  participantPDA,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  broadcast,
+  moderate,
+} from "./program-utils";
+
+describe("Channel moderation", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let moderator: Keypair;
+  let moderatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+  let muted: Keypair;
+  let mutedAgent: PublicKey;
+  let channel: PublicKey;
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [moderator, moderatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+    [muted, mutedAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent);
+    await joinChannel(channel, creator, creatorAgent);
+    await joinChannel(channel, moderator, moderatorAgent);
+    await joinChannel(channel, member, memberAgent);
+    await joinChannel(channel, muted, mutedAgent);
+
+    await program.methods
+      .setParticipantRole({ moderator: {} })
+      .accountsPartial(moderate(channel, creator, creatorAgent, moderatorAgent))
+      .signers([creator])
+      .rpc();
+  });
+
+  test("lets moderators mute members, who can then no longer post", async () => {
+    await program.methods
+      .muteParticipant(true)
+      .accountsPartial(
+        moderate(
+          channel,
+          moderator,
+          moderatorAgent,
+          mutedAgent,
+          participantPDA(channel, moderatorAgent),
+        ),
+      )
+      .signers([moderator])
+      .rpc();
+
+    const participant = await program.account.channelParticipant.fetch(
+      participantPDA(channel, mutedAgent),
+    );
+    expect(participant.isMuted).toBe(true);
+    await expectError(
+      broadcast(channel, muted, mutedAgent, "still here"),
+      "ParticipantMuted",
+    );
+  });
+
+  test("keeps the record of a kicked participant as a ban", async () => {
+    await program.methods
+      .kickParticipant()
+      .accountsPartial(moderate(channel, creator, creatorAgent, memberAgent))
+      .signers([creator])
+      .rpc();
+
+    const participant = await program.account.channelParticipant.fetch(
+      participantPDA(channel, memberAgent),
+    );
+    expect(participant.isActive).toBe(false);
+    expect(participant.isKicked).toBe(true);
+
+    await expectError(
+      broadcast(channel, member, memberAgent, "let me back in"),
+      "NotInChannel",
+    );
+  });
+
+  test("rejects moderation by plain members", async () => {
+    await expectError(
+      program.methods
+        .muteParticipant(true)
+        .accountsPartial(
+          moderate(
+            channel,
+            muted,
+            mutedAgent,
+            moderatorAgent,
+            participantPDA(channel, mutedAgent),
+          ),
+        )
+        .signers([muted])
+        .rpc(),
+      "InsufficientChannelRole",
+    );
+  });
+
+  test("never lets participants moderate the channel creator", async () => {
+    await expectError(
+      program.methods
+        .kickParticipant()
+        .accountsPartial(
+          moderate(
+            channel,
+            moderator,
+            moderatorAgent,
+            creatorAgent,
+            participantPDA(channel, moderatorAgent),
+          ),
+        )
+        .signers([moderator])
+        .rpc(),
+      "InsufficientChannelRole",
+    );
+  });
+});
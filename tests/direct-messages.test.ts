@@ -0,0 +1,198 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, Keypair, Ed25519Program } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import { createHash } from "crypto";
+import {
+  program,
+  protocolConfigPDA,
+  pda,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+} from "./program-utils";
+
+// Mirrors MESSAGE_RECEIPT_DOMAIN in the program
+const MESSAGE_RECEIPT_DOMAIN = Buffer.from("PoD-Protocol-Message-Receipt-v1");
+// Mirrors DIRECT_MESSAGE_RATE_LIMIT_PER_HOUR in the program (half of INBOX_CAPACITY)
+const DIRECT_MESSAGE_RATE_LIMIT_PER_HOUR = 8;
+
+const inboxPDA = (recipientAgent: PublicKey) =>
+  pda(Buffer.from("inbox"), recipientAgent.toBuffer());
+
+const payloadHash = (text: string) => createHash("sha256").update(text).digest();
+
+const messagePDA = (senderAgent: PublicKey, recipient: PublicKey, hash: Buffer) =>
+  pda(
+    Buffer.from("message"),
+    senderAgent.toBuffer(),
+    recipient.toBuffer(),
+    hash,
+    Buffer.from([0]),
+  );
+
+const sendMessage = async (
+  sender: Keypair,
+  senderAgent: PublicKey,
+  recipient: Keypair,
+  recipientAgent: PublicKey,
+  text: string,
+) => {
+  const hash = payloadHash(text);
+  const message = messagePDA(senderAgent, recipient.publicKey, hash);
+  await program.methods
+    .sendMessage(recipient.publicKey, Array.from(hash), { text: {} })
+    .accountsPartial({
+      messageAccount: message,
+      recipientInbox: inboxPDA(recipientAgent),
+      recipientAgent,
+      senderAgent,
+      sessionKey: null,
+      signer: sender.publicKey,
+      protocolConfig: protocolConfigPDA,
+    })
+    .signers([sender])
+    .rpc();
+  return message;
+};
+
+describe("Direct message inbox", () => {
+  let sender: Keypair;
+  let senderAgent: PublicKey;
+  let recipient: Keypair;
+  let recipientAgent: PublicKey;
+  let message: PublicKey;
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [sender, senderAgent] = await registerAgent();
+    [recipient, recipientAgent] = await registerAgent();
+    message = await sendMessage(sender, senderAgent, recipient, recipientAgent, "inbox");
+  });
+
+  test("indexes the message in the recipient agent's inbox", async () => {
+    const inbox = await program.account.agentInbox.fetch(inboxPDA(recipientAgent));
+    expect(inbox.owner.equals(recipientAgent)).toBe(true);
+    expect(inbox.totalReceived.toNumber()).toBe(1);
+    expect(inbox.entries[0].message.equals(message)).toBe(true);
+    expect("pending" in inbox.entries[0].status).toBe(true);
+
+    const messageAccount = await program.account.messageAccount.fetch(message);
+    expect(messageAccount.inboxIndexed).toBe(true);
+    expect(messageAccount.recipientAgent.equals(recipientAgent)).toBe(true);
+  });
+
+  test("only accepts the inbox of the message's recipient agent", async () => {
+    // Give the sender an inbox of its own to pass in place of the recipient's
+    await sendMessage(recipient, recipientAgent, sender, senderAgent, "reply");
+
+    await expectError(
+      program.methods
+        .updateMessageStatus({ failed: {} })
+        .accountsPartial({
+          messageAccount: message,
+          recipientAgent,
+          senderAgent,
+          recipientInbox: inboxPDA(senderAgent),
+          signer: recipient.publicKey,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          protocolConfig: protocolConfigPDA,
+        })
+        .signers([recipient])
+        .rpc(),
+      "ConstraintSeeds",
+    );
+  });
+
+  test("rate limits the inbox writes of each sender", async () => {
+    const [flooder, flooderAgent] = await registerAgent();
+    for (let i = 0; i < DIRECT_MESSAGE_RATE_LIMIT_PER_HOUR; i++) {
+      await sendMessage(flooder, flooderAgent, recipient, recipientAgent, `flood ${i}`);
+    }
+
+    await expectError(
+      sendMessage(flooder, flooderAgent, recipient, recipientAgent, "one too many"),
+      "RateLimitExceeded",
+    );
+
+    // The ring still holds the messages that came before the flood
+    const inbox = await program.account.agentInbox.fetch(inboxPDA(recipientAgent));
+    expect(inbox.entries.some((entry: any) => entry.message.equals(message))).toBe(true);
+  });
+});
+
+describe("Direct message receipts", () => {
+  let sender: Keypair;
+  let senderAgent: PublicKey;
+  let recipient: Keypair;
+  let recipientAgent: PublicKey;
+  let message: PublicKey;
+  const hash = payloadHash("receipts");
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [sender, senderAgent] = await registerAgent();
+    [recipient, recipientAgent] = await registerAgent();
+    message = await sendMessage(sender, senderAgent, recipient, recipientAgent, "receipts");
+  });
+
+  // Status bytes follow the MessageStatus declaration order
+  const receipt = (signer: Keypair, status: number) =>
+    Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: signer.secretKey,
+      message: Buffer.concat([
+        MESSAGE_RECEIPT_DOMAIN,
+        message.toBuffer(),
+        hash,
+        Buffer.from([status]),
+      ]),
+    });
+
+  const updateStatus = (status: object, receiptIx?: anchor.web3.TransactionInstruction) =>
+    program.methods
+      .updateMessageStatus(status as any)
+      .accountsPartial({
+        messageAccount: message,
+        recipientAgent,
+        senderAgent,
+        recipientInbox: inboxPDA(recipientAgent),
+        signer: recipient.publicKey,
+        instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        protocolConfig: protocolConfigPDA,
+      })
+      .preInstructions(receiptIx ? [receiptIx] : [])
+      .signers([recipient])
+      .rpc();
+
+  test("requires the recipient's signed receipt to mark delivery", async () => {
+    await expectError(updateStatus({ delivered: {} }), "InvalidDeliveryReceipt");
+    await expectError(
+      updateStatus({ delivered: {} }, receipt(sender, 1)),
+      "InvalidDeliveryReceipt",
+    );
+
+    await updateStatus({ delivered: {} }, receipt(recipient, 1));
+
+    const messageAccount = await program.account.messageAccount.fetch(message);
+    expect("delivered" in messageAccount.status).toBe(true);
+    expect(messageAccount.deliveredAt.toNumber()).toBeGreaterThan(0);
+    const inbox = await program.account.agentInbox.fetch(inboxPDA(recipientAgent));
+    expect("delivered" in inbox.entries[0].status).toBe(true);
+  });
+
+  test("rewards the sender once on read and then rejects further transitions", async () => {
+    const before = await program.account.agentAccount.fetch(senderAgent);
+    await updateStatus({ read: {} }, receipt(recipient, 2));
+
+    const after = await program.account.agentAccount.fetch(senderAgent);
+    expect(after.reputation.toNumber()).toBe(before.reputation.toNumber() + 1);
+    const messageAccount = await program.account.messageAccount.fetch(message);
+    expect(messageAccount.readAt.toNumber()).toBeGreaterThan(0);
+    const inbox = await program.account.agentInbox.fetch(inboxPDA(recipientAgent));
+    expect("read" in inbox.entries[0].status).toBe(true);
+
+    await expectError(
+      updateStatus({ delivered: {} }, receipt(recipient, 1)),
+      "InvalidMessageStatusTransition",
+    );
+  });
+});
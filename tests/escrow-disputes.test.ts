@@ -0,0 +1,219 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  authority,
+  protocolConfigPDA,
+  pda,
+  expectError,
+  ensureProtocolInitialized,
+  registerAgent,
+} from "./program-utils";
+
+describe("Agent escrow disputes", () => {
+  let payer: Keypair;
+  let payerAgent: PublicKey;
+  let payee: Keypair;
+  let payeeAgent: PublicKey;
+  const arbitrators = [Keypair.generate(), Keypair.generate()];
+  let arbitratorSet: PublicKey;
+
+  const createArbitratorSet = async (creator: Keypair, members: PublicKey[]) => {
+    const setId = new BN(Date.now());
+    const set = pda(
+      Buffer.from("arbitrator_set"),
+      creator.publicKey.toBuffer(),
+      setId.toArrayLike(Buffer, "le", 8),
+    );
+    await program.methods
+      .createArbitratorSet(setId, members, 2)
+      .accountsPartial({
+        arbitratorSet: set,
+        authority: creator.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+    return set;
+  };
+
+  const createEscrow = async (
+    amount: number,
+    milestones: number[],
+    set: PublicKey = arbitratorSet,
+  ) => {
+    const escrowId = Keypair.generate().publicKey.toBytes();
+    const escrow = pda(
+      Buffer.from("agent_escrow"),
+      payer.publicKey.toBuffer(),
+      Buffer.from(escrowId),
+    );
+    const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+
+    await program.methods
+      .createAgentEscrow(
+        Array.from(escrowId),
+        new BN(amount),
+        new BN(expiresAt),
+        milestones.map((milestone) => new BN(milestone)),
+      )
+      .accountsPartial({
+        escrowAccount: escrow,
+        payerAgent,
+        payeeAgent,
+        arbitratorSet: set,
+        protocolConfig: protocolConfigPDA,
+        payer: payer.publicKey,
+      })
+      .signers([payer])
+      .rpc();
+
+    return escrow;
+  };
+
+  const settleAccounts = (escrow: PublicKey) => ({
+    escrowAccount: escrow,
+    payeeWallet: payee.publicKey,
+    payeeAgent,
+    payerAgent,
+    payer: payer.publicKey,
+    protocolConfig: protocolConfigPDA,
+  });
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [payer, payerAgent] = await registerAgent();
+    [payee, payeeAgent] = await registerAgent();
+    arbitratorSet = await createArbitratorSet(
+      authority,
+      arbitrators.map((arbitrator) => arbitrator.publicKey),
+    );
+  });
+
+  test("only the protocol authority registers arbitrator sets", async () => {
+    await expectError(
+      createArbitratorSet(
+        payer,
+        arbitrators.map((arbitrator) => arbitrator.publicKey),
+      ),
+      "Unauthorized",
+    );
+  });
+
+  test("rejects arbitrator sets that include either party", async () => {
+    const payeeOnSet = await createArbitratorSet(authority, [
+      arbitrators[0].publicKey,
+      payee.publicKey,
+    ]);
+    await expectError(createEscrow(100_000, [], payeeOnSet), "InvalidArbitratorSet");
+
+    const payerAgentOnSet = await createArbitratorSet(authority, [
+      payerAgent,
+      arbitrators[1].publicKey,
+    ]);
+    await expectError(
+      createEscrow(100_000, [], payerAgentOnSet),
+      "InvalidArbitratorSet",
+    );
+  });
+
+  test("freezes a milestone escrow while disputed and settles on a matching quorum", async () => {
+    const escrow = await createEscrow(100_000, [40_000, 60_000]);
+    await program.methods
+      .completeAgentEscrowMilestone(0)
+      .accountsPartial(settleAccounts(escrow))
+      .signers([payer])
+      .rpc();
+
+    const dispute = pda(Buffer.from("escrow_dispute"), escrow.toBuffer());
+    await program.methods
+      .openEscrowDispute(Array.from(new Uint8Array(32).fill(7)))
+      .accountsPartial({
+        escrowAccount: escrow,
+        disputeAccount: dispute,
+        opener: payee.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([payee])
+      .rpc();
+
+    // A disputed escrow can no longer be released by the payer
+    await expectError(
+      program.methods
+        .releaseAgentEscrow()
+        .accountsPartial(settleAccounts(escrow))
+        .signers([payer])
+        .rpc(),
+      "EscrowNotActive",
+    );
+
+    await program.methods
+      .submitEscrowEvidence(
+        Array.from(new Uint8Array(32).fill(9)),
+        "QmDisputeEvidence",
+      )
+      .accountsPartial({
+        escrowAccount: escrow,
+        disputeAccount: dispute,
+        submitter: payer.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+
+    const vote = (arbitrator: Keypair, payeeShareBps: number) =>
+      program.methods
+        .resolveEscrowDispute(payeeShareBps)
+        .accountsPartial({
+          escrowAccount: escrow,
+          disputeAccount: dispute,
+          arbitratorSet,
+          payeeWallet: payee.publicKey,
+          payerWallet: payer.publicKey,
+          payerAgent,
+          payeeAgent,
+          arbitrator: arbitrator.publicKey,
+          protocolConfig: protocolConfigPDA,
+        })
+        .signers([arbitrator])
+        .rpc();
+
+    await expectError(vote(Keypair.generate(), 5_000), "NotArbitrator");
+
+    // Split votes leave the dispute open until two arbitrators agree
+    await vote(arbitrators[0], 10_000);
+    await vote(arbitrators[1], 5_000);
+    let disputeAccount = await program.account.escrowDispute.fetch(dispute);
+    expect(disputeAccount.isResolved).toBe(false);
+    expect(disputeAccount.evidenceCount).toBe(1);
+
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+    await vote(arbitrators[0], 5_000);
+
+    disputeAccount = await program.account.escrowDispute.fetch(dispute);
+    expect(disputeAccount.isResolved).toBe(true);
+    const account = await program.account.agentEscrowAccount.fetch(escrow);
+    expect("resolved" in account.status).toBe(true);
+    // Half of the unreleased 60_000 goes to the payee
+    expect(await provider.connection.getBalance(payee.publicKey)).toBe(
+      payeeBefore + 30_000,
+    );
+
+    // Closing a resolved escrow also closes its dispute
+    await program.methods
+      .closeAgentEscrow()
+      .accountsPartial({
+        escrowAccount: escrow,
+        disputeAccount: dispute,
+        disputeOpener: payee.publicKey,
+        payer: payer.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([payer])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(escrow)).toBeNull();
+    expect(await provider.connection.getAccountInfo(dispute)).toBeNull();
+  });
+});
@@ -0,0 +1,94 @@
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  provider,
+  program,
+  protocolConfigPDA,
+  expectError,
+  sleep,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  inviteToChannel,
+} from "./program-utils";
+
+describe("Channel invitations", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let channel: PublicKey;
+
+  const acceptInvitation = (invitation: PublicKey, invitee: Keypair) =>
+    program.methods
+      .acceptInvitation()
+      .accountsPartial({
+        invitationAccount: invitation,
+        invitee: invitee.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([invitee])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent, 0, { private: {} });
+  });
+
+  test("rejects expiries outside the protocol bound", async () => {
+    const [invitee] = await registerAgent();
+    await expectError(
+      inviteToChannel(channel, creator, creatorAgent, invitee.publicKey, 0),
+      "InvalidInvitationExpiry",
+    );
+  });
+
+  test("accepts an invitation ahead of joining", async () => {
+    const [invitee, inviteeAgent] = await registerAgent();
+    const invitation = await inviteToChannel(channel, creator, creatorAgent, invitee.publicKey);
+
+    await acceptInvitation(invitation, invitee);
+    expect((await program.account.channelInvitation.fetch(invitation)).isAccepted).toBe(true);
+
+    await joinChannel(channel, invitee, inviteeAgent, null, invitation);
+  });
+
+  test("reports expired invitations as expired", async () => {
+    const [invitee, inviteeAgent] = await registerAgent();
+    const invitation = await inviteToChannel(
+      channel,
+      creator,
+      creatorAgent,
+      invitee.publicKey,
+      1,
+    );
+
+    // Let the validator clock move past the one-second expiry
+    await sleep(3_000);
+
+    await expectError(acceptInvitation(invitation, invitee), "InvitationExpired");
+    await expectError(
+      joinChannel(channel, invitee, inviteeAgent, null, invitation),
+      "InvitationExpired",
+    );
+  });
+
+  test("lets the inviter revoke an outstanding invitation", async () => {
+    const [invitee] = await registerAgent();
+    const invitation = await inviteToChannel(channel, creator, creatorAgent, invitee.publicKey);
+
+    await program.methods
+      .revokeInvitation()
+      .accountsPartial({
+        invitationAccount: invitation,
+        channelAccount: channel,
+        inviter: creator.publicKey,
+        authority: creator.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(invitation)).toBeNull();
+  });
+});
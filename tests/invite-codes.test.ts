@@ -0,0 +1,118 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  program,
+  protocolConfigPDA,
+  participantPDA,
+  pda,
+  expectError,
+  sleep,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+} from "./program-utils";
+
+describe("Invite codes", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let channel: PublicKey;
+
+  const createInviteCode = async (code: Keypair, maxUses: number, expiresIn = 3600) => {
+    const inviteCode = pda(
+      Buffer.from("invite_code"),
+      channel.toBuffer(),
+      code.publicKey.toBuffer(),
+    );
+    await program.methods
+      .createInviteCode(code.publicKey, maxUses, new BN(expiresIn))
+      .accountsPartial({
+        channelAccount: channel,
+        participantAccount: null,
+        agentAccount: creatorAgent,
+        inviteCode,
+        protocolConfig: protocolConfigPDA,
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+    return inviteCode;
+  };
+
+  const joinWithCode = (
+    inviteCode: PublicKey,
+    member: Keypair,
+    memberAgent: PublicKey,
+    codeSigner: Keypair,
+  ) =>
+    program.methods
+      .joinChannelWithCode()
+      .accountsPartial({
+        channelAccount: channel,
+        inviteCode,
+        codeSigner: codeSigner.publicKey,
+        participantAccount: participantPDA(channel, memberAgent),
+        agentAccount: memberAgent,
+        escrowAccount: null,
+        user: member.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([member, codeSigner])
+      .rpc();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    channel = await createChannel(creator, creatorAgent, 0, { private: {} });
+  });
+
+  test("rejects codes without a usable max uses", async () => {
+    await expectError(
+      createInviteCode(Keypair.generate(), 0),
+      "InvalidInviteCodeMaxUses",
+    );
+  });
+
+  test("requires the code keypair to co-sign", async () => {
+    const inviteCode = await createInviteCode(Keypair.generate(), 1);
+    const [member, memberAgent] = await registerAgent();
+    await expectError(
+      joinWithCode(inviteCode, member, memberAgent, Keypair.generate()),
+      "InvalidInviteCode",
+    );
+  });
+
+  test("admits joiners until the code is used up", async () => {
+    const code = Keypair.generate();
+    const inviteCode = await createInviteCode(code, 1);
+    const [member, memberAgent] = await registerAgent();
+    await joinWithCode(inviteCode, member, memberAgent, code);
+
+    const participant = await program.account.channelParticipant.fetch(
+      participantPDA(channel, memberAgent),
+    );
+    expect(participant.isActive).toBe(true);
+    const codeAccount = await program.account.channelInviteCode.fetch(inviteCode);
+    expect(codeAccount.uses).toBe(1);
+
+    const [late, lateAgent] = await registerAgent();
+    await expectError(
+      joinWithCode(inviteCode, late, lateAgent, code),
+      "InviteCodeExhausted",
+    );
+  });
+
+  test("reports expired codes as expired", async () => {
+    const code = Keypair.generate();
+    const inviteCode = await createInviteCode(code, 5, 1);
+
+    // Let the validator clock move past the one-second expiry
+    await sleep(3_000);
+
+    const [member, memberAgent] = await registerAgent();
+    await expectError(
+      joinWithCode(inviteCode, member, memberAgent, code),
+      "InviteCodeExpired",
+    );
+  });
+});
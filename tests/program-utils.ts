@@ -0,0 +1,243 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { PodCom } from "../target/types/pod_com";
+import { PublicKey, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { expect } from "bun:test";
+
+// Shared setup for the program test suites. Every suite registers fresh wallets,
+// so the files can share one validator and run in any order.
+export const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+export const program = anchor.workspace.PodCom as Program<PodCom>;
+export const authority = (provider.wallet as anchor.Wallet).payer;
+
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111",
+);
+export const METADATA_URI = "https://example.com/program-test-agent";
+
+export const pda = (...seeds: Buffer[]) =>
+  PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+export const protocolConfigPDA = pda(Buffer.from("protocol_config"));
+
+// v1 agents live at ["agent", wallet], the same address as an empty v2 name
+export const agentPDA = (wallet: PublicKey) =>
+  pda(Buffer.from("agent"), wallet.toBuffer());
+export const agentTombstonePDA = (agent: PublicKey) =>
+  pda(Buffer.from("agent_tombstone"), agent.toBuffer());
+export const participantPDA = (channel: PublicKey, agent: PublicKey) =>
+  pda(Buffer.from("participant"), channel.toBuffer(), agent.toBuffer());
+export const escrowPDA = (channel: PublicKey, depositor: PublicKey) =>
+  pda(Buffer.from("escrow"), channel.toBuffer(), depositor.toBuffer());
+export const invitationPDA = (channel: PublicKey, invitee: PublicKey) =>
+  pda(Buffer.from("invitation"), channel.toBuffer(), invitee.toBuffer());
+
+export const fund = async (wallet: PublicKey, sol = 2) => {
+  const signature = await provider.connection.requestAirdrop(
+    wallet,
+    sol * LAMPORTS_PER_SOL,
+  );
+  const latestBlockhash = await provider.connection.getLatestBlockhash();
+  await provider.connection.confirmTransaction({
+    signature,
+    ...latestBlockhash,
+  });
+};
+
+export const sleep = (ms: number) =>
+  new Promise((resolve) => setTimeout(resolve, ms));
+
+// Rejects unless the program failed with the named PodComError variant
+export const expectError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+  } catch (error: any) {
+    const errorCode = error?.error?.errorCode?.code ?? error?.errorCode?.code;
+    expect(errorCode ?? String(error)).toContain(code);
+    return;
+  }
+  throw new Error(`expected ${code}, but the transaction succeeded`);
+};
+
+// The upgrade authority that deployed the program claims the protocol config once
+export const ensureProtocolInitialized = async () => {
+  const existing =
+    await program.account.protocolConfig.fetchNullable(protocolConfigPDA);
+  if (existing) {
+    return;
+  }
+
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID,
+  );
+  await program.methods
+    .initializeProtocol()
+    .accountsPartial({
+      protocolConfig: protocolConfigPDA,
+      program: program.programId,
+      programData,
+      authority: authority.publicKey,
+    })
+    .rpc();
+};
+
+export const registerAgent = async (): Promise<[Keypair, PublicKey]> => {
+  const wallet = Keypair.generate();
+  await fund(wallet.publicKey, 3);
+  const agent = agentPDA(wallet.publicKey);
+
+  await program.methods
+    .registerAgent(new BN(1), METADATA_URI)
+    .accountsPartial({
+      agentAccount: agent,
+      agentTombstone: agentTombstonePDA(agent),
+      signer: wallet.publicKey,
+      protocolConfig: protocolConfigPDA,
+    })
+    .signers([wallet])
+    .rpc();
+
+  return [wallet, agent];
+};
+
+export const createChannel = async (
+  creator: Keypair,
+  creatorAgent: PublicKey,
+  feePerMessage = 0,
+  visibility: object = { public: {} },
+): Promise<PublicKey> => {
+  const name = `pt-${Keypair.generate().publicKey.toBase58().slice(0, 12)}`;
+  const channel = pda(
+    Buffer.from("channel"),
+    creator.publicKey.toBuffer(),
+    Buffer.from(name),
+  );
+
+  await program.methods
+    .createChannel(name, "program tests", visibility as any, 10, new BN(feePerMessage))
+    .accountsPartial({
+      channelAccount: channel,
+      agentAccount: creatorAgent,
+      protocolConfig: protocolConfigPDA,
+      creator: creator.publicKey,
+    })
+    .signers([creator])
+    .rpc();
+
+  return channel;
+};
+
+export const joinChannel = async (
+  channel: PublicKey,
+  member: Keypair,
+  memberAgent: PublicKey,
+  escrowAccount: PublicKey | null = null,
+  invitationAccount: PublicKey | null = null,
+  inviterAgent: PublicKey | null = null,
+) => {
+  await program.methods
+    .joinChannel()
+    .accountsPartial({
+      channelAccount: channel,
+      participantAccount: participantPDA(channel, memberAgent),
+      agentAccount: memberAgent,
+      invitationAccount,
+      escrowAccount,
+      inviterAgent,
+      user: member.publicKey,
+      protocolConfig: protocolConfigPDA,
+    })
+    .signers([member])
+    .rpc();
+};
+
+// Invites `invitee` as the channel creator; returns the invitation PDA
+export const inviteToChannel = async (
+  channel: PublicKey,
+  creator: Keypair,
+  creatorAgent: PublicKey,
+  invitee: PublicKey,
+  expiresIn = 3600,
+) => {
+  const invitation = invitationPDA(channel, invitee);
+  await program.methods
+    .inviteToChannel(invitee, new BN(Date.now()), new BN(expiresIn))
+    .accountsPartial({
+      channelAccount: channel,
+      participantAccount: null,
+      agentAccount: creatorAgent,
+      invitationAccount: invitation,
+      protocolConfig: protocolConfigPDA,
+      inviter: creator.publicKey,
+    })
+    .signers([creator])
+    .rpc();
+  return invitation;
+};
+
+export const depositEscrow = (channel: PublicKey, depositor: Keypair, amount: number) =>
+  program.methods
+    .depositEscrow(new BN(amount))
+    .accountsPartial({
+      escrowAccount: escrowPDA(channel, depositor.publicKey),
+      channelAccount: channel,
+      protocolConfig: protocolConfigPDA,
+      depositor: depositor.publicKey,
+    })
+    .signers([depositor])
+    .rpc();
+
+// Posts a top-level channel message and returns its PDA; paid channels draw the fee from
+// the member's escrow
+export const broadcast = async (
+  channel: PublicKey,
+  member: Keypair,
+  memberAgent: PublicKey,
+  content: string,
+  escrowAccount: PublicKey | null = null,
+) => {
+  const nonce = new BN(Date.now());
+  const message = pda(
+    Buffer.from("channel_message"),
+    channel.toBuffer(),
+    member.publicKey.toBuffer(),
+    nonce.toArrayLike(Buffer, "le", 8),
+  );
+
+  await program.methods
+    .broadcastMessage(content, { text: {} }, null, nonce)
+    .accountsPartial({
+      channelAccount: channel,
+      participantAccount: participantPDA(channel, memberAgent),
+      agentAccount: memberAgent,
+      sessionKey: null,
+      messageAccount: message,
+      parentMessage: null,
+      escrowAccount,
+      protocolConfig: protocolConfigPDA,
+      user: member.publicKey,
+    })
+    .signers([member])
+    .rpc();
+  return message;
+};
+
+// Accounts for the moderation instructions; the channel creator moderates without a participant record
+export const moderate = (
+  channel: PublicKey,
+  moderator: Keypair,
+  moderatorAgent: PublicKey,
+  targetAgent: PublicKey,
+  moderatorParticipant: PublicKey | null = null,
+) => ({
+  channelAccount: channel,
+  targetParticipant: participantPDA(channel, targetAgent),
+  targetAgent,
+  moderatorParticipant,
+  moderatorAgent,
+  moderator: moderator.publicKey,
+  protocolConfig: protocolConfigPDA,
+});
@@ -0,0 +1,148 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import { expect, test, beforeAll, describe } from "bun:test";
+import {
+  program,
+  authority,
+  protocolConfigPDA,
+  pda,
+  agentTombstonePDA,
+  METADATA_URI,
+  ensureProtocolInitialized,
+  registerAgent,
+  createChannel,
+  joinChannel,
+  inviteToChannel,
+} from "./program-utils";
+
+describe("Reputation from protocol activity", () => {
+  let creator: Keypair;
+  let creatorAgent: PublicKey;
+  let member: Keypair;
+  let memberAgent: PublicKey;
+
+  const reputationOf = async (agent: PublicKey) =>
+    (await program.account.agentAccount.fetch(agent)).reputation.toNumber();
+
+  beforeAll(async () => {
+    await ensureProtocolInitialized();
+    [creator, creatorAgent] = await registerAgent();
+    [member, memberAgent] = await registerAgent();
+  });
+
+  test("credits the inviter when someone else accepts", async () => {
+    const channel = await createChannel(creator, creatorAgent, 0, { private: {} });
+    const invitation = await inviteToChannel(channel, creator, creatorAgent, member.publicKey);
+    const before = await reputationOf(creatorAgent);
+
+    await joinChannel(channel, member, memberAgent, null, invitation, creatorAgent);
+
+    expect(await reputationOf(creatorAgent)).toBe(before + 2);
+  });
+
+  test("never credits a wallet for inviting its own agents", async () => {
+    const channel = await createChannel(creator, creatorAgent, 0, { private: {} });
+    const name = "rep-alt";
+    const altAgent = pda(Buffer.from("agent"), creator.publicKey.toBuffer(), Buffer.from(name));
+    await program.methods
+      .registerAgentV2(name, new BN(1), METADATA_URI)
+      .accountsPartial({
+        agentAccount: altAgent,
+        agentTombstone: agentTombstonePDA(altAgent),
+        signer: creator.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+
+    const invitation = await inviteToChannel(channel, creator, creatorAgent, creator.publicKey);
+    const before = await reputationOf(creatorAgent);
+
+    await joinChannel(channel, creator, altAgent, null, invitation, creatorAgent);
+
+    expect(await reputationOf(creatorAgent)).toBe(before);
+  });
+
+  test("penalises the losing side of an independently arbitrated dispute", async () => {
+    const arbitrators = [Keypair.generate(), Keypair.generate()];
+    const setId = new BN(Date.now());
+    const arbitratorSet = pda(
+      Buffer.from("arbitrator_set"),
+      authority.publicKey.toBuffer(),
+      setId.toArrayLike(Buffer, "le", 8),
+    );
+    await program.methods
+      .createArbitratorSet(
+        setId,
+        arbitrators.map((arbitrator) => arbitrator.publicKey),
+        2,
+      )
+      .accountsPartial({
+        arbitratorSet,
+        authority: authority.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .rpc();
+
+    const escrowId = Keypair.generate().publicKey.toBytes();
+    const escrow = pda(
+      Buffer.from("agent_escrow"),
+      creator.publicKey.toBuffer(),
+      Buffer.from(escrowId),
+    );
+    await program.methods
+      .createAgentEscrow(
+        Array.from(escrowId),
+        new BN(100_000),
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        [],
+      )
+      .accountsPartial({
+        escrowAccount: escrow,
+        payerAgent: creatorAgent,
+        payeeAgent: memberAgent,
+        arbitratorSet,
+        protocolConfig: protocolConfigPDA,
+        payer: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+    const dispute = pda(Buffer.from("escrow_dispute"), escrow.toBuffer());
+    await program.methods
+      .openEscrowDispute(Array.from(new Uint8Array(32).fill(3)))
+      .accountsPartial({
+        escrowAccount: escrow,
+        disputeAccount: dispute,
+        opener: creator.publicKey,
+        protocolConfig: protocolConfigPDA,
+      })
+      .signers([creator])
+      .rpc();
+
+    const payerBefore = await reputationOf(creatorAgent);
+    const payeeBefore = await reputationOf(memberAgent);
+
+    // Both arbitrators refund the payer in full
+    for (const arbitrator of arbitrators) {
+      await program.methods
+        .resolveEscrowDispute(0)
+        .accountsPartial({
+          escrowAccount: escrow,
+          disputeAccount: dispute,
+          arbitratorSet,
+          payeeWallet: member.publicKey,
+          payerWallet: creator.publicKey,
+          payerAgent: creatorAgent,
+          payeeAgent: memberAgent,
+          arbitrator: arbitrator.publicKey,
+          protocolConfig: protocolConfigPDA,
+        })
+        .signers([arbitrator])
+        .rpc();
+    }
+
+    expect(await reputationOf(memberAgent)).toBe(payeeBefore - 25);
+    expect(await reputationOf(creatorAgent)).toBe(payerBefore);
+  });
+});